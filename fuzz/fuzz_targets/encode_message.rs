@@ -0,0 +1,7 @@
+#![no_main]
+use dhcp_parser::DhcpMessage;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|message: DhcpMessage| {
+    dhcp_parser::roundtrip::check_message_round_trip(&message).unwrap();
+});
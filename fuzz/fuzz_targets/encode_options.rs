@@ -0,0 +1,7 @@
+#![no_main]
+use dhcp_parser::options::DhcpOption;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|options: Vec<DhcpOption>| {
+    dhcp_parser::roundtrip::check_encode_round_trip(&options).unwrap();
+});
@@ -0,0 +1,6 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    dhcp_parser::roundtrip::check_parse_round_trip(data).unwrap();
+});
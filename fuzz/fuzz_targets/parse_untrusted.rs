@@ -0,0 +1,14 @@
+#![no_main]
+use dhcp_parser::options::{Limits, ParseConfig};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Must not panic on anything. parse_untrusted would turn a panic into
+    // an error, so go around it to make the fuzzer see one.
+    let _ = dhcp_parser::parse_message_with_config(data, &ParseConfig::default());
+    let limits = Limits { max_options: 16, max_depth: 1, max_decoded_bytes: 256 };
+    let _ = dhcp_parser::parse_message_with_config(data, &ParseConfig { limits, ..ParseConfig::default() });
+    let _ = dhcp_parser::parse_udp_payload(data);
+    let _ = dhcp_parser::options::parse_with_diagnostics(data);
+    let _ = dhcp_parser::preserved::PreservedMessage::parse(data);
+});
@@ -0,0 +1,75 @@
+/* Generated with cbindgen from src/ffi.rs; see that file for details. */
+
+#ifndef DHCP_PARSER_H
+#define DHCP_PARSER_H
+
+#include <stdint.h>
+#include <stddef.h>
+
+/* `dhcp_parse` succeeded */
+#define DHCP_OK 0
+
+/* A pointer argument was null */
+#define DHCP_ERR_NULL -1
+
+/* The bytes aren't a DHCP message */
+#define DHCP_ERR_PARSE -2
+
+/* The parser panicked, which is a bug in this library; the message is
+ * dropped instead of taking the host process down */
+#define DHCP_ERR_PANIC -3
+
+/* A parsed message */
+typedef struct DhcpHandle DhcpHandle;
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Parse the `len` bytes at `buf` as a message and store a handle to it
+ * in `handle`. Returns `DHCP_OK`, or an error code with `handle` set to
+ * null. */
+int32_t dhcp_parse(const uint8_t *buf, size_t len, DhcpHandle **handle);
+
+/* Release a handle from `dhcp_parse`. Null is ignored. */
+void dhcp_free(DhcpHandle *handle);
+
+/* 1 for BOOTREQUEST, 2 for BOOTREPLY */
+uint8_t dhcp_op(const DhcpHandle *handle);
+
+uint32_t dhcp_xid(const DhcpHandle *handle);
+
+/* The client's address, in host byte order */
+uint32_t dhcp_ciaddr(const DhcpHandle *handle);
+
+/* The address offered or assigned, in host byte order */
+uint32_t dhcp_yiaddr(const DhcpHandle *handle);
+
+/* The next server's address, in host byte order */
+uint32_t dhcp_siaddr(const DhcpHandle *handle);
+
+/* The relay's address, in host byte order */
+uint32_t dhcp_giaddr(const DhcpHandle *handle);
+
+/* The first `hlen` bytes of chaddr, at most 16. Their number is stored
+ * in `len`. */
+const uint8_t *dhcp_chaddr(const DhcpHandle *handle, size_t *len);
+
+/* The number of options, including Pad and End */
+size_t dhcp_option_count(const DhcpHandle *handle);
+
+/* The code of option `index`, or -1 if there are fewer options */
+int32_t dhcp_option_code(const DhcpHandle *handle, size_t index);
+
+/* The value of option `index`, with its length stored in `len`, or
+ * null if there are fewer options */
+const uint8_t *dhcp_option_data(const DhcpHandle *handle, size_t index, size_t *len);
+
+/* The message type from option 53, or -1 if there isn't one */
+int32_t dhcp_message_type(const DhcpHandle *handle);
+
+#ifdef __cplusplus
+}  /* extern "C" */
+#endif
+
+#endif  /* DHCP_PARSER_H */
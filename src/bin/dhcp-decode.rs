@@ -0,0 +1,258 @@
+//! Print the messages in a hex dump, base64 blob, raw message or capture
+//!
+//!     dhcp-decode [--format auto|hex|base64|raw|pcap] [--test NAME] [FILE]
+//!
+//! Reads standard input if no file is given. With `--test`, prints a unit
+//! test for the message instead (see `dhcp_parser::testgen`).
+
+extern crate dhcp_parser;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+use dhcp_parser::DhcpMessage;
+use dhcp_parser::options::DhcpOption;
+use dhcp_parser::pcap;
+use dhcp_parser::testgen;
+
+const USAGE: &str = "usage: dhcp-decode [--format auto|hex|base64|raw|pcap] [--test NAME] [FILE]";
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Format {
+    Hex,
+    Base64,
+    Raw,
+    Pcap,
+}
+
+fn parse_format(name: &str) -> Option<Option<Format>> {
+    match name {
+        "auto" => Some(None),
+        "hex" => Some(Some(Format::Hex)),
+        "base64" => Some(Some(Format::Base64)),
+        "raw" => Some(Some(Format::Raw)),
+        "pcap" => Some(Some(Format::Pcap)),
+        _ => None,
+    }
+}
+
+/// Guess the format from the content: captures by their magic number,
+/// then text that is entirely hex or base64
+fn detect(input: &[u8]) -> Format {
+    let magic = input.get(..4).map(|m| [m[0], m[1], m[2], m[3]]);
+    match magic {
+        Some([0xa1, 0xb2, 0xc3, 0xd4]) | Some([0xd4, 0xc3, 0xb2, 0xa1]) |
+        Some([0xa1, 0xb2, 0x3c, 0x4d]) | Some([0x4d, 0x3c, 0xb2, 0xa1]) |
+        Some([0x0a, 0x0d, 0x0d, 0x0a]) => return Format::Pcap,
+        _ => {},
+    }
+    let text = match std::str::from_utf8(input) {
+        Ok(text) => text,
+        Err(_) => return Format::Raw,
+    };
+    if decode_hex(text).is_some() {
+        Format::Hex
+    } else if decode_base64(text).is_some() {
+        Format::Base64
+    } else {
+        Format::Raw
+    }
+}
+
+/// Accepts plain hex with or without separators, and the output of
+/// `xxd` and `tcpdump -X`: a leading offset is dropped from each line,
+/// as is anything after a double space (the ASCII column).
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    let mut digits = Vec::new();
+    for line in text.lines() {
+        let mut line = line.trim();
+        if let Some(colon) = line.find(':') {
+            let offset = line[..colon].trim_start_matches("0x");
+            if colon > 2 && offset.chars().all(|c| c.is_ascii_hexdigit()) {
+                line = line[colon + 1..].trim_start();
+            }
+        }
+        if let Some(gap) = line.find("  ") {
+            line = &line[..gap];
+        }
+        for token in line.split(|c: char| c.is_whitespace() || c == ':' || c == '-' || c == ',') {
+            let token = token.trim_start_matches("0x");
+            if !token.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            digits.extend(token.chars().map(|c| c.to_digit(16).unwrap() as u8));
+        }
+    }
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        return None;
+    }
+    Some(digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect())
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let text = text.trim().trim_end_matches('=');
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => return None,
+        };
+        acc = acc << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    if out.is_empty() { None } else { Some(out) }
+}
+
+fn print_message(out: &mut dyn Write, message: &DhcpMessage) -> io::Result<()> {
+    writeln!(out, "op: {:?}", message.op)?;
+    writeln!(out, "htype: {:?}", message.htype)?;
+    writeln!(out, "hlen: {}", message.hlen)?;
+    writeln!(out, "hops: {}", message.hops)?;
+    writeln!(out, "xid: {:#010x}", message.xid)?;
+    writeln!(out, "secs: {}", message.secs)?;
+    writeln!(out, "flags: {:#06x}", message.flags)?;
+    writeln!(out, "ciaddr: {}", message.ciaddr)?;
+    writeln!(out, "yiaddr: {}", message.yiaddr)?;
+    writeln!(out, "siaddr: {}", message.siaddr)?;
+    writeln!(out, "giaddr: {}", message.giaddr)?;
+    writeln!(out, "chaddr: {}", message.hardware_addr())?;
+    print_text(out, "sname", &message.sname)?;
+    print_text(out, "file", &message.file)?;
+    writeln!(out, "options:")?;
+    for option in &message.options {
+        match option {
+            DhcpOption::RelayAgentInformation(subs) => {
+                writeln!(out, "  RelayAgentInformation:")?;
+                for sub in subs {
+                    writeln!(out, "    {:?}", sub)?;
+                }
+            },
+            option => writeln!(out, "  {:?}", option)?,
+        }
+    }
+    Ok(())
+}
+
+/// `sname` and `file` are usually NUL-padded strings, or empty
+fn print_text(out: &mut dyn Write, name: &str, field: &[u8]) -> io::Result<()> {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    if field[end..].iter().all(|&b| b == 0) {
+        if let Ok(text) = std::str::from_utf8(&field[..end]) {
+            return writeln!(out, "{}: {:?}", name, text);
+        }
+    }
+    writeln!(out, "{}: {:?}", name, field)
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut format = None;
+    let mut test_name = None;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let name = args.next().ok_or(USAGE)?;
+                format = parse_format(name).ok_or(USAGE)?;
+            },
+            "--test" => test_name = Some(args.next().ok_or(USAGE)?.clone()),
+            "-h" | "--help" => return Err(USAGE.to_owned()),
+            _ if path.is_none() => path = Some(arg.clone()),
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    let input = match path {
+        Some(ref path) if path != "-" => fs::read(path).map_err(|e| format!("{}: {}", path, e))?,
+        _ => {
+            let mut input = Vec::new();
+            io::stdin().read_to_end(&mut input).map_err(|e| e.to_string())?;
+            input
+        },
+    };
+    let format = format.unwrap_or_else(|| detect(&input));
+    let text = || std::str::from_utf8(&input).map_err(|_| "input is not text".to_owned());
+    let bytes = match format {
+        Format::Hex => decode_hex(text()?).ok_or("invalid hex")?,
+        Format::Base64 => decode_base64(text()?).ok_or("invalid base64")?,
+        Format::Raw => input.clone(),
+        Format::Pcap => Vec::new(),
+    };
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    if let Some(name) = test_name {
+        let test = if format == Format::Pcap {
+            testgen::capture_test(&name, &input, 0)
+        } else {
+            testgen::message_test(&name, &bytes)
+        };
+        return write!(out, "{}", test.map_err(|e| e.to_string())?).map_err(|e| e.to_string());
+    }
+    if format != Format::Pcap {
+        let message = DhcpMessage::parse(&bytes).map_err(|e| e.to_string())?;
+        return print_message(&mut out, &message).map_err(|e| e.to_string());
+    }
+    for (i, packet) in pcap::packets(&input).map_err(|e| e.to_string())?.enumerate() {
+        if i > 0 {
+            writeln!(out).map_err(|e| e.to_string())?;
+        }
+        match packet {
+            Ok(packet) => {
+                let at = packet.timestamp.duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+                writeln!(out, "# {}.{:06} {} -> {}", at.as_secs(), at.subsec_micros(),
+                         packet.source, packet.destination).map_err(|e| e.to_string())?;
+                print_message(&mut out, &packet.message).map_err(|e| e.to_string())?;
+            },
+            Err(e) => writeln!(out, "# error: {}", e).map_err(|e| e.to_string())?,
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{decode_base64, decode_hex, detect, Format};
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("01 01 06 00\n3903f326"), Some(vec![1, 1, 6, 0, 0x39, 0x03, 0xf3, 0x26]));
+        assert_eq!(decode_hex("00000000: 0101 0600 3903 f326  ....9..&\n"), Some(vec![1, 1, 6, 0, 0x39, 0x03, 0xf3, 0x26]));
+        assert_eq!(decode_hex("\t0x0000:  4500 0148  E..H"), Some(vec![0x45, 0, 1, 0x48]));
+        assert_eq!(decode_hex("01:02:0a"), Some(vec![1, 2, 10]));
+        assert_eq!(decode_hex("010"), None);
+        assert_eq!(decode_hex("hello"), None);
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64("AQEGAA=="), Some(vec![1, 1, 6, 0]));
+        assert_eq!(decode_base64("not base64!"), None);
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect(&[0xd4, 0xc3, 0xb2, 0xa1, 2, 0]), Format::Pcap);
+        assert_eq!(detect(b"0101 0600"), Format::Hex);
+        assert_eq!(detect(b"AQEGAA=="), Format::Base64);
+        assert_eq!(detect(&[1, 1, 6, 0, 0xff]), Format::Raw);
+    }
+}
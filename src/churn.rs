@@ -0,0 +1,211 @@
+//! Detect changes in the configuration a server hands out
+//!
+//! Feed OFFERs and ACKs seen on the wire into a `ChurnDetector`. It
+//! remembers the options last handed out for each server and subnet, and
+//! reports an `OptionChange` whenever a later reply differs, e.g. a new DNS
+//! server or a shorter lease time. The first reply seen for a scope only
+//! sets the baseline.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::SystemTime;
+use RawMessage;
+use options::{self, DhcpOption, DhcpMessageTypes};
+
+/// Options that are specific to one client or one exchange rather than
+/// part of the scope's configuration
+const PER_CLIENT_OPTIONS: [u8; 9] = [0u8, 12u8, 50u8, 53u8, 54u8, 56u8, 61u8, 81u8, 82u8];
+
+/// Where a reply came from: the server, and the subnet it assigned from
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Scope {
+    pub server: IpAddr,
+    /// `yiaddr` masked with option 1, if the reply carried a subnet mask
+    pub subnet: Option<(Ipv4Addr, Ipv4Addr)>,
+}
+
+impl Scope {
+    /// The scope of a reply, identified by its server identifier (or
+    /// `siaddr` if there isn't one)
+    pub fn of(message: &RawMessage) -> Scope {
+        let mut server = IpAddr::V4(message.siaddr);
+        let mut subnet = None;
+        for option in &message.options {
+            match *option {
+                DhcpOption::ServerIdentifier(addr) => server = addr,
+                DhcpOption::SubnetMask(IpAddr::V4(mask)) => {
+                    let network = u32::from(message.yiaddr) & u32::from(mask);
+                    subnet = Some((Ipv4Addr::from(network), mask));
+                },
+                _ => {},
+            }
+        }
+        Scope { server, subnet }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct OptionChange {
+    pub scope: Scope,
+    pub code: u8,
+    /// `None` if the option was newly added
+    pub old: Option<DhcpOption>,
+    /// `None` if the option was dropped
+    pub new: Option<DhcpOption>,
+    /// When the old value was last seen
+    pub since: SystemTime,
+    pub at: SystemTime,
+}
+
+impl OptionChange {
+    /// True if this is a change to option 51 that shortens the lease
+    pub fn is_lease_time_shortened(&self) -> bool {
+        match (&self.old, &self.new) {
+            (&Some(DhcpOption::IpAddressLeaseTime(old)), &Some(DhcpOption::IpAddressLeaseTime(new))) => new < old,
+            _ => false,
+        }
+    }
+}
+
+struct Snapshot {
+    /// Encoded options by code, so values can be compared and decoded
+    /// again without the originals
+    options: BTreeMap<u8, Vec<u8>>,
+    seen: SystemTime,
+}
+
+#[derive(Default)]
+pub struct ChurnDetector {
+    scopes: HashMap<Scope, Snapshot>,
+}
+
+impl ChurnDetector {
+    pub fn new() -> ChurnDetector {
+        ChurnDetector { scopes: HashMap::new() }
+    }
+
+    /// Record a message seen at `at`. Anything other than an OFFER or ACK
+    /// is ignored.
+    pub fn observe_message(&mut self, message: &RawMessage, at: SystemTime) -> Vec<OptionChange> {
+        let is_reply = message.options.iter().any(|o| {
+            matches!(o, DhcpOption::MessageType(DhcpMessageTypes::Offer) | DhcpOption::MessageType(DhcpMessageTypes::Ack))
+        });
+        if !is_reply {
+            return Vec::new();
+        }
+        self.observe(Scope::of(message), &message.options, at)
+    }
+
+    /// Record the options handed out for `scope` at `at`, returning how
+    /// they differ from the last observation
+    pub fn observe(&mut self, scope: Scope, handed_out: &[DhcpOption], at: SystemTime) -> Vec<OptionChange> {
+        let mut current = BTreeMap::new();
+        for option in handed_out {
+            let code = option.code();
+            if PER_CLIENT_OPTIONS.contains(&code) || code == 255u8 {
+                continue;
+            }
+            option.encode(current.entry(code).or_insert_with(Vec::new));
+        }
+
+        let mut changes = Vec::new();
+        if let Some(previous) = self.scopes.get(&scope) {
+            let codes = previous.options.keys().chain(current.keys()).cloned().collect::<BTreeSet<u8>>();
+            for code in codes {
+                let old = previous.options.get(&code);
+                let new = current.get(&code);
+                if old != new {
+                    changes.push(OptionChange {
+                        scope,
+                        code,
+                        old: old.and_then(|b| decode(b)),
+                        new: new.and_then(|b| decode(b)),
+                        since: previous.seen,
+                        at,
+                    });
+                }
+            }
+        }
+        self.scopes.insert(scope, Snapshot { options: current, seen: at });
+        changes
+    }
+}
+
+fn decode(bytes: &[u8]) -> Option<DhcpOption> {
+    options::parse(bytes).ok().and_then(|mut opts| opts.pop())
+}
+
+#[cfg(test)] mod tests {
+    use super::{ChurnDetector, Scope};
+    use {DhcpMessage, parse_message};
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, DhcpMessageTypes};
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn ack(yiaddr: u8, options: Vec<DhcpOption>) -> DhcpMessage {
+        DhcpMessage {
+            op: Op::BootReply,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: yiaddr as u32,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(10, 0, 0, yiaddr),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, yiaddr, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options,
+        }
+    }
+
+    #[test]
+    fn test_churn_detector() {
+        let scope = Scope { server: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), subnet: None };
+        let dns = |last| DhcpOption::DomainNameServer(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, last))]);
+        let t0 = UNIX_EPOCH;
+        let t1 = UNIX_EPOCH + Duration::from_secs(60);
+        let mut detector = ChurnDetector::new();
+
+        let baseline = vec![dns(53), DhcpOption::IpAddressLeaseTime(86400), DhcpOption::End];
+        assert!(detector.observe(scope, &baseline, t0).is_empty());
+        assert!(detector.observe(scope, &baseline, t0).is_empty());
+
+        let changed = vec![dns(54), DhcpOption::IpAddressLeaseTime(3600), DhcpOption::End];
+        let changes = detector.observe(scope, &changed, t1);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].code, 6u8);
+        assert_eq!(changes[0].old, Some(dns(53)));
+        assert_eq!(changes[0].new, Some(dns(54)));
+        assert_eq!(changes[0].since, t0);
+        assert_eq!(changes[1].code, 51u8);
+        assert!(changes[1].is_lease_time_shortened());
+
+        let dropped = vec![DhcpOption::IpAddressLeaseTime(3600)];
+        let changes = detector.observe(scope, &dropped, t1);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].new, None);
+    }
+
+    #[test]
+    fn test_churn_ignores_client_names() {
+        let reply = |last: u8, name: &str| ack(last, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Ack),
+            DhcpOption::ServerIdentifier(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))),
+            DhcpOption::SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+            DhcpOption::HostName(name.to_string()),
+            DhcpOption::Unknown { code: 81u8, data: [&[0u8, 0, 0][..], name.as_bytes()].concat() },
+            DhcpOption::IpAddressLeaseTime(86400),
+            DhcpOption::End,
+        ]).encode();
+        let (first, second) = (reply(10, "alice"), reply(11, "bob"));
+        let mut detector = ChurnDetector::new();
+        assert!(detector.observe_message(&parse_message(&first).unwrap(), UNIX_EPOCH).is_empty());
+        assert!(detector.observe_message(&parse_message(&second).unwrap(), UNIX_EPOCH + Duration::from_secs(60)).is_empty());
+    }
+}
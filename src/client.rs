@@ -0,0 +1,463 @@
+//! A DHCP client state machine without any I/O
+//!
+//! `Client` follows RFC 2131 section 4.4: INIT → SELECTING → REQUESTING →
+//! BOUND → RENEWING → REBINDING. The caller owns the socket and the clock:
+//! it passes in every message received and calls `handle_timeout` when
+//! the last timer requested goes off, and carries out the `Action`s that
+//! come back. A timer that goes off late, or one left over from an
+//! earlier state, is harmless.
+//!
+//! Addresses aren't checked with ARP before use, so no DECLINE is ever
+//! sent, and INIT-REBOOT isn't supported.
+
+use std::cmp;
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::{Duration, Instant};
+use DhcpMessage;
+use op::Op;
+use htype::Htype;
+use hwaddr::MacAddress;
+use lease::Lease;
+use options::{DhcpOption, DhcpMessageTypes};
+
+/// Longest wait between retransmissions while SELECTING or REQUESTING
+const MAX_RETRANSMIT_DELAY: Duration = Duration::from_secs(64);
+/// Shortest wait between retransmissions while RENEWING or REBINDING
+const MIN_RENEW_DELAY: Duration = Duration::from_secs(60);
+/// REQUESTs sent before going back to SELECTING
+const MAX_REQUESTS: u32 = 4;
+const BROADCAST_FLAG: u16 = 0x8000;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum State {
+    Init,
+    Selecting,
+    Requesting,
+    Bound,
+    Renewing,
+    Rebinding,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    /// Send `message` to port 67 at `destination`, which is either the
+    /// broadcast address or, while RENEWING, the server
+    Send { message: DhcpMessage, destination: Ipv4Addr },
+    /// Call `handle_timeout` at this time
+    SetTimer(Instant),
+    /// A lease was acquired or extended; configure the interface.
+    /// `start` is when the REQUEST was sent, which the lease's times
+    /// count from, and `options` are all the options in the ACK.
+    Bound { lease: Lease, start: Instant, options: Vec<DhcpOption> },
+    /// Stop using the address, if there was one, and call `start` again
+    Unbound,
+}
+
+/// What the client needs to keep of its lease to renew it
+#[derive(Debug, Clone, Copy)]
+struct Binding {
+    address: Ipv4Addr,
+    server: Ipv4Addr,
+    renew_at: Option<Instant>,
+    rebind_at: Option<Instant>,
+    expires_at: Option<Instant>,
+}
+
+pub struct Client {
+    mac: MacAddress,
+    param_request_list: Vec<u8>,
+    state: State,
+    xid: u32,
+    /// The address and server from the OFFER being requested
+    offer: Option<(Ipv4Addr, Ipv4Addr)>,
+    binding: Option<Binding>,
+    timeout: Option<Instant>,
+    /// Messages sent in the current state, for backing off
+    sent: u32,
+    request_sent: Option<Instant>,
+}
+
+impl Client {
+    /// A client for the interface with address `mac`, which asks for
+    /// subnet mask, router, DNS servers and domain name
+    pub fn new(mac: MacAddress) -> Client {
+        Client {
+            mac,
+            param_request_list: vec![1u8, 3u8, 6u8, 15u8],
+            state: State::Init,
+            xid: 0,
+            offer: None,
+            binding: None,
+            timeout: None,
+            sent: 0,
+            request_sent: None,
+        }
+    }
+
+    /// Set the option 55 codes sent in DISCOVERs and REQUESTs
+    pub fn param_request_list(mut self, codes: Vec<u8>) -> Client {
+        self.param_request_list = codes;
+        self
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// The bound address, while there is one
+    pub fn address(&self) -> Option<Ipv4Addr> {
+        self.binding.map(|b| b.address)
+    }
+
+    /// Start over from INIT with a DISCOVER. `xid` should be random.
+    pub fn start(&mut self, xid: u32, now: Instant) -> Vec<Action> {
+        self.xid = xid;
+        self.offer = None;
+        self.binding = None;
+        self.sent = 0;
+        self.state = State::Selecting;
+        self.send_discover(now)
+    }
+
+    pub fn handle_message(&mut self, message: DhcpMessage, now: Instant) -> Vec<Action> {
+        if message.op != Op::BootReply || message.xid != self.xid || !message.chaddr.starts_with(&self.mac.0) {
+            return Vec::new();
+        }
+        let mut message_type = None;
+        let mut server = None;
+        for option in &message.options {
+            match *option {
+                DhcpOption::MessageType(t) => message_type = Some(t),
+                DhcpOption::ServerIdentifier(IpAddr::V4(addr)) => server = Some(addr),
+                _ => {},
+            }
+        }
+        match (self.state, message_type) {
+            (State::Selecting, Some(DhcpMessageTypes::Offer)) => {
+                let server = match server {
+                    Some(server) => server,
+                    None => return Vec::new(),
+                };
+                self.offer = Some((message.yiaddr, server));
+                self.state = State::Requesting;
+                self.sent = 0;
+                self.send_request(now)
+            },
+            (State::Requesting, Some(DhcpMessageTypes::Ack)) |
+            (State::Renewing, Some(DhcpMessageTypes::Ack)) |
+            (State::Rebinding, Some(DhcpMessageTypes::Ack)) => self.bind(message, now),
+            (State::Requesting, Some(DhcpMessageTypes::Nak)) |
+            (State::Renewing, Some(DhcpMessageTypes::Nak)) |
+            (State::Rebinding, Some(DhcpMessageTypes::Nak)) => self.unbind(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn handle_timeout(&mut self, now: Instant) -> Vec<Action> {
+        match self.timeout {
+            Some(timeout) if now >= timeout => {},
+            _ => return Vec::new(),
+        }
+        match self.state {
+            State::Init => Vec::new(),
+            State::Selecting => self.send_discover(now),
+            State::Requesting if self.sent < MAX_REQUESTS => self.send_request(now),
+            State::Requesting => {
+                self.state = State::Selecting;
+                self.sent = 0;
+                self.send_discover(now)
+            },
+            State::Bound | State::Renewing | State::Rebinding => {
+                let binding = match self.binding {
+                    Some(binding) => binding,
+                    None => return self.unbind(),
+                };
+                if binding.expires_at.is_some_and(|t| now >= t) {
+                    return self.unbind();
+                }
+                let state = if binding.rebind_at.is_some_and(|t| now >= t) {
+                    State::Rebinding
+                } else {
+                    State::Renewing
+                };
+                if state != self.state {
+                    self.state = state;
+                    self.sent = 0;
+                }
+                self.send_request(now)
+            },
+        }
+    }
+
+    fn message(&self, message_type: DhcpMessageTypes) -> DhcpMessage {
+        let mut chaddr = self.mac.0.to_vec();
+        chaddr.resize(16, 0u8);
+        let unspecified = Ipv4Addr::new(0, 0, 0, 0);
+        DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: self.xid,
+            secs: 0,
+            flags: 0,
+            ciaddr: unspecified,
+            yiaddr: unspecified,
+            siaddr: unspecified,
+            giaddr: unspecified,
+            chaddr,
+            sname: vec![0u8; 64],
+            file: vec![0u8; 128],
+            options: vec![DhcpOption::MessageType(message_type)],
+        }
+    }
+
+    fn send_discover(&mut self, now: Instant) -> Vec<Action> {
+        let mut message = self.message(DhcpMessageTypes::Discover);
+        message.flags = BROADCAST_FLAG;
+        message.options.push(DhcpOption::ParamRequestList(self.param_request_list.clone()));
+        message.options.push(DhcpOption::End);
+        let timer = self.back_off(now);
+        vec![
+            Action::Send { message, destination: Ipv4Addr::new(255, 255, 255, 255) },
+            Action::SetTimer(timer),
+        ]
+    }
+
+    /// The REQUEST for the current state: naming the offer while
+    /// REQUESTING, from the bound address otherwise
+    fn send_request(&mut self, now: Instant) -> Vec<Action> {
+        // The lease counts from the first REQUEST in each state
+        if self.sent == 0 {
+            self.request_sent = Some(now);
+        }
+        let mut message = self.message(DhcpMessageTypes::Request);
+        let (destination, timer) = match (self.state, self.offer, self.binding) {
+            (State::Requesting, Some((address, server)), _) => {
+                message.flags = BROADCAST_FLAG;
+                message.options.push(DhcpOption::RequestedIpAddress(IpAddr::V4(address)));
+                message.options.push(DhcpOption::ServerIdentifier(IpAddr::V4(server)));
+                (Ipv4Addr::new(255, 255, 255, 255), self.back_off(now))
+            },
+            (State::Renewing, _, Some(binding)) => {
+                message.ciaddr = binding.address;
+                self.sent += 1;
+                (binding.server, retransmit_at(now, binding.rebind_at))
+            },
+            (State::Rebinding, _, Some(binding)) => {
+                message.ciaddr = binding.address;
+                self.sent += 1;
+                (Ipv4Addr::new(255, 255, 255, 255), retransmit_at(now, binding.expires_at))
+            },
+            _ => return Vec::new(),
+        };
+        message.options.push(DhcpOption::ParamRequestList(self.param_request_list.clone()));
+        message.options.push(DhcpOption::End);
+        self.timeout = Some(timer);
+        vec![Action::Send { message, destination }, Action::SetTimer(timer)]
+    }
+
+    /// 4 seconds before the first retransmission, doubling up to 64
+    fn back_off(&mut self, now: Instant) -> Instant {
+        let delay = cmp::min(Duration::from_secs(4 << cmp::min(self.sent, 4)), MAX_RETRANSMIT_DELAY);
+        self.sent += 1;
+        let timer = now + delay;
+        self.timeout = Some(timer);
+        timer
+    }
+
+    fn bind(&mut self, message: DhcpMessage, now: Instant) -> Vec<Action> {
+        let mut lease = Lease::from_message(&message);
+        let lease_time = match lease.lease_time {
+            Some(t) => t,
+            None => return Vec::new(),
+        };
+        let server = match lease.server.or(self.binding.map(|b| b.server)).or(self.offer.map(|o| o.1)) {
+            Some(server) => server,
+            None => return Vec::new(),
+        };
+        lease.server = Some(server);
+
+        let start = self.request_sent.unwrap_or(now);
+        let binding = Binding {
+            address: message.yiaddr,
+            server,
+            renew_at: lease.t1().map(|t| start + t),
+            rebind_at: lease.t2().map(|t| start + t),
+            expires_at: if lease.is_infinite() { None } else { Some(start + lease_time) },
+        };
+        self.binding = Some(binding);
+        self.offer = None;
+        self.state = State::Bound;
+        self.sent = 0;
+        self.timeout = binding.renew_at.or(binding.rebind_at).or(binding.expires_at);
+
+        let mut actions = vec![Action::Bound { lease, start, options: message.options }];
+        if let Some(timer) = self.timeout {
+            actions.push(Action::SetTimer(timer));
+        }
+        actions
+    }
+
+    fn unbind(&mut self) -> Vec<Action> {
+        self.state = State::Init;
+        self.offer = None;
+        self.binding = None;
+        self.timeout = None;
+        vec![Action::Unbound]
+    }
+}
+
+/// Half the time left until `deadline`, but at least 60 seconds, as in
+/// RFC 2131 section 4.4.5
+fn retransmit_at(now: Instant, deadline: Option<Instant>) -> Instant {
+    match deadline {
+        Some(deadline) if deadline > now => {
+            let wait = cmp::max((deadline - now) / 2, MIN_RENEW_DELAY);
+            cmp::min(now + wait, deadline)
+        },
+        Some(deadline) => deadline,
+        None => now + MIN_RENEW_DELAY,
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{Action, Client, State};
+    use DhcpMessage;
+    use op::Op;
+    use hwaddr::MacAddress;
+    use options::{DhcpOption, DhcpMessageTypes};
+    use validate::validate;
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::{Duration, Instant};
+
+    const MAC: MacAddress = MacAddress([0, 11, 130, 1, 252, 66]);
+
+    fn server() -> Ipv4Addr {
+        Ipv4Addr::new(10, 0, 0, 1)
+    }
+
+    fn reply(request: &DhcpMessage, message_type: DhcpMessageTypes) -> DhcpMessage {
+        let mut options = vec![
+            DhcpOption::MessageType(message_type),
+            DhcpOption::ServerIdentifier(IpAddr::V4(server())),
+        ];
+        if message_type != DhcpMessageTypes::Nak {
+            options.push(DhcpOption::IpAddressLeaseTime(3600));
+        }
+        options.push(DhcpOption::End);
+        DhcpMessage {
+            op: Op::BootReply,
+            yiaddr: if message_type == DhcpMessageTypes::Nak { Ipv4Addr::new(0, 0, 0, 0) } else { Ipv4Addr::new(10, 0, 0, 5) },
+            options,
+            chaddr: request.chaddr.clone(),
+            sname: vec![0u8; 64],
+            file: vec![0u8; 128],
+            ..DhcpMessage::parse(&request.encode()).unwrap()
+        }
+    }
+
+    /// The message and destination of the only `Send` in `actions`
+    fn sent(actions: &[Action]) -> (&DhcpMessage, Ipv4Addr) {
+        let sends: Vec<_> = actions.iter().filter_map(|a| match a {
+            &Action::Send { ref message, destination } => Some((message, destination)),
+            _ => None,
+        }).collect();
+        assert_eq!(sends.len(), 1, "{:?}", actions);
+        assert_eq!(validate(sends[0].0), vec![]);
+        sends[0]
+    }
+
+    fn timer(actions: &[Action]) -> Instant {
+        actions.iter().filter_map(|a| match a {
+            &Action::SetTimer(t) => Some(t),
+            _ => None,
+        }).next().unwrap()
+    }
+
+    fn bind(client: &mut Client, t0: Instant) {
+        let discover = client.start(7, t0);
+        let offer = reply(sent(&discover).0, DhcpMessageTypes::Offer);
+        let request = client.handle_message(offer, t0);
+        assert_eq!(client.state(), State::Requesting);
+        let ack = reply(sent(&request).0, DhcpMessageTypes::Ack);
+        let bound = client.handle_message(ack, t0);
+        match bound[0] {
+            Action::Bound { ref lease, start, .. } => {
+                assert_eq!(lease.address, Some(Ipv4Addr::new(10, 0, 0, 5)));
+                assert_eq!(lease.server, Some(server()));
+                assert_eq!(lease.t1(), Some(Duration::from_secs(1800)));
+                assert_eq!(lease.t2(), Some(Duration::from_secs(3150)));
+                assert_eq!(start, t0);
+            },
+            ref a => panic!("expected a lease, got {:?}", a),
+        }
+        assert_eq!(timer(&bound), t0 + Duration::from_secs(1800));
+        assert_eq!(client.state(), State::Bound);
+    }
+
+    #[test]
+    fn test_acquire_lease() {
+        let t0 = Instant::now();
+        let mut client = Client::new(MAC);
+        let discover = client.start(7, t0);
+        let (message, destination) = sent(&discover);
+        assert_eq!(destination, Ipv4Addr::new(255, 255, 255, 255));
+        assert_eq!(message.xid, 7);
+        assert_eq!(timer(&discover), t0 + Duration::from_secs(4));
+
+        // Replies for someone else are ignored
+        let mut other = reply(message, DhcpMessageTypes::Offer);
+        other.xid = 8;
+        assert_eq!(client.handle_message(other, t0), vec![]);
+
+        let retransmit = client.handle_timeout(t0 + Duration::from_secs(4));
+        assert_eq!(timer(&retransmit), t0 + Duration::from_secs(12));
+
+        bind(&mut client, t0);
+        assert_eq!(client.address(), Some(Ipv4Addr::new(10, 0, 0, 5)));
+    }
+
+    #[test]
+    fn test_renew_and_rebind() {
+        let t0 = Instant::now();
+        let mut client = Client::new(MAC);
+        bind(&mut client, t0);
+        assert_eq!(client.handle_timeout(t0 + Duration::from_secs(1000)), vec![]);
+
+        let t1 = t0 + Duration::from_secs(1800);
+        let renew = client.handle_timeout(t1);
+        assert_eq!(client.state(), State::Renewing);
+        let (message, destination) = sent(&renew);
+        assert_eq!(destination, server());
+        assert_eq!(message.ciaddr, Ipv4Addr::new(10, 0, 0, 5));
+        assert_eq!(timer(&renew), t1 + Duration::from_secs(675));
+
+        let t2 = t0 + Duration::from_secs(3150);
+        let rebind = client.handle_timeout(t2);
+        assert_eq!(client.state(), State::Rebinding);
+        assert_eq!(sent(&rebind).1, Ipv4Addr::new(255, 255, 255, 255));
+        assert_eq!(timer(&rebind), t2 + Duration::from_secs(225));
+
+        let ack = reply(sent(&rebind).0, DhcpMessageTypes::Ack);
+        let bound = client.handle_message(ack, t2);
+        assert_eq!(client.state(), State::Bound);
+        assert_eq!(timer(&bound), t2 + Duration::from_secs(1800));
+    }
+
+    #[test]
+    fn test_lease_lost() {
+        let t0 = Instant::now();
+        let mut client = Client::new(MAC);
+        bind(&mut client, t0);
+        let renew = client.handle_timeout(t0 + Duration::from_secs(1800));
+        let nak = reply(sent(&renew).0, DhcpMessageTypes::Nak);
+        assert_eq!(client.handle_message(nak, t0), vec![Action::Unbound]);
+        assert_eq!(client.state(), State::Init);
+        assert_eq!(client.address(), None);
+
+        bind(&mut client, t0);
+        client.handle_timeout(t0 + Duration::from_secs(3150));
+        assert_eq!(client.handle_timeout(t0 + Duration::from_secs(3600)), vec![Action::Unbound]);
+    }
+}
@@ -0,0 +1,130 @@
+//! `tokio_util` codec for DHCP datagrams
+//!
+//! Each datagram is decoded as one whole message, so this is meant to be
+//! used with `tokio_util::udp::UdpFramed`:
+//!
+//! ```ignore
+//! let socket = UdpSocket::bind("0.0.0.0:67").await?;
+//! let mut framed = UdpFramed::new(socket, DhcpCodec);
+//! while let Some(Ok((msg, peer))) = framed.next().await {
+//!     // msg is a DhcpMessage
+//! }
+//! ```
+
+use std::fmt;
+use std::error;
+use std::io;
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+use {DhcpMessage, Error};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DhcpCodec;
+
+#[derive(Debug)]
+pub enum CodecError {
+    Io(io::Error),
+    Parse(Error),
+    /// The message can't be encoded, see `DhcpMessage::try_encode`
+    Encode(Error),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CodecError::Io(ref e) => write!(f, "{}", e),
+            CodecError::Parse(ref e) => write!(f, "{}", e),
+            CodecError::Encode(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for CodecError {}
+
+impl From<io::Error> for CodecError {
+    fn from(e: io::Error) -> CodecError {
+        CodecError::Io(e)
+    }
+}
+
+impl From<Error> for CodecError {
+    fn from(e: Error) -> CodecError {
+        CodecError::Parse(e)
+    }
+}
+
+impl Decoder for DhcpCodec {
+    type Item = DhcpMessage;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> ::std::result::Result<Option<DhcpMessage>, CodecError> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+        let datagram = src.split_to(src.len());
+        Ok(Some(DhcpMessage::parse(&datagram)?))
+    }
+}
+
+impl Encoder<DhcpMessage> for DhcpCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: DhcpMessage, dst: &mut BytesMut) -> ::std::result::Result<(), CodecError> {
+        dst.extend_from_slice(&item.try_encode().map_err(CodecError::Encode)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{DhcpCodec, CodecError};
+    use bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder};
+    use {DhcpMessage};
+    use op::Op;
+    use htype::Htype;
+    use options::DhcpOption;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    fn message(options: Vec<DhcpOption>) -> DhcpMessage {
+        DhcpMessage {
+            op: Op::BootReply,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 1,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(192, 168, 1, 10),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0u8; 16],
+            sname: vec![0u8; 64],
+            file: vec![0u8; 128],
+            options,
+        }
+    }
+
+    #[test]
+    fn test_codec_round_trip() {
+        let msg = message(vec![DhcpOption::End]);
+        let expected = DhcpMessage::parse(&msg.encode()).unwrap();
+        let mut buf = BytesMut::new();
+        DhcpCodec.encode(msg, &mut buf).unwrap();
+        assert_eq!(DhcpCodec.decode(&mut buf).unwrap(), Some(expected));
+        assert!(buf.is_empty());
+        assert!(DhcpCodec.decode(&mut buf).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_codec_rejects_ipv6() {
+        let router = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+        let msg = message(vec![DhcpOption::Router(ip_list![router]), DhcpOption::End]);
+        let mut buf = BytesMut::new();
+        match DhcpCodec.encode(msg, &mut buf) {
+            Err(CodecError::Encode(_)) => {},
+            other => panic!("expected an encode error, got {:?}", other),
+        }
+        assert!(buf.is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+//! Compact binary export of messages, as CBOR or MessagePack
+//!
+//! The encoding is self-describing: a message is a map of its header
+//! fields, with the options as a map from option code to the option's
+//! wire value. `sname` and `file` lose their trailing zeros and get them
+//! back on import. Pad is dropped, back-to-back repeats of an option are
+//! joined as RFC 3396 fragments the way `parse` joins them, and End is
+//! added back on import, so a message comes back as it would be parsed
+//! after being sent. Other repeats, including those of options with a
+//! fixed length, stay separate, so a code can be in the map more than
+//! once.
+
+use std::fmt;
+use std::net::Ipv4Addr;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{MapAccess, Visitor};
+use serde_bytes::{ByteBuf, Bytes};
+use {DhcpMessage, Result, Error};
+use op::Op;
+use htype::Htype;
+use options::{self, DhcpOption, put_option, fixed_length};
+use options::raw::next_raw_option;
+
+/// Options in order of first appearance, as `(code, value)` pairs
+struct OptionMap(Vec<(u8, Vec<u8>)>);
+
+impl Serialize for OptionMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|&(code, ref data)| (code, Bytes::new(data))))
+    }
+}
+
+struct OptionMapVisitor;
+
+impl<'de> Visitor<'de> for OptionMapVisitor {
+    type Value = OptionMap;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of option codes to values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> ::std::result::Result<OptionMap, A::Error> {
+        let mut options = Vec::new();
+        while let Some((code, data)) = map.next_entry::<u8, ByteBuf>()? {
+            options.push((code, data.into_vec()));
+        }
+        Ok(OptionMap(options))
+    }
+}
+
+impl<'de> Deserialize<'de> for OptionMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<OptionMap, D::Error> {
+        deserializer.deserialize_map(OptionMapVisitor)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CompactMessage {
+    op: u8,
+    htype: u8,
+    hlen: u8,
+    hops: u8,
+    xid: u32,
+    secs: u16,
+    flags: u16,
+    ciaddr: u32,
+    yiaddr: u32,
+    siaddr: u32,
+    giaddr: u32,
+    chaddr: ByteBuf,
+    sname: ByteBuf,
+    file: ByteBuf,
+    options: OptionMap,
+}
+
+fn trimmed(bytes: &[u8]) -> ByteBuf {
+    let len = bytes.iter().rposition(|&b| b != 0u8).map_or(0, |last| last + 1);
+    ByteBuf::from(&bytes[..len])
+}
+
+fn padded(bytes: ByteBuf, len: usize) -> Vec<u8> {
+    let mut bytes = bytes.into_vec();
+    bytes.resize(len, 0u8);
+    bytes
+}
+
+impl<'a> From<&'a DhcpMessage> for CompactMessage {
+    fn from(msg: &'a DhcpMessage) -> CompactMessage {
+        let mut wire = Vec::new();
+        for option in &msg.options {
+            option.encode(&mut wire);
+        }
+        let mut options: Vec<(u8, Vec<u8>)> = Vec::new();
+        let mut previous = None;
+        let mut rest = &wire[..];
+        while let Some((raw, next)) = next_raw_option(rest) {
+            rest = next;
+            let fragment = previous == Some(raw.code) && fixed_length(raw.code).is_none();
+            previous = Some(raw.code);
+            if raw.code == 0u8 || raw.code == 255u8 {
+                continue;
+            }
+            match options.last_mut() {
+                Some(&mut (_, ref mut data)) if fragment => data.extend_from_slice(raw.data),
+                _ => options.push((raw.code, raw.data.to_vec())),
+            }
+        }
+        CompactMessage {
+            op: msg.op.to_byte(),
+            htype: msg.htype.to_byte(),
+            hlen: msg.hlen,
+            hops: msg.hops,
+            xid: msg.xid,
+            secs: msg.secs,
+            flags: msg.flags,
+            ciaddr: u32::from(msg.ciaddr),
+            yiaddr: u32::from(msg.yiaddr),
+            siaddr: u32::from(msg.siaddr),
+            giaddr: u32::from(msg.giaddr),
+            chaddr: trimmed(&msg.chaddr),
+            sname: trimmed(&msg.sname),
+            file: trimmed(&msg.file),
+            options: OptionMap(options),
+        }
+    }
+}
+
+impl CompactMessage {
+    fn into_message(self) -> Result<DhcpMessage> {
+        let mut wire = Vec::new();
+        let mut previous = None;
+        for &(code, ref data) in &self.options.0 {
+            // Kept apart, or `parse` would join them as fragments
+            if previous == Some(code) && fixed_length(code).is_none() {
+                wire.push(0u8);
+            }
+            put_option(&mut wire, code, data);
+            previous = Some(code);
+        }
+        let mut options = options::parse(&wire)?;
+        options.retain(|o| *o != DhcpOption::Pad);
+        options.push(DhcpOption::End);
+        Ok(DhcpMessage {
+            op: Op::from_byte(self.op)?,
+            htype: Htype::from_byte(self.htype),
+            hlen: self.hlen,
+            hops: self.hops,
+            xid: self.xid,
+            secs: self.secs,
+            flags: self.flags,
+            ciaddr: Ipv4Addr::from(self.ciaddr),
+            yiaddr: Ipv4Addr::from(self.yiaddr),
+            siaddr: Ipv4Addr::from(self.siaddr),
+            giaddr: Ipv4Addr::from(self.giaddr),
+            chaddr: padded(self.chaddr, 16),
+            sname: padded(self.sname, 64),
+            file: padded(self.file, 128),
+            options,
+        })
+    }
+}
+
+#[cfg(feature = "cbor")]
+pub fn to_cbor(msg: &DhcpMessage) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    ::ciborium::ser::into_writer(&CompactMessage::from(msg), &mut out)
+        .map_err(|e| Error::ParseError(format!("CBOR serialization failed: {}", e)))?;
+    Ok(out)
+}
+
+#[cfg(feature = "cbor")]
+pub fn from_cbor(bytes: &[u8]) -> Result<DhcpMessage> {
+    let compact: CompactMessage = ::ciborium::de::from_reader(bytes)
+        .map_err(|e| Error::ParseError(format!("Invalid CBOR message: {}", e)))?;
+    compact.into_message()
+}
+
+#[cfg(feature = "msgpack")]
+pub fn to_msgpack(msg: &DhcpMessage) -> Result<Vec<u8>> {
+    ::rmp_serde::to_vec_named(&CompactMessage::from(msg))
+        .map_err(|e| Error::ParseError(format!("MessagePack serialization failed: {}", e)))
+}
+
+#[cfg(feature = "msgpack")]
+pub fn from_msgpack(bytes: &[u8]) -> Result<DhcpMessage> {
+    let compact: CompactMessage = ::rmp_serde::from_slice(bytes)
+        .map_err(|e| Error::ParseError(format!("Invalid MessagePack message: {}", e)))?;
+    compact.into_message()
+}
+
+#[cfg(test)] mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use DhcpMessage;
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, DhcpMessageTypes};
+
+    fn ack() -> DhcpMessage {
+        let mut sname = b"server".to_vec();
+        sname.resize(64, 0u8);
+        DhcpMessage {
+            op: Op::BootReply,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(10, 0, 0, 5),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname,
+            file: vec![0u8; 128],
+            options: vec![
+                DhcpOption::MessageType(DhcpMessageTypes::Ack),
+                DhcpOption::ServerIdentifier(ip(1)),
+                DhcpOption::ServerIdentifier(ip(2)),
+                DhcpOption::Router(ip_list![ip(1)]),
+                DhcpOption::Router(ip_list![ip(2)]),
+                DhcpOption::Pad,
+                DhcpOption::Router(ip_list![ip(3)]),
+                DhcpOption::End,
+            ],
+        }
+    }
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, last))
+    }
+
+    /// `ack` as it comes back: the back-to-back routers are joined, and
+    /// nothing else is
+    fn merged() -> DhcpMessage {
+        let mut msg = ack();
+        msg.options = vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Ack),
+            DhcpOption::ServerIdentifier(ip(1)),
+            DhcpOption::ServerIdentifier(ip(2)),
+            DhcpOption::Router(ip_list![ip(1), ip(2)]),
+            DhcpOption::Router(ip_list![ip(3)]),
+            DhcpOption::End,
+        ];
+        msg
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_cbor() {
+        use super::{to_cbor, from_cbor};
+
+        let bytes = to_cbor(&ack()).unwrap();
+        assert!(bytes.len() < ack().encode().len());
+        assert_eq!(from_cbor(&bytes).unwrap(), merged());
+        assert!(from_cbor(&bytes[..10]).is_err());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack() {
+        use super::{to_msgpack, from_msgpack};
+
+        let bytes = to_msgpack(&ack()).unwrap();
+        assert!(bytes.len() < ack().encode().len());
+        assert_eq!(from_msgpack(&bytes).unwrap(), merged());
+        assert!(from_msgpack(&bytes[..10]).is_err());
+    }
+}
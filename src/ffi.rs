@@ -0,0 +1,285 @@
+//! C interface
+//!
+//! A message is parsed into an opaque `DhcpHandle`, which the accessors
+//! read from and `dhcp_free` releases. `dhcp_parse` returns one of the
+//! `DHCP_OK`/`DHCP_ERR_*` codes. Options are walked by index; each
+//! one's value is given as its wire bytes, with the fragments of a split
+//! option joined. Pointers returned by the accessors live as long as the
+//! handle. The declarations are in `include/dhcp_parser.h`, which can be
+//! regenerated with `cbindgen --config cbindgen.toml`. Build the library
+//! for C with
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type staticlib
+//! ```
+
+use std::panic;
+use std::ptr;
+use std::slice;
+use DhcpMessage;
+use options::DhcpOption;
+use options::raw::next_raw_option;
+
+/// `dhcp_parse` succeeded
+pub const DHCP_OK: i32 = 0;
+/// A pointer argument was null
+pub const DHCP_ERR_NULL: i32 = -1;
+/// The bytes aren't a DHCP message
+pub const DHCP_ERR_PARSE: i32 = -2;
+/// The parser panicked, which is a bug in this library; the message is
+/// dropped instead of taking the host process down
+pub const DHCP_ERR_PANIC: i32 = -3;
+
+/// A parsed message
+pub struct DhcpHandle {
+    message: DhcpMessage,
+    options: Vec<(u8, Vec<u8>)>,
+}
+
+fn option_value(option: &DhcpOption) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    option.encode(&mut bytes);
+    let mut value = Vec::new();
+    let mut rest = &bytes[..];
+    while let Some((raw, next)) = next_raw_option(rest) {
+        value.extend_from_slice(raw.data);
+        rest = next;
+    }
+    value
+}
+
+/// Parse the `len` bytes at `buf` as a message and store a handle to it
+/// in `handle`. Returns `DHCP_OK`, or an error code with `handle` set to
+/// null.
+///
+/// # Safety
+///
+/// `buf` must point to `len` readable bytes and `handle` must be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_parse(buf: *const u8, len: usize, handle: *mut *mut DhcpHandle) -> i32 {
+    if handle.is_null() {
+        return DHCP_ERR_NULL;
+    }
+    *handle = ptr::null_mut();
+    if buf.is_null() {
+        return DHCP_ERR_NULL;
+    }
+    let bytes = slice::from_raw_parts(buf, len);
+    let parsed = panic::catch_unwind(|| {
+        DhcpMessage::parse(bytes).map(|message| {
+            let options = message.options.iter().map(|o| (o.code(), option_value(o))).collect();
+            DhcpHandle { message, options }
+        })
+    });
+    match parsed {
+        Ok(Ok(parsed)) => {
+            *handle = Box::into_raw(Box::new(parsed));
+            DHCP_OK
+        },
+        Ok(Err(_)) => DHCP_ERR_PARSE,
+        Err(_) => DHCP_ERR_PANIC,
+    }
+}
+
+/// Release a handle from `dhcp_parse`. Null is ignored.
+///
+/// # Safety
+///
+/// `handle` must be null or from `dhcp_parse`, and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_free(handle: *mut DhcpHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// 1 for BOOTREQUEST, 2 for BOOTREPLY
+///
+/// # Safety
+///
+/// `handle` must be a live handle from `dhcp_parse`.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_op(handle: *const DhcpHandle) -> u8 {
+    (*handle).message.op.to_byte()
+}
+
+/// # Safety
+///
+/// `handle` must be a live handle from `dhcp_parse`.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_xid(handle: *const DhcpHandle) -> u32 {
+    (*handle).message.xid
+}
+
+/// The client's address, in host byte order
+///
+/// # Safety
+///
+/// `handle` must be a live handle from `dhcp_parse`.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_ciaddr(handle: *const DhcpHandle) -> u32 {
+    u32::from((*handle).message.ciaddr)
+}
+
+/// The address offered or assigned, in host byte order
+///
+/// # Safety
+///
+/// `handle` must be a live handle from `dhcp_parse`.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_yiaddr(handle: *const DhcpHandle) -> u32 {
+    u32::from((*handle).message.yiaddr)
+}
+
+/// The next server's address, in host byte order
+///
+/// # Safety
+///
+/// `handle` must be a live handle from `dhcp_parse`.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_siaddr(handle: *const DhcpHandle) -> u32 {
+    u32::from((*handle).message.siaddr)
+}
+
+/// The relay's address, in host byte order
+///
+/// # Safety
+///
+/// `handle` must be a live handle from `dhcp_parse`.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_giaddr(handle: *const DhcpHandle) -> u32 {
+    u32::from((*handle).message.giaddr)
+}
+
+/// The first `hlen` bytes of chaddr, at most 16. Their number is stored
+/// in `len`.
+///
+/// # Safety
+///
+/// `handle` must be a live handle from `dhcp_parse` and `len` must be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_chaddr(handle: *const DhcpHandle, len: *mut usize) -> *const u8 {
+    let message = &(*handle).message;
+    *len = ::std::cmp::min(message.hlen as usize, message.chaddr.len());
+    message.chaddr.as_ptr()
+}
+
+/// The number of options, including Pad and End
+///
+/// # Safety
+///
+/// `handle` must be a live handle from `dhcp_parse`.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_option_count(handle: *const DhcpHandle) -> usize {
+    (*handle).options.len()
+}
+
+/// The code of option `index`, or -1 if there are fewer options
+///
+/// # Safety
+///
+/// `handle` must be a live handle from `dhcp_parse`.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_option_code(handle: *const DhcpHandle, index: usize) -> i32 {
+    let options = &(*handle).options;
+    match options.get(index) {
+        Some(&(code, _)) => code as i32,
+        None => -1,
+    }
+}
+
+/// The value of option `index`, with its length stored in `len`, or
+/// null if there are fewer options
+///
+/// # Safety
+///
+/// `handle` must be a live handle from `dhcp_parse` and `len` must be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_option_data(handle: *const DhcpHandle, index: usize, len: *mut usize) -> *const u8 {
+    let options = &(*handle).options;
+    match options.get(index) {
+        Some((_, data)) => {
+            *len = data.len();
+            data.as_ptr()
+        },
+        None => {
+            *len = 0;
+            ptr::null()
+        },
+    }
+}
+
+/// The message type from option 53, or -1 if there isn't one
+///
+/// # Safety
+///
+/// `handle` must be a live handle from `dhcp_parse`.
+#[no_mangle]
+pub unsafe extern "C" fn dhcp_message_type(handle: *const DhcpHandle) -> i32 {
+    (*handle).message.options.iter().filter_map(|o| match o {
+        &DhcpOption::MessageType(t) => Some(u8::from(t) as i32),
+        _ => None,
+    }).next().unwrap_or(-1)
+}
+
+#[cfg(test)] mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use op::Op;
+    use htype::Htype;
+    use options::DhcpMessageTypes;
+
+    #[test]
+    fn test_ffi() {
+        let msg = DhcpMessage {
+            op: Op::BootReply,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(10, 0, 0, 5),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options: vec![
+                DhcpOption::MessageType(DhcpMessageTypes::Offer),
+                DhcpOption::HostName("host".to_string()),
+                DhcpOption::End,
+            ],
+        };
+        let bytes = msg.encode();
+        unsafe {
+            let mut handle = ptr::null_mut();
+            assert_eq!(dhcp_parse(bytes.as_ptr(), 10, &mut handle), DHCP_ERR_PARSE);
+            assert!(handle.is_null());
+            assert_eq!(dhcp_parse(ptr::null(), 0, &mut handle), DHCP_ERR_NULL);
+            assert_eq!(dhcp_parse(bytes.as_ptr(), bytes.len(), ptr::null_mut()), DHCP_ERR_NULL);
+            assert_eq!(dhcp_parse(bytes.as_ptr(), bytes.len(), &mut handle), DHCP_OK);
+            assert!(!handle.is_null());
+            assert_eq!(dhcp_op(handle), 2);
+            assert_eq!(dhcp_xid(handle), 0x3903f326);
+            assert_eq!(dhcp_yiaddr(handle), 0x0a000005);
+            assert_eq!(dhcp_message_type(handle), 2);
+
+            let mut len = 0;
+            let chaddr = dhcp_chaddr(handle, &mut len);
+            assert_eq!(slice::from_raw_parts(chaddr, len), &[0, 11, 130, 1, 252, 66]);
+
+            assert_eq!(dhcp_option_count(handle), 3);
+            assert_eq!(dhcp_option_code(handle, 1), 12);
+            assert_eq!(dhcp_option_code(handle, 3), -1);
+            let data = dhcp_option_data(handle, 1, &mut len);
+            assert_eq!(slice::from_raw_parts(data, len), b"host");
+            assert!(dhcp_option_data(handle, 3, &mut len).is_null());
+            dhcp_free(handle);
+        }
+    }
+}
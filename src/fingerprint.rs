@@ -0,0 +1,301 @@
+//! DHCP client fingerprinting
+//!
+//! Clients tend to send a very stable set of options for a given OS or
+//! firmware version, in particular the order of the codes in the
+//! Parameter Request List (option 55). This pulls those bits out of a
+//! parsed message so they can be fed to a device-identification system.
+//!
+//! `FingerprintDatabase` is a simple one: it maps option 55 signatures
+//! and option 60 prefixes to OS or device classes. The built-in table
+//! behind the `fingerprints` feature only covers a handful of common
+//! clients; load a fuller list with `FingerprintDatabase::from_text`.
+
+use std::cmp::Reverse;
+use std::fmt;
+use RawMessage;
+use hwaddr::MacAddress;
+use options::DhcpOption;
+
+/// What a client's request says about its OS or firmware, from
+/// `from_options` or `from_message`
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Fingerprint {
+    /// Option 55 codes, in the order the client sent them
+    pub param_request_list: Vec<u8>,
+    /// Option 60, decoded lossily since it is usually ASCII
+    pub vendor_class: Option<String>,
+    /// Option 12
+    pub hostname: Option<String>,
+    /// Option 57; clients usually hard-code this, so it helps tell
+    /// apart stacks that request the same parameters
+    pub max_message_size: Option<u16>,
+    /// Option 51; only some clients ask for a particular lease time
+    pub requested_lease_time: Option<u32>,
+    /// From chaddr; only set by `from_message`
+    pub client_mac: Option<MacAddress>,
+}
+
+impl Fingerprint {
+    pub fn from_options(options: &[DhcpOption]) -> Fingerprint {
+        let mut fp = Fingerprint::default();
+        for option in options {
+            match option {
+                DhcpOption::ParamRequestList(codes) => {
+                    fp.param_request_list = codes.clone();
+                },
+                DhcpOption::ClassIdentifier(class) => {
+                    fp.vendor_class = Some(String::from_utf8_lossy(class).into_owned());
+                },
+                DhcpOption::HostName(name) => {
+                    fp.hostname = Some(name.clone());
+                },
+                DhcpOption::MaxMessageSize(size) => {
+                    fp.max_message_size = Some(*size);
+                },
+                DhcpOption::IpAddressLeaseTime(time) => {
+                    fp.requested_lease_time = Some(*time);
+                },
+                _ => {},
+            }
+        }
+        fp
+    }
+
+    pub fn from_message(message: &RawMessage) -> Fingerprint {
+        let mut fp = Fingerprint::from_options(&message.options);
+        fp.client_mac = message.client_mac();
+        fp
+    }
+
+    /// Manufacturer of the client's network interface, from the built-in
+    /// OUI table
+    #[cfg(feature = "oui")]
+    pub fn manufacturer(&self) -> Option<&'static str> {
+        self.client_mac.and_then(|mac| mac.manufacturer())
+    }
+
+    /// The Parameter Request List in the usual "1,3,6,15" form
+    pub fn prl_signature(&self) -> String {
+        self.param_request_list.iter()
+                               .map(|c| c.to_string())
+                               .collect::<Vec<_>>()
+                               .join(",")
+    }
+
+    /// The leading alphabetic part of the hostname, e.g. "android" for
+    /// "android-5f3ac9e1" or "DESKTOP" for "DESKTOP-4JQ1A2B". Per-device
+    /// suffixes are useless for identification but the prefix often
+    /// names the OS or vendor.
+    pub fn hostname_pattern(&self) -> Option<&str> {
+        self.hostname.as_ref().and_then(|name| {
+            let end = name.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(name.len());
+            if end == 0 { None } else { Some(&name[..end]) }
+        })
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.prl_signature())
+    }
+}
+
+/// A known client. At least one of the two fields is set.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FingerprintEntry {
+    /// Option 55 codes, in order
+    pub param_request_list: Option<Vec<u8>>,
+    /// Matches any option 60 starting with this
+    pub vendor_class_prefix: Option<String>,
+    /// The OS or device class, e.g. "Windows 10" or "Android"
+    pub class: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FingerprintMatch<'a> {
+    pub class: &'a str,
+    /// Out of 100: 60 for the same Parameter Request List (40 if it has
+    /// the same codes in another order) plus 40 for the vendor class
+    pub score: u8,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct FingerprintDatabase {
+    entries: Vec<FingerprintEntry>,
+}
+
+impl FingerprintDatabase {
+    pub fn new() -> FingerprintDatabase {
+        FingerprintDatabase { entries: Vec::new() }
+    }
+
+    pub fn insert(&mut self, entry: FingerprintEntry) {
+        if entry.param_request_list.is_some() || entry.vendor_class_prefix.is_some() {
+            self.entries.push(entry);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Load tab-separated lines of signature, vendor class prefix and
+    /// class, where an empty field matches anything, e.g. (with the tabs
+    /// written as `\t`)
+    ///
+    /// ```text
+    /// 1,3,6,15,31,33,43,44,46,47,119,121,249,252\tMSFT 5.0\tWindows 10
+    /// \tandroid-dhcp-\tAndroid
+    /// ```
+    ///
+    /// Blank lines, `#` comments and lines in any other format are ignored.
+    pub fn from_text(text: &str) -> FingerprintDatabase {
+        let mut db = FingerprintDatabase::new();
+        for line in text.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').map(|f| f.trim()).collect();
+            if fields.len() != 3 || fields[2].is_empty() {
+                continue;
+            }
+            let param_request_list = if fields[0].is_empty() {
+                None
+            } else {
+                match fields[0].split(',').map(|c| c.trim().parse::<u8>()).collect() {
+                    Ok(codes) => Some(codes),
+                    Err(_) => continue,
+                }
+            };
+            let vendor_class_prefix = if fields[1].is_empty() { None } else { Some(fields[1].to_string()) };
+            db.insert(FingerprintEntry { param_request_list, vendor_class_prefix, class: fields[2].to_string() });
+        }
+        db
+    }
+
+    /// The table compiled into the crate
+    #[cfg(feature = "fingerprints")]
+    pub fn builtin() -> FingerprintDatabase {
+        FingerprintDatabase::from_text(BUILTIN)
+    }
+
+    /// Every entry `fp` matches, best first. An entry matches if each
+    /// field it sets matches.
+    pub fn lookup(&self, fp: &Fingerprint) -> Vec<FingerprintMatch<'_>> {
+        let mut matches = Vec::new();
+        for entry in &self.entries {
+            let mut score = 0u8;
+            if let Some(ref codes) = entry.param_request_list {
+                if *codes == fp.param_request_list {
+                    score += 60;
+                } else if same_codes(codes, &fp.param_request_list) {
+                    score += 40;
+                } else {
+                    continue;
+                }
+            }
+            if let Some(ref prefix) = entry.vendor_class_prefix {
+                match fp.vendor_class {
+                    Some(ref class) if class.starts_with(&prefix[..]) => score += 40,
+                    _ => continue,
+                }
+            }
+            matches.push(FingerprintMatch { class: &entry.class, score });
+        }
+        matches.sort_by_key(|m| Reverse(m.score));
+        matches
+    }
+}
+
+fn same_codes(a: &[u8], b: &[u8]) -> bool {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort();
+    b.sort();
+    a == b
+}
+
+#[cfg(feature = "fingerprints")]
+static BUILTIN: &str = "\
+1,15,3,6,44,46,47,31,33,249,43\tMSFT 5.0\tWindows XP
+1,15,3,6,44,46,47,31,33,121,249,43,252\tMSFT 5.0\tWindows 7
+1,3,6,15,31,33,43,44,46,47,119,121,249,252\tMSFT 5.0\tWindows 10
+1,121,3,6,15,119,252,95,44,46\t\tmacOS
+1,121,3,6,15,119,252\t\tiOS
+1,3,6,15,26,28,51,58,59,43\tandroid-dhcp-\tAndroid
+\tandroid-dhcp-\tAndroid
+1,28,2,3,15,6,119,12,44,47,26,121,42\t\tLinux (dhclient)
+\tudhcp\tEmbedded Linux (udhcpc)
+\tCisco Systems, Inc. IP Phone\tCisco IP Phone
+";
+
+#[cfg(test)] mod tests {
+    use super::{Fingerprint, FingerprintDatabase};
+    use options::DhcpOption;
+
+    #[test]
+    fn test_fingerprint_from_options() {
+        let options = vec![
+            DhcpOption::ClassIdentifier(b"MSFT 5.0".to_vec()),
+            DhcpOption::HostName("DESKTOP-4JQ1A2B".to_string()),
+            DhcpOption::ParamRequestList(vec![1u8, 3u8, 6u8, 15u8, 31u8, 33u8, 43u8, 44u8]),
+            DhcpOption::MaxMessageSize(1500),
+            DhcpOption::End,
+        ];
+        let fp = Fingerprint::from_options(&options);
+        assert_eq!(fp.prl_signature(), "1,3,6,15,31,33,43,44");
+        assert_eq!(fp.to_string(), "1,3,6,15,31,33,43,44");
+        assert_eq!(fp.vendor_class, Some("MSFT 5.0".to_string()));
+        assert_eq!(fp.hostname_pattern(), Some("DESKTOP"));
+        assert_eq!(fp.max_message_size, Some(1500));
+        assert_eq!(fp.requested_lease_time, None);
+    }
+
+    #[test]
+    fn test_fingerprint_empty() {
+        let fp = Fingerprint::from_options(&[]);
+        assert_eq!(fp.prl_signature(), "");
+        assert_eq!(fp.hostname_pattern(), None);
+    }
+
+    #[test]
+    fn test_fingerprint_database() {
+        let db = FingerprintDatabase::from_text("# signature, vendor class, class\n\
+                                                 1,3,6\tMSFT 5.0\tWindows\n\
+                                                 1,3,6\t\tSomething\n\
+                                                 \tMSFT\tMicrosoft\n\
+                                                 1,x\t\tBroken\n\
+                                                 \t\tNothing\n");
+        assert_eq!(db.len(), 3);
+
+        let mut fp = Fingerprint {
+            param_request_list: vec![1u8, 3u8, 6u8],
+            vendor_class: Some("MSFT 5.0".to_string()),
+            ..Fingerprint::default()
+        };
+        let matches: Vec<(&str, u8)> = db.lookup(&fp).iter().map(|m| (m.class, m.score)).collect();
+        assert_eq!(matches, vec![("Windows", 100), ("Something", 60), ("Microsoft", 40)]);
+
+        fp.param_request_list = vec![6u8, 3u8, 1u8];
+        fp.vendor_class = None;
+        let matches: Vec<(&str, u8)> = db.lookup(&fp).iter().map(|m| (m.class, m.score)).collect();
+        assert_eq!(matches, vec![("Something", 40)]);
+    }
+
+    #[cfg(feature = "fingerprints")]
+    #[test]
+    fn test_builtin() {
+        let fp = Fingerprint {
+            param_request_list: vec![1u8, 3u8, 6u8, 15u8, 26u8, 28u8, 51u8, 58u8, 59u8, 43u8],
+            vendor_class: Some("android-dhcp-13".to_string()),
+            ..Fingerprint::default()
+        };
+        let db = FingerprintDatabase::builtin();
+        let best = db.lookup(&fp)[0];
+        assert_eq!((best.class, best.score), ("Android", 100));
+    }
+}
@@ -1,9 +1,17 @@
-use super::{Result, Error};
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[allow(non_camel_case_types)]
 pub enum Htype {
-    Ethernet_10mb = 1,
+    Ethernet_10mb,
     Experimental_Ethernet_3mb,
     Amateur_Radio_AX_25,
     Proteon_ProNET_Token_Ring,
@@ -19,28 +27,128 @@ pub enum Htype {
     SMDS,
     Frame_Relay,
     Asynchronous_Transmission_Mode,
+    HDLC,
+    Fibre_Channel,
+    Asynchronous_Transmission_Mode_RFC2225,
+    Serial_Line,
+    Asynchronous_Transmission_Mode_21,
+    MIL_STD_188_220,
+    Metricom,
+    IEEE_1394_1995,
+    MAPOS,
+    Twinaxial,
+    EUI_64,
+    HIPARP,
+    ISO_7816_3,
+    ARPSec,
+    IPsec_Tunnel,
+    InfiniBand,
+    TIA_102_Project_25,
+    Wiegand_Interface,
+    Pure_IP,
+    HW_EXP1,
+    HFI,
+    /// A hardware type this crate doesn't know yet
+    Other(u8),
 }
 
 impl Htype {
-    pub fn from_byte(byte: u8) -> Result<Htype> {
+    pub fn from_byte(byte: u8) -> Htype {
         match byte {
-            1u8 => Ok(Htype::Ethernet_10mb),
-            2u8 => Ok(Htype::Experimental_Ethernet_3mb),
-            3u8 => Ok(Htype::Amateur_Radio_AX_25),
-            4u8 => Ok(Htype::Proteon_ProNET_Token_Ring),
-            5u8 => Ok(Htype::Chaos),
-            6u8 => Ok(Htype::IEEE_802_Networks),
-            7u8 => Ok(Htype::Arcnet),
-            8u8 => Ok(Htype::Hyperchannel),
-            9u8 => Ok(Htype::Lanstar),
-            10u8 => Ok(Htype::Autonet_Short_Address),
-            11u8 => Ok(Htype::LocalTalk),
-            12u8 => Ok(Htype::LocalNet),
-            13u8 => Ok(Htype::Ultra_link),
-            14u8 => Ok(Htype::SMDS),
-            15u8 => Ok(Htype::Frame_Relay),
-            16u8 => Ok(Htype::Asynchronous_Transmission_Mode),
-            _ => Err(Error::ParseError(format!("Unknown Htype {:?}", byte)))
+            1u8 => Htype::Ethernet_10mb,
+            2u8 => Htype::Experimental_Ethernet_3mb,
+            3u8 => Htype::Amateur_Radio_AX_25,
+            4u8 => Htype::Proteon_ProNET_Token_Ring,
+            5u8 => Htype::Chaos,
+            6u8 => Htype::IEEE_802_Networks,
+            7u8 => Htype::Arcnet,
+            8u8 => Htype::Hyperchannel,
+            9u8 => Htype::Lanstar,
+            10u8 => Htype::Autonet_Short_Address,
+            11u8 => Htype::LocalTalk,
+            12u8 => Htype::LocalNet,
+            13u8 => Htype::Ultra_link,
+            14u8 => Htype::SMDS,
+            15u8 => Htype::Frame_Relay,
+            16u8 => Htype::Asynchronous_Transmission_Mode,
+            17u8 => Htype::HDLC,
+            18u8 => Htype::Fibre_Channel,
+            19u8 => Htype::Asynchronous_Transmission_Mode_RFC2225,
+            20u8 => Htype::Serial_Line,
+            21u8 => Htype::Asynchronous_Transmission_Mode_21,
+            22u8 => Htype::MIL_STD_188_220,
+            23u8 => Htype::Metricom,
+            24u8 => Htype::IEEE_1394_1995,
+            25u8 => Htype::MAPOS,
+            26u8 => Htype::Twinaxial,
+            27u8 => Htype::EUI_64,
+            28u8 => Htype::HIPARP,
+            29u8 => Htype::ISO_7816_3,
+            30u8 => Htype::ARPSec,
+            31u8 => Htype::IPsec_Tunnel,
+            32u8 => Htype::InfiniBand,
+            33u8 => Htype::TIA_102_Project_25,
+            34u8 => Htype::Wiegand_Interface,
+            35u8 => Htype::Pure_IP,
+            36u8 => Htype::HW_EXP1,
+            37u8 => Htype::HFI,
+            _ => Htype::Other(byte),
+        }
+    }
+
+    /// The length of this type's addresses in chaddr, where it is fixed.
+    /// InfiniBand's are too long for chaddr, so it is left empty and the
+    /// client identifier carries them instead (RFC 4390).
+    pub fn address_len(&self) -> Option<usize> {
+        match self {
+            &Htype::Ethernet_10mb | &Htype::IEEE_802_Networks => Some(6),
+            &Htype::Experimental_Ethernet_3mb => Some(1),
+            &Htype::IEEE_1394_1995 | &Htype::EUI_64 => Some(8),
+            &Htype::InfiniBand => Some(0),
+            _ => None,
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match *self {
+            Htype::Ethernet_10mb => 1u8,
+            Htype::Experimental_Ethernet_3mb => 2u8,
+            Htype::Amateur_Radio_AX_25 => 3u8,
+            Htype::Proteon_ProNET_Token_Ring => 4u8,
+            Htype::Chaos => 5u8,
+            Htype::IEEE_802_Networks => 6u8,
+            Htype::Arcnet => 7u8,
+            Htype::Hyperchannel => 8u8,
+            Htype::Lanstar => 9u8,
+            Htype::Autonet_Short_Address => 10u8,
+            Htype::LocalTalk => 11u8,
+            Htype::LocalNet => 12u8,
+            Htype::Ultra_link => 13u8,
+            Htype::SMDS => 14u8,
+            Htype::Frame_Relay => 15u8,
+            Htype::Asynchronous_Transmission_Mode => 16u8,
+            Htype::HDLC => 17u8,
+            Htype::Fibre_Channel => 18u8,
+            Htype::Asynchronous_Transmission_Mode_RFC2225 => 19u8,
+            Htype::Serial_Line => 20u8,
+            Htype::Asynchronous_Transmission_Mode_21 => 21u8,
+            Htype::MIL_STD_188_220 => 22u8,
+            Htype::Metricom => 23u8,
+            Htype::IEEE_1394_1995 => 24u8,
+            Htype::MAPOS => 25u8,
+            Htype::Twinaxial => 26u8,
+            Htype::EUI_64 => 27u8,
+            Htype::HIPARP => 28u8,
+            Htype::ISO_7816_3 => 29u8,
+            Htype::ARPSec => 30u8,
+            Htype::IPsec_Tunnel => 31u8,
+            Htype::InfiniBand => 32u8,
+            Htype::TIA_102_Project_25 => 33u8,
+            Htype::Wiegand_Interface => 34u8,
+            Htype::Pure_IP => 35u8,
+            Htype::HW_EXP1 => 36u8,
+            Htype::HFI => 37u8,
+            Htype::Other(byte) => byte,
         }
     }
 }
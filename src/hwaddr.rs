@@ -0,0 +1,200 @@
+//! Hardware addresses
+
+use std::fmt;
+use std::str::FromStr;
+use {Result, Error};
+use htype::Htype;
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "oui")]
+use oui;
+use oui::OuiProvider;
+
+/// A 48-bit IEEE MAC address
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct MacAddress(pub [u8; 6]);
+
+impl MacAddress {
+    /// Build a MAC address from a slice of exactly six bytes, e.g. a chaddr
+    /// with `hlen` 6 or a MAC-formatted Agent Remote ID
+    pub fn from_slice(bytes: &[u8]) -> Option<MacAddress> {
+        if bytes.len() != 6 {
+            return None;
+        }
+        let mut mac = [0u8; 6];
+        mac.copy_from_slice(bytes);
+        Some(MacAddress(mac))
+    }
+
+    pub fn oui(&self) -> [u8; 3] {
+        [self.0[0], self.0[1], self.0[2]]
+    }
+
+    /// Randomized (privacy) addresses set this bit; their OUI means nothing
+    pub fn is_locally_administered(&self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    /// Look up the manufacturer in the built-in OUI table
+    #[cfg(feature = "oui")]
+    pub fn manufacturer(&self) -> Option<&'static str> {
+        self.manufacturer_with(&oui::BuiltinOui)
+    }
+
+    /// Look up the manufacturer using `provider`
+    pub fn manufacturer_with<'p, P: OuiProvider + ?Sized>(&self, provider: &'p P) -> Option<&'p str> {
+        if self.is_locally_administered() {
+            return None;
+        }
+        provider.lookup(self.oui())
+    }
+}
+
+impl fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let b = &self.0;
+        write!(f, "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}", b[0], b[1], b[2], b[3], b[4], b[5])
+    }
+}
+
+/// A hardware address of any type, as in chaddr or a client identifier
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct HardwareAddr {
+    pub htype: Htype,
+    pub addr: Vec<u8>,
+}
+
+impl HardwareAddr {
+    pub fn new(htype: Htype, addr: Vec<u8>) -> HardwareAddr {
+        HardwareAddr { htype, addr }
+    }
+
+    pub fn from_mac(mac: MacAddress) -> HardwareAddr {
+        HardwareAddr::new(Htype::Ethernet_10mb, mac.0.to_vec())
+    }
+
+    /// The address as a MAC address, if it is a six byte Ethernet or
+    /// IEEE 802 one
+    pub fn mac(&self) -> Option<MacAddress> {
+        if self.htype != Htype::Ethernet_10mb && self.htype != Htype::IEEE_802_Networks {
+            return None;
+        }
+        MacAddress::from_slice(&self.addr)
+    }
+}
+
+impl From<MacAddress> for HardwareAddr {
+    fn from(mac: MacAddress) -> HardwareAddr {
+        HardwareAddr::from_mac(mac)
+    }
+}
+
+/// Colon-separated hex, e.g. `00:0b:82:01:fc:42`. Other types than
+/// Ethernet are prefixed with the type number, e.g. `6/00:0b:82:01:fc:42`.
+impl fmt::Display for HardwareAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.htype != Htype::Ethernet_10mb {
+            write!(f, "{}/", self.htype.to_byte())?;
+        }
+        for (i, b) in self.addr.iter().enumerate() {
+            if i > 0 {
+                write!(f, ":")?;
+            }
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// The inverse of `Display`; dashes are also accepted between bytes.
+/// Nothing after the type number is an empty address, as InfiniBand's
+/// is in chaddr, e.g. `32/`.
+impl FromStr for HardwareAddr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<HardwareAddr> {
+        let invalid = || Error::ParseError(format!("Invalid hardware address {:?}", s));
+        let (htype, hex) = match s.find('/') {
+            Some(slash) => {
+                let byte = s[..slash].parse::<u8>().map_err(|_| invalid())?;
+                (Htype::from_byte(byte), &s[slash + 1..])
+            },
+            None => (Htype::Ethernet_10mb, s),
+        };
+        let mut addr = Vec::new();
+        if hex.is_empty() && htype != Htype::Ethernet_10mb {
+            return Ok(HardwareAddr { htype, addr });
+        }
+        for part in hex.split([':', '-']) {
+            if part.len() != 2 {
+                return Err(invalid());
+            }
+            addr.push(u8::from_str_radix(part, 16).map_err(|_| invalid())?);
+        }
+        Ok(HardwareAddr { htype, addr })
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{MacAddress, HardwareAddr};
+    use htype::Htype;
+    use oui::OuiTable;
+
+    #[test]
+    fn test_mac_address() {
+        let mac = MacAddress::from_slice(&[0u8, 80u8, 86u8, 192u8, 0u8, 8u8]).unwrap();
+        assert_eq!(mac.to_string(), "00:50:56:c0:00:08");
+        assert_eq!(mac.oui(), [0u8, 80u8, 86u8]);
+        assert!(!mac.is_locally_administered());
+        assert_eq!(MacAddress::from_slice(&[0u8; 16]), None);
+    }
+
+    #[test]
+    fn test_manufacturer_with() {
+        let table = OuiTable::from_ieee_txt("00-50-56   (hex)\t\tVMware, Inc.\n");
+        let mac = MacAddress([0u8, 80u8, 86u8, 1u8, 2u8, 3u8]);
+        assert_eq!(mac.manufacturer_with(&table), Some("VMware, Inc."));
+        let random = MacAddress([2u8, 80u8, 86u8, 1u8, 2u8, 3u8]);
+        assert_eq!(random.manufacturer_with(&table), None);
+    }
+
+    #[test]
+    fn test_hardware_addr() {
+        let mac = MacAddress([0u8, 11u8, 130u8, 1u8, 252u8, 66u8]);
+        let addr = HardwareAddr::from_mac(mac);
+        assert_eq!(addr.to_string(), "00:0b:82:01:fc:42");
+        assert_eq!("00-0b-82-01-fc-42".parse::<HardwareAddr>().unwrap(), addr);
+        assert_eq!(addr.mac(), Some(mac));
+
+        let ieee802 = HardwareAddr::new(Htype::IEEE_802_Networks, vec![1u8, 2u8]);
+        assert_eq!(ieee802.to_string(), "6/01:02");
+        assert_eq!("6/01:02".parse::<HardwareAddr>().unwrap(), ieee802);
+        assert_eq!(ieee802.mac(), None);
+
+        assert!("".parse::<HardwareAddr>().is_err());
+        assert!("0:1".parse::<HardwareAddr>().is_err());
+        assert!("256/01".parse::<HardwareAddr>().is_err());
+
+        let infiniband = HardwareAddr::new(Htype::InfiniBand, vec![]);
+        assert_eq!(infiniband.to_string(), "32/");
+        assert_eq!("32/".parse::<HardwareAddr>().unwrap(), infiniband);
+
+        let unknown = HardwareAddr::new(Htype::Other(99u8), vec![1u8]);
+        assert_eq!(unknown.to_string(), "99/01");
+        assert_eq!("99/01".parse::<HardwareAddr>().unwrap(), unknown);
+    }
+}
@@ -0,0 +1,162 @@
+//! Parsing from buffers other than plain slices
+//!
+//! The parse functions accept anything that is `AsRef<[u8]>`, so
+//! `bytes::Bytes`, memory maps and `Vec<u8>` can be handed over directly
+//! and messages borrow from them without copying. `Input` adds the
+//! position of the buffer within a larger capture, so option locations
+//! can be reported relative to the capture rather than the message.
+
+use {parse_message, RawMessage, Result, Error, OPTIONS_OFFSET};
+use options::{self, DhcpOption, RawOption};
+use options::raw::next_raw_option;
+
+#[derive(Debug, Clone)]
+pub struct Input<T> {
+    buf: T,
+    offset: usize,
+}
+
+impl<T: AsRef<[u8]>> Input<T> {
+    pub fn new(buf: T) -> Input<T> {
+        Input { buf, offset: 0 }
+    }
+
+    /// `buf` starts `offset` bytes into some larger buffer
+    pub fn at(buf: T, offset: usize) -> Input<T> {
+        Input { buf, offset }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        self.buf.as_ref()
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn into_inner(self) -> T {
+        self.buf
+    }
+
+    /// Parse the buffer as a whole message
+    pub fn parse_message(&self) -> Result<RawMessage<'_>> {
+        parse_message(self.bytes())
+    }
+
+    /// Parse the buffer as an options area
+    pub fn parse_options(&self) -> Result<Vec<DhcpOption>> {
+        options::parse(self.bytes())
+    }
+
+    /// The undecoded options of the message in the buffer, each with the
+    /// absolute offset of its code byte
+    pub fn message_options(&self) -> RawOptionOffsets<'_> {
+        let bytes = self.bytes();
+        let start = ::std::cmp::min(OPTIONS_OFFSET, bytes.len());
+        RawOptionOffsets { remaining: &bytes[start..], offset: self.offset + start }
+    }
+}
+
+/// Iterator over `(offset, option)` pairs, stopping after `End`
+pub struct RawOptionOffsets<'a> {
+    remaining: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for RawOptionOffsets<'a> {
+    type Item = (usize, RawOption<'a>);
+
+    fn next(&mut self) -> Option<(usize, RawOption<'a>)> {
+        let (opt, rest) = next_raw_option(self.remaining)?;
+        let offset = self.offset;
+        self.offset += self.remaining.len() - rest.len();
+        self.remaining = if opt.code == 255u8 { &rest[rest.len()..] } else { rest };
+        Some((offset, opt))
+    }
+}
+
+/// Iterator over the messages in a buffer of length-prefixed datagrams
+pub struct Messages<'a> {
+    remaining: &'a [u8],
+}
+
+/// Walk `bytes` as a run of messages, each preceded by its length as a
+/// big-endian `u16`, as replay and load-test tools write them. Messages
+/// borrow from `bytes`. One that fails to parse is yielded as an error
+/// and skipped; a cut off length or message yields one error and ends
+/// the iteration.
+pub fn parse_many<'a>(bytes: &'a [u8]) -> Messages<'a> {
+    Messages { remaining: bytes }
+}
+
+impl<'a> Iterator for Messages<'a> {
+    type Item = Result<RawMessage<'a>>;
+
+    fn next(&mut self) -> Option<Result<RawMessage<'a>>> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        let remaining = self.remaining;
+        self.remaining = &[];
+        let (len, rest) = match remaining {
+            [hi, lo, rest @ ..] => ((*hi as usize) << 8 | *lo as usize, rest),
+            _ => return Some(Err(Error::ParseError("Truncated message length".to_string()))),
+        };
+        if rest.len() < len {
+            return Some(Err(Error::ParseError(format!("Message of {} bytes is truncated", len))));
+        }
+        let (message, rest) = rest.split_at(len);
+        self.remaining = rest;
+        Some(parse_message(message))
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{Input, parse_many};
+    use options::{DhcpOption, RawOption};
+
+    #[test]
+    fn test_input_generic_buffers() {
+        let options: Box<[u8]> = vec![53u8, 1u8, 1u8, 255u8].into_boxed_slice();
+        let input = Input::new(options);
+        assert_eq!(input.parse_options().unwrap().len(), 2);
+        assert_eq!(::options::parse(&vec![0u8]).unwrap(), vec![DhcpOption::Pad]);
+    }
+
+    #[test]
+    fn test_message_option_offsets() {
+        let mut message = vec![0u8; 236];
+        message.extend_from_slice(&[99u8, 130u8, 83u8, 99u8, 53u8, 1u8, 1u8, 0u8, 255u8, 0u8]);
+        let input = Input::at(&message[..], 1000);
+        let offsets: Vec<_> = input.message_options().collect();
+        assert_eq!(offsets, vec![
+            (1240, RawOption { code: 53u8, data: &[1u8] }),
+            (1243, RawOption { code: 0u8, data: &[] }),
+            (1244, RawOption { code: 255u8, data: &[] }),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_many() {
+        let mut message = vec![1u8, 1u8, 6u8, 0u8];
+        message.resize(236, 0u8);
+        message.extend_from_slice(&[99u8, 130u8, 83u8, 99u8, 53u8, 1u8, 1u8, 255u8]);
+        let mut bytes = Vec::new();
+        for xid in 1..3u8 {
+            message[7] = xid;
+            bytes.extend_from_slice(&[0u8, message.len() as u8]);
+            bytes.extend_from_slice(&message);
+        }
+        bytes.extend_from_slice(&[0u8, 3u8, 1u8, 1u8, 6u8]);
+        bytes.extend_from_slice(&[0u8, 250u8, 1u8]);
+
+        let mut messages = parse_many(&bytes);
+        assert_eq!(messages.next().unwrap().unwrap().xid, 1);
+        let second = messages.next().unwrap().unwrap();
+        assert_eq!(second.xid, 2);
+        assert_eq!(second.options, vec![DhcpOption::MessageType(::options::DhcpMessageTypes::Discover), DhcpOption::End]);
+        assert!(messages.next().unwrap().is_err());
+        assert!(messages.next().unwrap().is_err());
+        assert!(messages.next().is_none());
+    }
+}
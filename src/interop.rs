@@ -0,0 +1,98 @@
+//! Conversions to and from the `dhcproto` crate's v4 types
+//!
+//! Values are converted through their wire form, so anything one crate
+//! can encode and the other can decode comes across, and each crate
+//! decides for itself how to represent an option the other doesn't know.
+//! dhcproto keeps options in a map, so a message's option order is lost
+//! on the way through it.
+
+use std::convert::TryFrom;
+use dhcproto::{Decodable, Encodable};
+use dhcproto::v4;
+use {DhcpMessage, Error};
+use options::{self, DhcpOption};
+
+fn dhcproto_error<E: ::std::fmt::Display>(e: E) -> Error {
+    Error::ParseError(format!("dhcproto: {}", e))
+}
+
+impl<'a> TryFrom<&'a DhcpOption> for v4::DhcpOption {
+    type Error = Error;
+
+    fn try_from(option: &'a DhcpOption) -> Result<v4::DhcpOption, Error> {
+        let mut bytes = Vec::new();
+        option.encode(&mut bytes);
+        v4::DhcpOption::from_bytes(&bytes).map_err(dhcproto_error)
+    }
+}
+
+impl<'a> TryFrom<&'a v4::DhcpOption> for DhcpOption {
+    type Error = Error;
+
+    fn try_from(option: &'a v4::DhcpOption) -> Result<DhcpOption, Error> {
+        let bytes = option.to_vec().map_err(dhcproto_error)?;
+        options::parse_single_option(&bytes).map(|(option, _)| option)
+    }
+}
+
+impl<'a> TryFrom<&'a DhcpMessage> for v4::Message {
+    type Error = Error;
+
+    fn try_from(msg: &'a DhcpMessage) -> Result<v4::Message, Error> {
+        v4::Message::from_bytes(&msg.encode()).map_err(dhcproto_error)
+    }
+}
+
+impl<'a> TryFrom<&'a v4::Message> for DhcpMessage {
+    type Error = Error;
+
+    fn try_from(msg: &'a v4::Message) -> Result<DhcpMessage, Error> {
+        DhcpMessage::parse(&msg.to_vec().map_err(dhcproto_error)?)
+    }
+}
+
+#[cfg(test)] mod tests {
+    use std::convert::TryFrom;
+    use std::net::{IpAddr, Ipv4Addr};
+    use dhcproto::v4;
+    use DhcpMessage;
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, DhcpMessageTypes};
+
+    #[test]
+    fn test_option_conversions() {
+        let router = DhcpOption::Router(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]);
+        let theirs = v4::DhcpOption::try_from(&router).unwrap();
+        assert_eq!(theirs, v4::DhcpOption::Router(vec![Ipv4Addr::new(10, 0, 0, 1)]));
+        assert_eq!(DhcpOption::try_from(&theirs).unwrap(), router);
+
+        let message_type = v4::DhcpOption::MessageType(v4::MessageType::Offer);
+        assert_eq!(DhcpOption::try_from(&message_type).unwrap(), DhcpOption::MessageType(DhcpMessageTypes::Offer));
+    }
+
+    #[test]
+    fn test_message_conversions() {
+        let msg = DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(0, 0, 0, 0),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0u8; 64],
+            file: vec![0u8; 128],
+            options: vec![DhcpOption::MessageType(DhcpMessageTypes::Discover), DhcpOption::End],
+        };
+        let theirs = v4::Message::try_from(&msg).unwrap();
+        assert_eq!(theirs.xid(), 0x3903f326);
+        assert_eq!(theirs.opts().msg_type(), Some(v4::MessageType::Discover));
+        assert_eq!(DhcpMessage::try_from(&theirs).unwrap(), msg);
+    }
+}
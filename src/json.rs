@@ -0,0 +1,90 @@
+//! JSON output with a stable layout, for golden-file tests
+//!
+//! Options are always in wire order and struct fields in declaration
+//! order. Variant and field names are the Rust names, so renaming one is
+//! a breaking change to this output. `to_canonical_json` additionally
+//! sorts object keys and drops all whitespace, so its output only depends
+//! on the data and not on the order fields happen to be declared in.
+
+use std::fmt::Write;
+use serde::Serialize;
+use serde_json::{self, Value};
+use {Result, Error};
+
+fn json_error(e: serde_json::Error) -> Error {
+    Error::ParseError(format!("JSON serialization failed: {}", e))
+}
+
+/// Indented JSON, in field declaration and wire order
+pub fn to_json_pretty<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string_pretty(value).map_err(json_error)
+}
+
+/// Compact JSON with object keys sorted at every level
+pub fn to_canonical_json<T: Serialize>(value: &T) -> Result<String> {
+    let value = serde_json::to_value(value).map_err(json_error)?;
+    let mut out = String::new();
+    write_canonical(&value, &mut out)?;
+    Ok(out)
+}
+
+fn write_canonical(value: &Value, out: &mut String) -> Result<()> {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out)?;
+            }
+            out.push(']');
+        },
+        Value::Object(map) => {
+            // Sort here rather than relying on serde_json's map type, which
+            // keeps insertion order if its `preserve_order` feature is on
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::to_string(key).map_err(json_error)?);
+                out.push(':');
+                write_canonical(item, out)?;
+            }
+            out.push('}');
+        },
+        scalar => {
+            write!(out, "{}", scalar).map_err(|e| Error::ParseError(e.to_string()))?;
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)] mod tests {
+    use super::{to_canonical_json, to_json_pretty};
+    use serde::Serialize;
+    use options::{DhcpOption, DhcpMessageTypes};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn test_canonical_json() {
+        let options = vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Ack),
+            DhcpOption::Router(ip_list![IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))]),
+            DhcpOption::End,
+        ];
+        assert_eq!(to_canonical_json(&options).unwrap(),
+                   r#"[{"MessageType":"Ack"},{"Router":["192.168.1.1"]},"End"]"#);
+    }
+
+    #[test]
+    fn test_canonical_json_sorts_keys() {
+        #[derive(Serialize)]
+        struct Unsorted { b: u8, a: u8 }
+        assert_eq!(to_canonical_json(&Unsorted { b: 2, a: 1 }).unwrap(), r#"{"a":1,"b":2}"#);
+        assert_eq!(to_json_pretty(&Unsorted { b: 2, a: 1 }).unwrap(), "{\n  \"b\": 2,\n  \"a\": 1\n}");
+    }
+}
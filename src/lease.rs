@@ -0,0 +1,132 @@
+//! The configuration an ACK hands out, summarized
+//!
+//! `Lease` pulls the address, subnet mask, routers, DNS servers and
+//! lease timers out of a message, with the times as `Duration`s, for
+//! code that wants to configure an interface or report on leases rather
+//! than deal with individual options.
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+use DhcpMessage;
+use options::DhcpOption;
+
+/// Lease time that means the lease never expires
+const INFINITE_LEASE: u32 = 0xffffffff;
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Lease {
+    /// yiaddr; only set by `from_message`
+    pub address: Option<Ipv4Addr>,
+    /// Option 1
+    pub subnet_mask: Option<Ipv4Addr>,
+    /// Option 3
+    pub routers: Vec<Ipv4Addr>,
+    /// Option 6
+    pub dns_servers: Vec<Ipv4Addr>,
+    /// Option 54
+    pub server: Option<Ipv4Addr>,
+    /// Option 51; `is_infinite` tells whether it is the "infinity" value
+    pub lease_time: Option<Duration>,
+    /// Option 58, if the server sent it; see `t1`
+    pub renewal_time: Option<Duration>,
+    /// Option 59, if the server sent it; see `t2`
+    pub rebinding_time: Option<Duration>,
+}
+
+fn ipv4s(addrs: &[IpAddr]) -> Vec<Ipv4Addr> {
+    addrs.iter().filter_map(|a| match a {
+        &IpAddr::V4(a) => Some(a),
+        _ => None,
+    }).collect()
+}
+
+impl Lease {
+    pub fn from_options(options: &[DhcpOption]) -> Lease {
+        let seconds = |t: u32| Some(Duration::from_secs(t as u64));
+        let mut lease = Lease::default();
+        for option in options {
+            match *option {
+                DhcpOption::SubnetMask(IpAddr::V4(mask)) => lease.subnet_mask = Some(mask),
+                DhcpOption::Router(ref addrs) => lease.routers = ipv4s(addrs),
+                DhcpOption::DomainNameServer(ref addrs) => lease.dns_servers = ipv4s(addrs),
+                DhcpOption::ServerIdentifier(IpAddr::V4(addr)) => lease.server = Some(addr),
+                DhcpOption::IpAddressLeaseTime(t) => lease.lease_time = seconds(t),
+                DhcpOption::RenewalTimeValue(t) => lease.renewal_time = seconds(t),
+                DhcpOption::RebindingTimeValue(t) => lease.rebinding_time = seconds(t),
+                _ => {},
+            }
+        }
+        lease
+    }
+
+    pub fn from_message(message: &DhcpMessage) -> Lease {
+        let mut lease = Lease::from_options(&message.options);
+        lease.address = Some(message.yiaddr);
+        lease
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        self.lease_time == Some(Duration::from_secs(INFINITE_LEASE as u64))
+    }
+
+    /// When to start renewing: option 58, or half the lease time.
+    /// `None` for an infinite lease or one without a lease time.
+    pub fn t1(&self) -> Option<Duration> {
+        self.timer(self.renewal_time, 1, 2)
+    }
+
+    /// When to start rebinding: option 59, or seven eighths of the lease
+    /// time. `None` for an infinite lease or one without a lease time.
+    pub fn t2(&self) -> Option<Duration> {
+        self.timer(self.rebinding_time, 7, 8)
+    }
+
+    fn timer(&self, sent: Option<Duration>, numerator: u32, denominator: u32) -> Option<Duration> {
+        if self.is_infinite() {
+            return None;
+        }
+        sent.or(self.lease_time.map(|t| t * numerator / denominator))
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::Lease;
+    use options::DhcpOption;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::time::Duration;
+
+    #[test]
+    fn test_lease_from_options() {
+        let options = vec![
+            DhcpOption::SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+            DhcpOption::Router(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]),
+            DhcpOption::DomainNameServer(ip_list![
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 53)),
+                IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)),
+            ]),
+            DhcpOption::ServerIdentifier(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))),
+            DhcpOption::IpAddressLeaseTime(3600),
+            DhcpOption::RebindingTimeValue(3000),
+            DhcpOption::End,
+        ];
+        let lease = Lease::from_options(&options);
+        assert_eq!(lease.address, None);
+        assert_eq!(lease.subnet_mask, Some(Ipv4Addr::new(255, 255, 255, 0)));
+        assert_eq!(lease.routers, vec![Ipv4Addr::new(10, 0, 0, 1)]);
+        assert_eq!(lease.dns_servers, vec![Ipv4Addr::new(10, 0, 0, 53)]);
+        assert_eq!(lease.server, Some(Ipv4Addr::new(10, 0, 0, 2)));
+        assert_eq!(lease.lease_time, Some(Duration::from_secs(3600)));
+        assert!(!lease.is_infinite());
+        assert_eq!(lease.t1(), Some(Duration::from_secs(1800)));
+        assert_eq!(lease.t2(), Some(Duration::from_secs(3000)));
+    }
+
+    #[test]
+    fn test_infinite_lease() {
+        let lease = Lease::from_options(&[DhcpOption::IpAddressLeaseTime(0xffffffff)]);
+        assert!(lease.is_infinite());
+        assert_eq!(lease.t1(), None);
+        assert_eq!(lease.t2(), None);
+        assert_eq!(Lease::from_options(&[]).t1(), None);
+    }
+}
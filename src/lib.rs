@@ -4,51 +4,148 @@
 
 // #[plugin] #[no_link] extern crate rest_easy;
 
-/// DHCP Parsing
-///
-/// Takes bytes and turns them into Rust datatypes
+#![cfg_attr(not(test), deny(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
+//! DHCP Parsing
+//!
+//! Takes bytes and turns them into Rust datatypes
 
 #[macro_use] extern crate nom;
 #[macro_use] extern crate enum_primitive;
 extern crate num;
+#[cfg(feature = "heapless")] extern crate heapless;
+#[cfg(feature = "bumpalo")] extern crate bumpalo;
+#[cfg(feature = "smallvec")] extern crate smallvec;
+#[cfg(feature = "rayon")] extern crate rayon;
+#[cfg(feature = "dhcproto")] extern crate dhcproto;
+#[cfg(feature = "pnet")] extern crate pnet_packet;
+#[cfg(feature = "wasm")] extern crate wasm_bindgen;
+#[cfg(feature = "tracing")] extern crate tracing;
+#[cfg(feature = "arbitrary")] extern crate arbitrary;
+#[cfg(feature = "proptest")] extern crate proptest;
+#[cfg(feature = "serde")] extern crate serde;
+#[cfg(feature = "schemars")] extern crate schemars;
+#[cfg(feature = "json")] extern crate serde_json;
+#[cfg(any(feature = "cbor", feature = "msgpack"))] extern crate serde_bytes;
+#[cfg(feature = "cbor")] extern crate ciborium;
+#[cfg(feature = "msgpack")] extern crate rmp_serde;
+#[cfg(feature = "tokio")] extern crate bytes;
+#[cfg(feature = "tokio")] extern crate tokio_util;
+
+/// An `options::IpAddrList` of the given addresses, whichever type the
+/// `smallvec` feature makes it
+#[cfg(test)]
+macro_rules! ip_list(
+    () => (::options::IpAddrList::new());
+    ($ip:expr; $n:expr) => (::std::iter::repeat($ip).take($n).collect::<::options::IpAddrList>());
+    ($($ip:expr),+ $(,)*) => ([$($ip),+].iter().cloned().collect::<::options::IpAddrList>());
+);
 
+pub mod churn;
+pub mod client;
+#[cfg(feature = "tokio")]
+pub mod codec;
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+pub mod compact;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fingerprint;
 pub mod htype;
+pub mod hwaddr;
+pub mod input;
+#[cfg(feature = "dhcproto")]
+pub mod interop;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod lease;
 pub mod op;
 pub mod options;
+pub mod oui;
+#[cfg(feature = "pcap")]
+pub mod pcap;
+#[cfg(feature = "pnet")]
+pub mod pnet;
+pub mod preserved;
+pub mod quirks;
+pub mod redact;
+pub mod roundtrip;
+pub mod sanitize;
+pub mod server;
+pub mod stats;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+#[cfg(feature = "serde")]
+pub mod testgen;
+#[cfg(feature = "testutil")]
+pub mod testutil;
 mod util;
+pub mod validate;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 use std::fmt;
 use std::error;
+use std::panic;
 use std::convert::{From};
 use std::net::{ Ipv4Addr };
-use nom::{IResult, be_u8, be_u16, be_u32};
+use nom::{be_u8, be_u16, be_u32};
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use self::op::Op;
 use self::htype::Htype;
+use self::hwaddr::{MacAddress, HardwareAddr};
 use self::util::{take_rest};
-use self::options::{DhcpOption};
+use self::options::{DhcpOption, OptionOverloadType, ParseConfig};
+use self::quirks::QuirkRegistry;
 
 const MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+/// Offset of the options area: the fixed BOOTP header plus the magic cookie
+const OPTIONS_OFFSET: usize = 240;
 
 #[derive(Debug, Clone)]
 pub enum Error {
     ParseError(String),
+    /// A fixed-capacity output buffer was too small for the input, or the
+    /// input went over a size limit
+    CapacityExceeded,
+    /// A value can't be represented on the wire, e.g. an IPv6 address in
+    /// a DHCPv4 address option
+    EncodeError(String),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            &Error::ParseError(ref s) => {
+        match *self {
+            Error::ParseError(ref s) => {
                 write!(f, "{:?}", s)
             }
+            Error::CapacityExceeded => {
+                write!(f, "output buffer capacity exceeded")
+            }
+            Error::EncodeError(ref s) => {
+                write!(f, "{}", s)
+            }
         }
     }
 }
 
 impl error::Error for Error {
     fn description(&self) -> &str {
-        match self {
-            &Error::ParseError(ref s) => {
+        match *self {
+            Error::ParseError(ref s) => {
+                s
+            }
+            Error::CapacityExceeded => {
+                "output buffer capacity exceeded"
+            }
+            Error::EncodeError(ref s) => {
                 s
             }
         }
@@ -58,6 +155,8 @@ impl error::Error for Error {
 pub type Result<T> = ::std::result::Result<T, Error>;
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 /// Data type that the bytes get translated into.
 ///
 /// In some cases I translated them into more specific data types
@@ -84,42 +183,312 @@ pub struct RawMessage<'a> {
     pub options: Vec<DhcpOption>,
 }
 
+/// An owned copy of a `RawMessage`, for when the message has to outlive
+/// the buffer it was parsed from. It can also be serialized back to bytes.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct DhcpMessage {
+    pub op: Op,
+    pub htype: Htype,
+    pub hlen: u8,
+    pub hops: u8,
+    pub xid: u32,
+    pub secs: u16,
+    pub flags: u16,
+    pub ciaddr: Ipv4Addr,
+    pub yiaddr: Ipv4Addr,
+    pub siaddr: Ipv4Addr,
+    pub giaddr: Ipv4Addr,
+    pub chaddr: Vec<u8>,
+    pub sname: Vec<u8>,
+    pub file: Vec<u8>,
+    pub options: Vec<DhcpOption>,
+}
+
+impl DhcpMessage {
+    pub fn parse<T: AsRef<[u8]> + ?Sized>(bytes: &T) -> Result<DhcpMessage> {
+        parse_message(bytes).map(DhcpMessage::from)
+    }
+
+    /// See `RawMessage::server_name`
+    pub fn server_name(&self) -> Option<String> {
+        header_string(&self.sname, OptionOverloadType::Sname, &self.options)
+    }
+
+    /// See `RawMessage::boot_file`
+    pub fn boot_file(&self) -> Option<String> {
+        header_string(&self.file, OptionOverloadType::File, &self.options)
+    }
+
+    /// htype and the first `hlen` bytes of chaddr, or as many as htype's
+    /// addresses have where that's fixed, e.g. none for InfiniBand
+    pub fn hardware_addr(&self) -> HardwareAddr {
+        hardware_addr(self.htype, self.hlen, &self.chaddr)
+    }
+
+    /// Serialize the message. `chaddr`, `sname` and `file` are
+    /// zero-padded or truncated to their fixed sizes; the options are
+    /// written as-is, so they should end with `DhcpOption::End`. IPv6
+    /// addresses in options are written as 0.0.0.0; use `try_encode` to
+    /// have them rejected instead.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = self.encode_header();
+        for option in &self.options {
+            option.encode(&mut buf);
+        }
+        buf
+    }
+
+    /// Like `encode`, but fail if an option holds an IPv6 address
+    pub fn try_encode(&self) -> Result<Vec<u8>> {
+        let mut buf = self.encode_header();
+        for option in &self.options {
+            option.try_encode(&mut buf)?;
+        }
+        Ok(buf)
+    }
+
+    /// Everything up to and including the magic cookie
+    fn encode_header(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(OPTIONS_OFFSET + 64);
+        buf.push(self.op.to_byte());
+        buf.push(self.htype.to_byte());
+        buf.push(self.hlen);
+        buf.push(self.hops);
+        buf.extend_from_slice(&self.xid.to_be_bytes());
+        buf.extend_from_slice(&self.secs.to_be_bytes());
+        buf.extend_from_slice(&self.flags.to_be_bytes());
+        buf.extend_from_slice(&self.ciaddr.octets());
+        buf.extend_from_slice(&self.yiaddr.octets());
+        buf.extend_from_slice(&self.siaddr.octets());
+        buf.extend_from_slice(&self.giaddr.octets());
+        put_fixed(&mut buf, &self.chaddr, 16);
+        put_fixed(&mut buf, &self.sname, 64);
+        put_fixed(&mut buf, &self.file, 128);
+        buf.extend_from_slice(&MAGIC_COOKIE);
+        buf
+    }
+}
+
+impl<'a> From<RawMessage<'a>> for DhcpMessage {
+    fn from(msg: RawMessage<'a>) -> DhcpMessage {
+        DhcpMessage {
+            op: msg.op,
+            htype: msg.htype,
+            hlen: msg.hlen,
+            hops: msg.hops,
+            xid: msg.xid,
+            secs: msg.secs,
+            flags: msg.flags,
+            ciaddr: msg.ciaddr,
+            yiaddr: msg.yiaddr,
+            siaddr: msg.siaddr,
+            giaddr: msg.giaddr,
+            chaddr: msg.chaddr.to_vec(),
+            sname: msg.sname.to_vec(),
+            file: msg.file.to_vec(),
+            options: msg.options,
+        }
+    }
+}
+
+fn hardware_addr(htype: Htype, hlen: u8, chaddr: &[u8]) -> HardwareAddr {
+    let len = ::std::cmp::min(htype.address_len().unwrap_or(hlen as usize), chaddr.len());
+    HardwareAddr::new(htype, chaddr[..len].to_vec())
+}
+
+/// `field` up to its first NUL, or if option 52 says it holds options,
+/// the value of the option standing in for it (66 for sname, 67 for file)
+fn header_string(field: &[u8], which: OptionOverloadType, options: &[DhcpOption]) -> Option<String> {
+    let overloaded = options.iter().any(|o| match o {
+        &DhcpOption::OptionOverload(t) => t == which || t == OptionOverloadType::FileAndSname,
+        _ => false,
+    });
+    if overloaded {
+        return options.iter().filter_map(|o| match (o, which) {
+            (DhcpOption::TftpServerName(name), OptionOverloadType::Sname) => Some(name.clone()),
+            (DhcpOption::BootfileName(name), OptionOverloadType::File) => Some(name.clone()),
+            _ => None,
+        }).next();
+    }
+    let len = field.iter().position(|&b| b == 0u8).unwrap_or(field.len());
+    if len == 0 {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&field[..len]).into_owned())
+}
+
+fn put_fixed(buf: &mut Vec<u8>, field: &[u8], size: usize) {
+    let len = ::std::cmp::min(field.len(), size);
+    buf.extend_from_slice(&field[..len]);
+    buf.extend((len..size).map(|_| 0u8));
+}
+
+impl<'a> RawMessage<'a> {
+    /// htype and the first `hlen` bytes of chaddr, or as many as htype's
+    /// addresses have where that's fixed, e.g. none for InfiniBand
+    pub fn hardware_addr(&self) -> HardwareAddr {
+        hardware_addr(self.htype, self.hlen, self.chaddr)
+    }
+
+    /// The server host name from sname, up to the first NUL. `None` if it
+    /// is empty, or if sname holds options and there's no option 66.
+    pub fn server_name(&self) -> Option<String> {
+        header_string(self.sname, OptionOverloadType::Sname, &self.options)
+    }
+
+    /// The boot file name from file, up to the first NUL. `None` if it is
+    /// empty, or if file holds options and there's no option 67.
+    pub fn boot_file(&self) -> Option<String> {
+        header_string(self.file, OptionOverloadType::File, &self.options)
+    }
+
+    /// The client's MAC address, if chaddr holds one
+    pub fn client_mac(&self) -> Option<MacAddress> {
+        self.hardware_addr().mac()
+    }
+}
+
 #[allow(dead_code)]
-pub fn parse_message<'a>(bytes: &'a [u8]) -> Result<RawMessage<'a>> {
-    match _parse_message(bytes) {
-        IResult::Done(inp, msg) => {
-            if inp.len() > 0 {
+pub fn parse_message<'a, T: AsRef<[u8]> + ?Sized>(bytes: &'a T) -> Result<RawMessage<'a>> {
+    #[cfg(feature = "tracing")]
+    let _span = ::tracing::debug_span!("parse_message", len = bytes.as_ref().len()).entered();
+    match _parse_message(bytes.as_ref()) {
+        Ok((inp, msg)) => {
+            if !inp.is_empty() {
                 return Err(Error::ParseError("LEFTOVER INPUT".into()));
             }
             Ok(msg)
         },
-        IResult::Error(err) => {
-            Err(Error::ParseError(format!("SOME OTHER ERROR: {:?}", err)))
-        },
-        IResult::Incomplete(_) => {
+        Err(nom::Err::Incomplete(_)) => {
             Err(Error::ParseError("INCOMPLETE".into()))
+        },
+        Err(err) => {
+            Err(Error::ParseError(format!("SOME OTHER ERROR: {:?}", err)))
         }
     }
 }
 
-named!(_parse_message(&[u8]) -> RawMessage,
+/// Parse a message, re-parsing its options with the quirks of the first
+/// profile in `registry` matching the sender's vendor class or chaddr OUI
+pub fn parse_message_with_quirks<'a>(bytes: &'a [u8], registry: &QuirkRegistry) -> Result<RawMessage<'a>> {
+    let mut msg = parse_message(bytes)?;
+    let quirks = {
+        let vendor_class = msg.options.iter().filter_map(|o| match o {
+            DhcpOption::ClassIdentifier(class) => Some(&class[..]),
+            _ => None,
+        }).next();
+        registry.select(vendor_class, msg.chaddr).map(|p| p.quirks.clone())
+    };
+    if let Some(quirks) = quirks {
+        msg.options = options::parse_with_quirks(&bytes[OPTIONS_OFFSET..], &quirks)?;
+    }
+    Ok(msg)
+}
+
+/// Parse a message, with its options parsed with `config`
+pub fn parse_message_with_config<'a>(bytes: &'a [u8], config: &ParseConfig) -> Result<RawMessage<'a>> {
+    let mut msg = match _parse_header(bytes) {
+        Ok((_, msg)) => msg,
+        Err(nom::Err::Incomplete(_)) => return Err(Error::ParseError("INCOMPLETE".into())),
+        Err(err) => return Err(Error::ParseError(format!("SOME OTHER ERROR: {:?}", err))),
+    };
+    msg.options = options::parse_with_config(&bytes[OPTIONS_OFFSET..], config)?;
+    Ok(msg)
+}
+
+/// Parse the payload of a UDP datagram to or from port 67 or 68.
+///
+/// If option 52 says `file` or `sname` hold options too, they are parsed
+/// and added after the options field's, in the order RFC 2131 section
+/// 4.1 gives: `file`, then `sname`. The message then carries them as if
+/// it had never been overloaded: option 52 and the inner End options are
+/// dropped, and the two fields are zeroed.
+pub fn parse_udp_payload(bytes: &[u8]) -> Result<DhcpMessage> {
+    let mut msg = DhcpMessage::parse(bytes)?;
+    let overload = msg.options.iter().filter_map(|o| match o {
+        &DhcpOption::OptionOverload(t) => Some(t),
+        _ => None,
+    }).next();
+    let (file, sname) = match overload {
+        Some(OptionOverloadType::File) => (true, false),
+        Some(OptionOverloadType::Sname) => (false, true),
+        Some(OptionOverloadType::FileAndSname) => (true, true),
+        None => return Ok(msg),
+    };
+    let mut overflow = Vec::new();
+    if file {
+        overflow.extend(options::parse(&msg.file)?);
+        msg.file = vec![0u8; 128];
+    }
+    if sname {
+        overflow.extend(options::parse(&msg.sname)?);
+        msg.sname = vec![0u8; 64];
+    }
+    msg.options.retain(|o| !matches!(o, DhcpOption::OptionOverload(_) | DhcpOption::End));
+    msg.options.extend(overflow.into_iter().filter(|o| o != &DhcpOption::End));
+    msg.options.push(DhcpOption::End);
+    Ok(msg)
+}
+
+/// Parse a message from anyone, in a process that must not go down with
+/// it, e.g. a relay or server daemon.
+///
+/// Parses as `parse_message_with_config`; set `config.limits` to bound
+/// the work one message can cause. The parser is written not to panic on
+/// any input: the crate denies `unwrap`, `expect` and `panic!` outside
+/// tests, the option walker and every decoder deny unchecked indexing,
+/// and the `parse_untrusted` fuzz target runs this on arbitrary bytes.
+///
+/// Catching a panic here is only a fallback for one those checks miss,
+/// e.g. in a dependency. It is returned as an `Error::ParseError`, so a
+/// bug costs one message, not the process. This needs unwinding, so it
+/// doesn't help under `panic = "abort"`.
+pub fn parse_untrusted(bytes: &[u8], config: &ParseConfig) -> Result<DhcpMessage> {
+    let parsed = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        parse_message_with_config(bytes, config).map(DhcpMessage::from)
+    }));
+    match parsed {
+        Ok(result) => result,
+        Err(_) => Err(Error::ParseError(format!("Parser panicked on a message of {} bytes", bytes.len()))),
+    }
+}
+
+/// Parse each of `packets` with `DhcpMessage::parse`, spread over the
+/// rayon thread pool. Results are in the same order as `packets`.
+#[cfg(feature = "rayon")]
+pub fn parse_batch(packets: &[&[u8]]) -> Vec<Result<DhcpMessage>> {
+    packets.par_iter().map(DhcpMessage::parse).collect()
+}
+
+named!(_parse_message(&[u8]) -> RawMessage<'_>,
+    do_parse!(
+        msg: _parse_header >>
+        poptions: map_res!(take_rest, options::parse) >>
+        ({ RawMessage { options: poptions, ..msg } })
+    )
+);
+
+// Everything up to and including the magic cookie, with no options
+named!(_parse_header<&[u8], RawMessage<'_>>,
     do_parse!(
         pop: map_res!(be_u8, Op::from_byte) >>
-        phtype: map_res!(be_u8, Htype::from_byte) >>
+        phtype: map!(be_u8, Htype::from_byte) >>
         phlen: be_u8 >>
         phops: be_u8 >>
         pxid: be_u32 >>
         psecs: be_u16 >>
         pflags: be_u16 >>
-        pciaddr: map!(be_u32, |a| Ipv4Addr::from(a)) >>
-        pyiaddr: map!(be_u32, |a| Ipv4Addr::from(a)) >>
-        psiaddr: map!(be_u32, |a| Ipv4Addr::from(a)) >>
-        pgiaddr: map!(be_u32, |a| Ipv4Addr::from(a)) >>
+        pciaddr: map!(be_u32, Ipv4Addr::from) >>
+        pyiaddr: map!(be_u32, Ipv4Addr::from) >>
+        psiaddr: map!(be_u32, Ipv4Addr::from) >>
+        pgiaddr: map!(be_u32, Ipv4Addr::from) >>
         pchaddr: take!(16) >>
         psname: take!(64) >>
         pfile: take!(128) >>
         _cookie: tag!(&MAGIC_COOKIE) >>
-        poptions: map_res!(take_rest, options::parse) >>
     ({
         RawMessage {
             op: pop,
@@ -136,7 +505,7 @@ named!(_parse_message(&[u8]) -> RawMessage,
             chaddr: pchaddr,
             sname: psname,
             file: pfile,
-            options: poptions,
+            options: Vec::new(),
         }
     })
     )
@@ -146,7 +515,8 @@ named!(_parse_message(&[u8]) -> RawMessage,
 mod tests {
 
     use std::str;
-    use super::{parse_message, RawMessage};
+    use super::{parse_message, parse_udp_payload, RawMessage, DhcpMessage};
+    use super::options::{DhcpOption, DhcpMessageTypes, OptionOverloadType};
     use super::op::{Op};
     use super::htype::{Htype};
 
@@ -249,4 +619,220 @@ mod tests {
         });
 
     }
+
+    #[test]
+    fn test_dhcp_message_round_trip() {
+        let msg = DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0x8000,
+            ciaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            yiaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            siaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            giaddr: str::FromStr::from_str("10.0.0.1").unwrap(),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options: vec![
+                DhcpOption::MessageType(DhcpMessageTypes::Discover),
+                DhcpOption::ParamRequestList(vec![1, 3, 6, 42]),
+                DhcpOption::End,
+            ],
+        };
+        assert_eq!(msg.hardware_addr().to_string(), "00:0b:82:01:fc:42");
+        let bytes = msg.encode();
+        assert_eq!(bytes.len(), 240 + 3 + 6 + 1);
+        assert_eq!(DhcpMessage::parse(&bytes).unwrap(), msg);
+
+        // RFC 4390: InfiniBand leaves chaddr empty
+        let mut infiniband = bytes.clone();
+        infiniband[1] = 32u8;
+        infiniband[2] = 0u8;
+        let parsed = DhcpMessage::parse(&infiniband).unwrap();
+        assert_eq!(parsed.htype, Htype::InfiniBand);
+        assert!(parsed.hardware_addr().addr.is_empty());
+        assert_eq!(parsed.hardware_addr().mac(), None);
+
+        let mut ieee802 = bytes.clone();
+        ieee802[1] = 6u8;
+        assert_eq!(DhcpMessage::parse(&ieee802).unwrap().hardware_addr().mac(), msg.hardware_addr().mac());
+
+        // A hardware type that isn't assigned yet still parses, and encodes back
+        let mut unknown = bytes.clone();
+        unknown[1] = 200u8;
+        let parsed = DhcpMessage::parse(&unknown).unwrap();
+        assert_eq!(parsed.htype, Htype::Other(200u8));
+        assert_eq!(parsed.encode(), unknown);
+    }
+
+    #[test]
+    fn test_parse_udp_payload_overload() {
+        let mut file = vec![12u8, 4u8, b'h', b'o', b's', b't', 255u8];
+        file.resize(128, 0u8);
+        let mut sname = vec![15u8, 3u8, b'l', b'a', b'n', 255u8];
+        sname.resize(64, 0u8);
+        let mut msg = DhcpMessage {
+            op: Op::BootReply,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            yiaddr: str::FromStr::from_str("10.0.0.5").unwrap(),
+            siaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            giaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname,
+            file,
+            options: vec![
+                DhcpOption::MessageType(DhcpMessageTypes::Offer),
+                DhcpOption::OptionOverload(OptionOverloadType::FileAndSname),
+                DhcpOption::End,
+            ],
+        };
+        let parsed = parse_udp_payload(&msg.encode()).unwrap();
+        assert_eq!(parsed.options, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Offer),
+            DhcpOption::HostName("host".to_string()),
+            DhcpOption::DomainName("lan".to_string()),
+            DhcpOption::End,
+        ]);
+        assert_eq!(parsed.file, vec![0u8; 128]);
+        assert_eq!(parsed.sname, vec![0u8; 64]);
+
+        msg.options.remove(1);
+        assert_eq!(parse_udp_payload(&msg.encode()).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_server_name_and_boot_file() {
+        let mut sname = b"tftp1\0junk".to_vec();
+        sname.resize(64, 0u8);
+        let mut file = b"pxelinux.0".to_vec();
+        file.resize(128, 0u8);
+        let mut msg = DhcpMessage {
+            op: Op::BootReply,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            yiaddr: str::FromStr::from_str("10.0.0.5").unwrap(),
+            siaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            giaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname,
+            file,
+            options: vec![DhcpOption::MessageType(DhcpMessageTypes::Offer), DhcpOption::End],
+        };
+        assert_eq!(msg.server_name(), Some("tftp1".to_string()));
+        assert_eq!(msg.boot_file(), Some("pxelinux.0".to_string()));
+        assert_eq!(parse_message(&msg.encode()).unwrap().server_name(), Some("tftp1".to_string()));
+
+        msg.file = vec![12u8, 4u8, b'h', b'o', b's', b't', 255u8];
+        msg.file.resize(128, 0u8);
+        msg.options.insert(1, DhcpOption::OptionOverload(OptionOverloadType::File));
+        assert_eq!(msg.boot_file(), None);
+        msg.options.insert(1, DhcpOption::BootfileName("undionly.kpxe".to_string()));
+        assert_eq!(msg.boot_file(), Some("undionly.kpxe".to_string()));
+        assert_eq!(msg.server_name(), Some("tftp1".to_string()));
+
+        msg.sname = vec![0u8; 64];
+        assert_eq!(msg.server_name(), None);
+    }
+
+    #[test]
+    fn test_parse_untrusted() {
+        use super::{parse_untrusted, Error};
+        use super::options::{ParseConfig, Limits};
+
+        let msg = DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            yiaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            siaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            giaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options: vec![
+                DhcpOption::MessageType(DhcpMessageTypes::Discover),
+                DhcpOption::HostName("host".to_string()),
+                DhcpOption::End,
+            ],
+        };
+        let bytes = msg.encode();
+        let config = ParseConfig::default();
+        assert_eq!(parse_untrusted(&bytes, &config).unwrap(), msg);
+
+        for len in 0..bytes.len() {
+            let _ = parse_untrusted(&bytes[..len], &config);
+        }
+        for i in super::OPTIONS_OFFSET..bytes.len() {
+            for b in [0u8, 1u8, 0x7fu8, 0xffu8].iter() {
+                let mut corrupted = bytes.clone();
+                corrupted[i] = *b;
+                let _ = parse_untrusted(&corrupted, &config);
+            }
+        }
+
+        let limits = Limits { max_options: 1, ..Limits::default() };
+        let limited = parse_untrusted(&bytes, &ParseConfig { limits, ..ParseConfig::default() });
+        assert!(matches!(limited, Err(Error::CapacityExceeded)));
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_message_schema() {
+        let schema = schemars::schema_for!(DhcpMessage);
+        let properties = schema.get("properties").unwrap();
+        assert!(properties.get("xid").is_some());
+        assert!(properties.get("options").is_some());
+        assert!(schema.get("$defs").and_then(|defs| defs.get("DhcpOption")).is_some());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parse_batch() {
+        use super::parse_batch;
+
+        let msg = |xid| DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid,
+            secs: 0,
+            flags: 0,
+            ciaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            yiaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            siaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            giaddr: str::FromStr::from_str("0.0.0.0").unwrap(),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0u8; 64],
+            file: vec![0u8; 128],
+            options: vec![DhcpOption::MessageType(DhcpMessageTypes::Discover), DhcpOption::End],
+        };
+        let (first, second) = (msg(1).encode(), msg(2).encode());
+        let packets: Vec<&[u8]> = vec![&first, &[1u8, 1u8, 6u8], &second];
+        let parsed = parse_batch(&packets);
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0].as_ref().unwrap(), &msg(1));
+        assert!(parsed[1].is_err());
+        assert_eq!(parsed[2].as_ref().unwrap(), &msg(2));
+    }
 }
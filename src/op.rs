@@ -1,6 +1,15 @@
 use super::{Error, Result};
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub enum Op {
     BootRequest = 1,
     BootReply,
@@ -14,5 +23,12 @@ impl Op {
             _ => { Err(Error::ParseError("Got bad value for `op`".into())) }
         }
     }
+
+    pub fn to_byte(&self) -> u8 {
+        match *self {
+            Op::BootRequest => 1u8,
+            Op::BootReply => 2u8,
+        }
+    }
 }
 
@@ -0,0 +1,234 @@
+//! Making sense of the Agent Circuit ID and Agent Remote ID suboptions
+//! of option 82
+//!
+//! RFC 3046 leaves their contents to the relay. These recognize the
+//! formats switches commonly send; anything else is kept as raw bytes.
+
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+use std::fmt;
+use std::str;
+use hwaddr::MacAddress;
+
+/// A decoded Agent Circuit ID
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum CircuitId {
+    /// `[0, 4, vlan (2 bytes), module, port]`, the default on Cisco and
+    /// many other switches
+    VlanModulePort { vlan: u16, module: u8, port: u8 },
+    /// `[1, length, text]`, a string configured on the switch
+    Configured(String),
+    /// Printable ASCII without a type prefix, e.g. "Gi1/0/24:100"
+    Text(String),
+    Raw(Vec<u8>),
+}
+
+fn printable(bytes: &[u8]) -> Option<&str> {
+    if bytes.is_empty() || !bytes.iter().all(|&b| (0x20..0x7f).contains(&b)) {
+        return None;
+    }
+    str::from_utf8(bytes).ok()
+}
+
+/// The number starting at `bytes[start]` and where it ends
+fn number_at(bytes: &[u8], start: usize) -> (u32, usize) {
+    let digits = bytes.iter().skip(start).take_while(|b| b.is_ascii_digit());
+    let value = digits.clone().fold(0u32, |n, &d| n.saturating_mul(10).saturating_add((d - b'0') as u32));
+    (value, start + digits.count())
+}
+
+/// The numbers of the last `a/b[/c...]` run in `text`, and the number
+/// after a `:` or `.` directly following it
+fn interface_numbers(text: &str) -> (Vec<u32>, Option<u32>) {
+    let bytes = text.as_bytes();
+    let digit_at = |i: usize| bytes.get(i).is_some_and(|b| b.is_ascii_digit());
+    let mut last = (Vec::new(), None);
+    let mut i = 0;
+    while i < bytes.len() {
+        if !digit_at(i) {
+            i += 1;
+            continue;
+        }
+        let mut numbers = Vec::new();
+        loop {
+            let (n, end) = number_at(bytes, i);
+            numbers.push(n);
+            i = end;
+            if bytes.get(i) == Some(&b'/') && digit_at(i + 1) {
+                i += 1;
+            } else {
+                break;
+            }
+        }
+        if numbers.len() >= 2 {
+            let vlan = match bytes.get(i) {
+                Some(&b':') | Some(&b'.') if digit_at(i + 1) => Some(number_at(bytes, i + 1).0),
+                _ => None,
+            };
+            last = (numbers, vlan);
+        }
+    }
+    last
+}
+
+impl CircuitId {
+    pub fn from_bytes(bytes: &[u8]) -> CircuitId {
+        match bytes {
+            [0u8, 4u8, vlan_hi, vlan_lo, module, port] => CircuitId::VlanModulePort {
+                vlan: u16::from_be_bytes([*vlan_hi, *vlan_lo]),
+                module: *module,
+                port: *port,
+            },
+            [1u8, len, text @ ..] if *len as usize == text.len() => match printable(text) {
+                Some(text) => CircuitId::Configured(text.to_string()),
+                None => CircuitId::Raw(bytes.to_vec()),
+            },
+            _ => match printable(bytes) {
+                Some(text) => CircuitId::Text(text.to_string()),
+                None => CircuitId::Raw(bytes.to_vec()),
+            },
+        }
+    }
+
+    /// The VLAN, for the binary format and text ending in `:vlan` or
+    /// `.vlan` after the port
+    pub fn vlan(&self) -> Option<u16> {
+        match self {
+            &CircuitId::VlanModulePort { vlan, .. } => Some(vlan),
+            &CircuitId::Configured(ref text) | &CircuitId::Text(ref text) => {
+                interface_numbers(text).1.and_then(|v| if v <= 4095 { Some(v as u16) } else { None })
+            },
+            &CircuitId::Raw(_) => None,
+        }
+    }
+
+    /// The module, or for text like "1/0/24" the number before the port
+    pub fn slot(&self) -> Option<u32> {
+        match self {
+            &CircuitId::VlanModulePort { module, .. } => Some(module as u32),
+            &CircuitId::Configured(ref text) | &CircuitId::Text(ref text) => {
+                let (numbers, _) = interface_numbers(text);
+                numbers.iter().rev().nth(1).cloned()
+            },
+            &CircuitId::Raw(_) => None,
+        }
+    }
+
+    /// The port, or for text like "1/0/24" the last number
+    pub fn port(&self) -> Option<u32> {
+        match self {
+            &CircuitId::VlanModulePort { port, .. } => Some(port as u32),
+            &CircuitId::Configured(ref text) | &CircuitId::Text(ref text) => interface_numbers(text).0.last().cloned(),
+            &CircuitId::Raw(_) => None,
+        }
+    }
+}
+
+/// A decoded Agent Remote ID
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum RemoteId {
+    /// Six bytes, or `[0, 6, MAC]`: usually the switch's or modem's MAC
+    Mac(MacAddress),
+    /// `[1, length, text]`, a string configured on the switch
+    Configured(String),
+    /// Printable ASCII without a type prefix, e.g. a subscriber name
+    Text(String),
+    /// `[type, length, data]` with a type other than the above
+    Typed { remote_type: u8, data: Vec<u8> },
+    Raw(Vec<u8>),
+}
+
+impl RemoteId {
+    pub fn from_bytes(bytes: &[u8]) -> RemoteId {
+        match bytes {
+            &[0u8, 6u8, a, b, c, d, e, f] => RemoteId::Mac(MacAddress([a, b, c, d, e, f])),
+            [1u8, len, text @ ..] if *len as usize == text.len() && printable(text).is_some() => {
+                RemoteId::Configured(String::from_utf8_lossy(text).into_owned())
+            },
+            _ if printable(bytes).is_some() => RemoteId::Text(String::from_utf8_lossy(bytes).into_owned()),
+            &[a, b, c, d, e, f] => RemoteId::Mac(MacAddress([a, b, c, d, e, f])),
+            [remote_type, len, data @ ..] if *len as usize == data.len() => {
+                RemoteId::Typed { remote_type: *remote_type, data: data.to_vec() }
+            },
+            _ => RemoteId::Raw(bytes.to_vec()),
+        }
+    }
+
+    pub fn mac(&self) -> Option<MacAddress> {
+        match self {
+            &RemoteId::Mac(mac) => Some(mac),
+            _ => None,
+        }
+    }
+}
+
+fn write_hex(f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+    for (i, b) in bytes.iter().enumerate() {
+        if i > 0 {
+            write!(f, ":")?;
+        }
+        write!(f, "{:02x}", b)?;
+    }
+    Ok(())
+}
+
+/// MACs as in `MacAddress`, text as it is, and anything else as
+/// colon-separated hex, after the type number for `Typed`, e.g. `2/01:02`
+impl fmt::Display for RemoteId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RemoteId::Mac(ref mac) => write!(f, "{}", mac),
+            RemoteId::Configured(ref text) | RemoteId::Text(ref text) => f.write_str(text),
+            RemoteId::Typed { remote_type, ref data } => {
+                write!(f, "{}/", remote_type)?;
+                write_hex(f, data)
+            },
+            RemoteId::Raw(ref data) => write_hex(f, data),
+        }
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{CircuitId, RemoteId};
+    use hwaddr::MacAddress;
+
+    #[test]
+    fn test_circuit_id() {
+        let binary = CircuitId::from_bytes(&[0u8, 4u8, 0u8, 100u8, 1u8, 24u8]);
+        assert_eq!(binary, CircuitId::VlanModulePort { vlan: 100, module: 1, port: 24 });
+        assert_eq!((binary.vlan(), binary.slot(), binary.port()), (Some(100), Some(1), Some(24)));
+
+        let text = CircuitId::from_bytes(b"Gi1/0/24:100");
+        assert_eq!(text, CircuitId::Text("Gi1/0/24:100".to_string()));
+        assert_eq!((text.vlan(), text.slot(), text.port()), (Some(100), Some(0), Some(24)));
+
+        let configured = CircuitId::from_bytes(b"\x01\x0bport 3/7.20");
+        assert_eq!(configured, CircuitId::Configured("port 3/7.20".to_string()));
+        assert_eq!((configured.vlan(), configured.slot(), configured.port()), (Some(20), Some(3), Some(7)));
+
+        let name = CircuitId::from_bytes(b"lobby-ap");
+        assert_eq!((name.vlan(), name.slot(), name.port()), (None, None, None));
+
+        let raw = CircuitId::from_bytes(&[0u8, 4u8, 0u8, 100u8, 1u8]);
+        assert_eq!(raw, CircuitId::Raw(vec![0u8, 4u8, 0u8, 100u8, 1u8]));
+        assert_eq!(raw.port(), None);
+    }
+
+    #[test]
+    fn test_remote_id() {
+        let mac = MacAddress([0u8, 11u8, 130u8, 1u8, 252u8, 66u8]);
+        assert_eq!(RemoteId::from_bytes(&[0u8, 11u8, 130u8, 1u8, 252u8, 66u8]), RemoteId::Mac(mac));
+        let prefixed = RemoteId::from_bytes(&[0u8, 6u8, 0u8, 11u8, 130u8, 1u8, 252u8, 66u8]);
+        assert_eq!(prefixed.mac(), Some(mac));
+        assert_eq!(prefixed.to_string(), "00:0b:82:01:fc:42");
+
+        let configured = RemoteId::from_bytes(b"\x01\x06user01");
+        assert_eq!(configured, RemoteId::Configured("user01".to_string()));
+        assert_eq!(RemoteId::from_bytes(b"user01").to_string(), "user01");
+
+        let typed = RemoteId::from_bytes(&[2u8, 2u8, 1u8, 2u8]);
+        assert_eq!(typed, RemoteId::Typed { remote_type: 2u8, data: vec![1u8, 2u8] });
+        assert_eq!(typed.to_string(), "2/01:02");
+        assert_eq!(RemoteId::from_bytes(&[0xffu8, 0u8, 1u8]).to_string(), "ff:00:01");
+    }
+}
@@ -0,0 +1,263 @@
+//! Option 99, civic address location (RFC 4776)
+
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+use { Result, Error };
+use std::str;
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+
+/// Which location the address describes
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum CivicWhat {
+    DhcpServer,
+    /// The network element closest to the client, e.g. its switch
+    NetworkElement,
+    Client,
+    Other(u8),
+}
+
+impl From<u8> for CivicWhat {
+    fn from(what: u8) -> CivicWhat {
+        match what {
+            0u8 => CivicWhat::DhcpServer,
+            1u8 => CivicWhat::NetworkElement,
+            2u8 => CivicWhat::Client,
+            n => CivicWhat::Other(n),
+        }
+    }
+}
+
+impl From<CivicWhat> for u8 {
+    fn from(what: CivicWhat) -> u8 {
+        match what {
+            CivicWhat::DhcpServer => 0u8,
+            CivicWhat::NetworkElement => 1u8,
+            CivicWhat::Client => 2u8,
+            CivicWhat::Other(n) => n,
+        }
+    }
+}
+
+/// The CAtype of an address element, from the RFC 4776 registry
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum CaType {
+    /// An RFC 3066 language tag for the elements that follow
+    Language,
+    /// National subdivisions (state, region, province, prefecture)
+    A1,
+    /// County, parish, gun (JP), district (IN)
+    A2,
+    /// City, township, shi (JP)
+    A3,
+    /// City division, borough, city district, ward, chou (JP)
+    A4,
+    /// Neighborhood, block
+    A5,
+    /// Street
+    A6,
+    LeadingStreetDirection,
+    TrailingStreetSuffix,
+    StreetSuffix,
+    HouseNumber,
+    HouseNumberSuffix,
+    Landmark,
+    AdditionalLocation,
+    Name,
+    PostalCode,
+    Building,
+    Unit,
+    Floor,
+    Room,
+    PlaceType,
+    PostalCommunityName,
+    PoBox,
+    AdditionalCode,
+    /// An ISO 15924 script code, e.g. "Latn"
+    Script,
+    Other(u8),
+}
+
+impl From<u8> for CaType {
+    fn from(ca_type: u8) -> CaType {
+        use self::CaType::*;
+        match ca_type {
+            0u8 => Language,
+            1u8 => A1,
+            2u8 => A2,
+            3u8 => A3,
+            4u8 => A4,
+            5u8 => A5,
+            6u8 => A6,
+            16u8 => LeadingStreetDirection,
+            17u8 => TrailingStreetSuffix,
+            18u8 => StreetSuffix,
+            19u8 => HouseNumber,
+            20u8 => HouseNumberSuffix,
+            21u8 => Landmark,
+            22u8 => AdditionalLocation,
+            23u8 => Name,
+            24u8 => PostalCode,
+            25u8 => Building,
+            26u8 => Unit,
+            27u8 => Floor,
+            28u8 => Room,
+            29u8 => PlaceType,
+            30u8 => PostalCommunityName,
+            31u8 => PoBox,
+            32u8 => AdditionalCode,
+            128u8 => Script,
+            n => Other(n),
+        }
+    }
+}
+
+impl From<CaType> for u8 {
+    fn from(ca_type: CaType) -> u8 {
+        use self::CaType::*;
+        match ca_type {
+            Language => 0u8,
+            A1 => 1u8,
+            A2 => 2u8,
+            A3 => 3u8,
+            A4 => 4u8,
+            A5 => 5u8,
+            A6 => 6u8,
+            LeadingStreetDirection => 16u8,
+            TrailingStreetSuffix => 17u8,
+            StreetSuffix => 18u8,
+            HouseNumber => 19u8,
+            HouseNumberSuffix => 20u8,
+            Landmark => 21u8,
+            AdditionalLocation => 22u8,
+            Name => 23u8,
+            PostalCode => 24u8,
+            Building => 25u8,
+            Unit => 26u8,
+            Floor => 27u8,
+            Room => 28u8,
+            PlaceType => 29u8,
+            PostalCommunityName => 30u8,
+            PoBox => 31u8,
+            AdditionalCode => 32u8,
+            Script => 128u8,
+            Other(n) => n,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct CivicAddressElement {
+    pub ca_type: CaType,
+    /// UTF-8, at most 255 bytes
+    pub value: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct CivicLocation {
+    pub what: CivicWhat,
+    /// Two letter ISO 3166 code, e.g. "US"
+    pub country_code: String,
+    pub elements: Vec<CivicAddressElement>,
+}
+
+impl CivicLocation {
+    pub fn from_bytes(bytes: &[u8]) -> Result<CivicLocation> {
+        let (what, country_code, mut data) = match bytes {
+            &[what, c1, c2, ref data @ ..] => (what, [c1, c2], data),
+            _ => return Err(Error::ParseError("Civic location is shorter than 3 bytes".to_string())),
+        };
+        let country_code = str::from_utf8(&country_code)
+            .map_err(|_| Error::ParseError("Civic location country code is not UTF-8".to_string()))?;
+        let mut elements = Vec::new();
+        while let Some((&ca_type, rest)) = data.split_first() {
+            let (value, rest) = match rest.split_first() {
+                Some((&len, rest)) if rest.len() >= len as usize => rest.split_at(len as usize),
+                _ => return Err(Error::ParseError(format!("Truncated civic address element {}", ca_type))),
+            };
+            let value = str::from_utf8(value)
+                .map_err(|_| Error::ParseError(format!("Civic address element {} is not UTF-8", ca_type)))?;
+            elements.push(CivicAddressElement { ca_type: CaType::from(ca_type), value: value.to_string() });
+            data = rest;
+        }
+        Ok(CivicLocation { what: CivicWhat::from(what), country_code: country_code.to_string(), elements })
+    }
+
+    /// The country code is padded with spaces or cut to two bytes, and
+    /// element values are cut to 255 bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![u8::from(self.what)];
+        let mut country_code = self.country_code.as_bytes().to_vec();
+        country_code.resize(2, b' ');
+        data.extend_from_slice(&country_code);
+        for element in &self.elements {
+            let len = ::std::cmp::min(element.value.len(), 255);
+            data.push(u8::from(element.ca_type));
+            data.push(len as u8);
+            data.extend(element.value.bytes().take(len));
+        }
+        data
+    }
+
+    /// The value of the first element of type `ca_type`
+    pub fn get(&self, ca_type: CaType) -> Option<&str> {
+        self.elements.iter().find(|e| e.ca_type == ca_type).map(|e| e.value.as_str())
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{CivicLocation, CivicAddressElement, CivicWhat, CaType};
+
+    #[test]
+    fn test_civic_location() {
+        let data = [
+            2u8, b'D', b'E',
+            0u8, 2u8, b'd', b'e',
+            3u8, 8u8, b'M', 0xc3u8, 0xbcu8, b'n', b'c', b'h', b'e', b'n',
+            6u8, 10u8, b'L', b'e', b'o', b'p', b'o', b'l', b'd', b's', b't', b'r',
+            19u8, 2u8, b'1', b'9',
+            40u8, 1u8, b'x',
+        ];
+        let location = CivicLocation::from_bytes(&data).unwrap();
+        assert_eq!(location, CivicLocation {
+            what: CivicWhat::Client,
+            country_code: "DE".to_string(),
+            elements: vec![
+                CivicAddressElement { ca_type: CaType::Language, value: "de".to_string() },
+                CivicAddressElement { ca_type: CaType::A3, value: "München".to_string() },
+                CivicAddressElement { ca_type: CaType::A6, value: "Leopoldstr".to_string() },
+                CivicAddressElement { ca_type: CaType::HouseNumber, value: "19".to_string() },
+                CivicAddressElement { ca_type: CaType::Other(40), value: "x".to_string() },
+            ],
+        });
+        assert_eq!(location.get(CaType::A3), Some("München"));
+        assert_eq!(location.get(CaType::PostalCode), None);
+        assert_eq!(location.to_bytes(), data.to_vec());
+    }
+
+    #[test]
+    fn test_invalid_civic_location() {
+        assert!(CivicLocation::from_bytes(&[0u8, b'U']).is_err());
+        assert!(CivicLocation::from_bytes(&[0u8, b'U', b'S', 3u8, 4u8, b'a']).is_err());
+        assert!(CivicLocation::from_bytes(&[0u8, b'U', b'S', 3u8, 1u8, 0xffu8]).is_err());
+        let empty = CivicLocation::from_bytes(&[1u8, b'U', b'S']).unwrap();
+        assert_eq!(empty.what, CivicWhat::NetworkElement);
+        assert!(empty.elements.is_empty());
+    }
+}
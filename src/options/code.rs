@@ -0,0 +1,694 @@
+//! Option codes, from the IANA registry of BOOTP and DHCP options
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+
+/// Every code in the IANA registry, named as the `DhcpOption` variant
+/// where there is one. Converts to and from `u8`; the variants that hold
+/// a `u8` hold the code itself.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum OptionCode {
+    Pad,
+    SubnetMask,
+    TimeOffset,
+    Router,
+    TimeServer,
+    NameServer,
+    DomainNameServer,
+    LogServer,
+    CookieServer,
+    LprServer,
+    ImpressServer,
+    ResourceLocationServer,
+    HostName,
+    BootFileSize,
+    MeritDumpFile,
+    DomainName,
+    SwapServer,
+    RootPath,
+    ExtensionsPath,
+    IPForwarding,
+    NonLocalSourceRouting,
+    PolicyFilter,
+    MaxDatagramReassemblySize,
+    DefaultIpTtl,
+    PathMtuAgingTimeout,
+    PathMtuPlateauTable,
+    InterfaceMtu,
+    AllSubnetsAreLocal,
+    BroadcastAddress,
+    PerformMaskDiscovery,
+    MaskSupplier,
+    PerformRouterDiscovery,
+    RouterSolicitationAddress,
+    StaticRoute,
+    TrailerEncapsulation,
+    ArpCacheTimeout,
+    EthernetEncapsulation,
+    TcpDefaultTtl,
+    TcpKeepaliveInterval,
+    TcpKeepaliveGarbage,
+    NisDomain,
+    NetworkInformationServers,
+    NtpServers,
+    VendorExtensions,
+    NetBiosNameServers,
+    NetBiosDatagramDistributionServer,
+    NetBiosNodeType,
+    NetBiosScope,
+    XFontServer,
+    XDisplayManager,
+    RequestedIpAddress,
+    IpAddressLeaseTime,
+    OptionOverload,
+    MessageType,
+    ServerIdentifier,
+    ParamRequestList,
+    Message,
+    MaxMessageSize,
+    RenewalTimeValue,
+    RebindingTimeValue,
+    ClassIdentifier,
+    ClientIdentifier,
+    NetwareIpDomain,
+    NetwareIpInformation,
+    NisPlusDomain,
+    NisPlusServers,
+    TftpServerName,
+    BootfileName,
+    MobileIpHomeAgent,
+    SmtpServers,
+    Pop3Servers,
+    NntpServers,
+    WwwServers,
+    FingerServers,
+    IrcServers,
+    StreetTalkServers,
+    StdaServers,
+    UserClass,
+    DirectoryAgent,
+    ServiceScope,
+    RapidCommit,
+    ClientFqdn,
+    RelayAgentInformation,
+    Isns,
+    NdsServers,
+    NdsTreeName,
+    NdsContext,
+    BcmcsControllerDomains,
+    BcmcsControllerAddresses,
+    Authentication,
+    ClientLastTransactionTime,
+    AssociatedIp,
+    ClientSystem,
+    ClientNdi,
+    Ldap,
+    ClientUuid,
+    UserAuth,
+    CivicLocation,
+    PCode,
+    TCode,
+    Ipv6OnlyPreferred,
+    Dhcp4o6S46Saddr,
+    NetinfoParentServerAddress,
+    NetinfoParentServerTag,
+    CaptivePortal,
+    AutoConfigure,
+    NameServiceSearch,
+    SubnetSelection,
+    DomainSearch,
+    SipServers,
+    ClasslessStaticRoute,
+    Ccc,
+    GeoConf,
+    VendorIdentifyingVendorClass,
+    VendorIdentifyingVendorInformation,
+    /// 128 to 135, which PXE leaves to vendors
+    Pxe(u8),
+    PanaAgents,
+    LostServer,
+    CapwapAcAddresses,
+    MosAddresses,
+    MosFqdns,
+    SipUaConfigDomains,
+    AndsfAddresses,
+    SztpRedirect,
+    GeoLoc,
+    ForcerenewNonceCapable,
+    RdnssSelection,
+    DotsReferenceIdentifier,
+    DotsAddresses,
+    TftpServerAddresses,
+    StatusCode,
+    BaseTime,
+    StartTimeOfState,
+    QueryStartTime,
+    QueryEndTime,
+    DhcpState,
+    DataSource,
+    PcpServer,
+    PortParams,
+    MudUrl,
+    EncryptedDnsResolver,
+    Etherboot,
+    IpTelephone,
+    PacketCable,
+    PxelinuxMagic,
+    PxelinuxConfigFile,
+    PxelinuxPathPrefix,
+    PxelinuxRebootTime,
+    SixRd,
+    AccessDomain,
+    SubnetAllocation,
+    VirtualSubnetSelection,
+    End,
+    /// 252, which is for private use but everyone uses for WPAD
+    ProxyAutoDiscovery,
+    /// 224 to 254, other than 252
+    PrivateUse(u8),
+    Unassigned(u8),
+}
+
+impl From<u8> for OptionCode {
+    fn from(code: u8) -> OptionCode {
+        use self::OptionCode::*;
+        match code {
+            0u8 => Pad,
+            1u8 => SubnetMask,
+            2u8 => TimeOffset,
+            3u8 => Router,
+            4u8 => TimeServer,
+            5u8 => NameServer,
+            6u8 => DomainNameServer,
+            7u8 => LogServer,
+            8u8 => CookieServer,
+            9u8 => LprServer,
+            10u8 => ImpressServer,
+            11u8 => ResourceLocationServer,
+            12u8 => HostName,
+            13u8 => BootFileSize,
+            14u8 => MeritDumpFile,
+            15u8 => DomainName,
+            16u8 => SwapServer,
+            17u8 => RootPath,
+            18u8 => ExtensionsPath,
+            19u8 => IPForwarding,
+            20u8 => NonLocalSourceRouting,
+            21u8 => PolicyFilter,
+            22u8 => MaxDatagramReassemblySize,
+            23u8 => DefaultIpTtl,
+            24u8 => PathMtuAgingTimeout,
+            25u8 => PathMtuPlateauTable,
+            26u8 => InterfaceMtu,
+            27u8 => AllSubnetsAreLocal,
+            28u8 => BroadcastAddress,
+            29u8 => PerformMaskDiscovery,
+            30u8 => MaskSupplier,
+            31u8 => PerformRouterDiscovery,
+            32u8 => RouterSolicitationAddress,
+            33u8 => StaticRoute,
+            34u8 => TrailerEncapsulation,
+            35u8 => ArpCacheTimeout,
+            36u8 => EthernetEncapsulation,
+            37u8 => TcpDefaultTtl,
+            38u8 => TcpKeepaliveInterval,
+            39u8 => TcpKeepaliveGarbage,
+            40u8 => NisDomain,
+            41u8 => NetworkInformationServers,
+            42u8 => NtpServers,
+            43u8 => VendorExtensions,
+            44u8 => NetBiosNameServers,
+            45u8 => NetBiosDatagramDistributionServer,
+            46u8 => NetBiosNodeType,
+            47u8 => NetBiosScope,
+            48u8 => XFontServer,
+            49u8 => XDisplayManager,
+            50u8 => RequestedIpAddress,
+            51u8 => IpAddressLeaseTime,
+            52u8 => OptionOverload,
+            53u8 => MessageType,
+            54u8 => ServerIdentifier,
+            55u8 => ParamRequestList,
+            56u8 => Message,
+            57u8 => MaxMessageSize,
+            58u8 => RenewalTimeValue,
+            59u8 => RebindingTimeValue,
+            60u8 => ClassIdentifier,
+            61u8 => ClientIdentifier,
+            62u8 => NetwareIpDomain,
+            63u8 => NetwareIpInformation,
+            64u8 => NisPlusDomain,
+            65u8 => NisPlusServers,
+            66u8 => TftpServerName,
+            67u8 => BootfileName,
+            68u8 => MobileIpHomeAgent,
+            69u8 => SmtpServers,
+            70u8 => Pop3Servers,
+            71u8 => NntpServers,
+            72u8 => WwwServers,
+            73u8 => FingerServers,
+            74u8 => IrcServers,
+            75u8 => StreetTalkServers,
+            76u8 => StdaServers,
+            77u8 => UserClass,
+            78u8 => DirectoryAgent,
+            79u8 => ServiceScope,
+            80u8 => RapidCommit,
+            81u8 => ClientFqdn,
+            82u8 => RelayAgentInformation,
+            83u8 => Isns,
+            85u8 => NdsServers,
+            86u8 => NdsTreeName,
+            87u8 => NdsContext,
+            88u8 => BcmcsControllerDomains,
+            89u8 => BcmcsControllerAddresses,
+            90u8 => Authentication,
+            91u8 => ClientLastTransactionTime,
+            92u8 => AssociatedIp,
+            93u8 => ClientSystem,
+            94u8 => ClientNdi,
+            95u8 => Ldap,
+            97u8 => ClientUuid,
+            98u8 => UserAuth,
+            99u8 => CivicLocation,
+            100u8 => PCode,
+            101u8 => TCode,
+            108u8 => Ipv6OnlyPreferred,
+            109u8 => Dhcp4o6S46Saddr,
+            112u8 => NetinfoParentServerAddress,
+            113u8 => NetinfoParentServerTag,
+            114u8 => CaptivePortal,
+            116u8 => AutoConfigure,
+            117u8 => NameServiceSearch,
+            118u8 => SubnetSelection,
+            119u8 => DomainSearch,
+            120u8 => SipServers,
+            121u8 => ClasslessStaticRoute,
+            122u8 => Ccc,
+            123u8 => GeoConf,
+            124u8 => VendorIdentifyingVendorClass,
+            125u8 => VendorIdentifyingVendorInformation,
+            128u8..=135u8 => Pxe(code),
+            136u8 => PanaAgents,
+            137u8 => LostServer,
+            138u8 => CapwapAcAddresses,
+            139u8 => MosAddresses,
+            140u8 => MosFqdns,
+            141u8 => SipUaConfigDomains,
+            142u8 => AndsfAddresses,
+            143u8 => SztpRedirect,
+            144u8 => GeoLoc,
+            145u8 => ForcerenewNonceCapable,
+            146u8 => RdnssSelection,
+            147u8 => DotsReferenceIdentifier,
+            148u8 => DotsAddresses,
+            150u8 => TftpServerAddresses,
+            151u8 => StatusCode,
+            152u8 => BaseTime,
+            153u8 => StartTimeOfState,
+            154u8 => QueryStartTime,
+            155u8 => QueryEndTime,
+            156u8 => DhcpState,
+            157u8 => DataSource,
+            158u8 => PcpServer,
+            159u8 => PortParams,
+            161u8 => MudUrl,
+            162u8 => EncryptedDnsResolver,
+            175u8 => Etherboot,
+            176u8 => IpTelephone,
+            177u8 => PacketCable,
+            208u8 => PxelinuxMagic,
+            209u8 => PxelinuxConfigFile,
+            210u8 => PxelinuxPathPrefix,
+            211u8 => PxelinuxRebootTime,
+            212u8 => SixRd,
+            213u8 => AccessDomain,
+            220u8 => SubnetAllocation,
+            221u8 => VirtualSubnetSelection,
+            252u8 => ProxyAutoDiscovery,
+            255u8 => End,
+            224u8..=254u8 => PrivateUse(code),
+            n => Unassigned(n),
+        }
+    }
+}
+
+impl From<OptionCode> for u8 {
+    fn from(code: OptionCode) -> u8 {
+        use self::OptionCode::*;
+        match code {
+            Pad => 0u8,
+            SubnetMask => 1u8,
+            TimeOffset => 2u8,
+            Router => 3u8,
+            TimeServer => 4u8,
+            NameServer => 5u8,
+            DomainNameServer => 6u8,
+            LogServer => 7u8,
+            CookieServer => 8u8,
+            LprServer => 9u8,
+            ImpressServer => 10u8,
+            ResourceLocationServer => 11u8,
+            HostName => 12u8,
+            BootFileSize => 13u8,
+            MeritDumpFile => 14u8,
+            DomainName => 15u8,
+            SwapServer => 16u8,
+            RootPath => 17u8,
+            ExtensionsPath => 18u8,
+            IPForwarding => 19u8,
+            NonLocalSourceRouting => 20u8,
+            PolicyFilter => 21u8,
+            MaxDatagramReassemblySize => 22u8,
+            DefaultIpTtl => 23u8,
+            PathMtuAgingTimeout => 24u8,
+            PathMtuPlateauTable => 25u8,
+            InterfaceMtu => 26u8,
+            AllSubnetsAreLocal => 27u8,
+            BroadcastAddress => 28u8,
+            PerformMaskDiscovery => 29u8,
+            MaskSupplier => 30u8,
+            PerformRouterDiscovery => 31u8,
+            RouterSolicitationAddress => 32u8,
+            StaticRoute => 33u8,
+            TrailerEncapsulation => 34u8,
+            ArpCacheTimeout => 35u8,
+            EthernetEncapsulation => 36u8,
+            TcpDefaultTtl => 37u8,
+            TcpKeepaliveInterval => 38u8,
+            TcpKeepaliveGarbage => 39u8,
+            NisDomain => 40u8,
+            NetworkInformationServers => 41u8,
+            NtpServers => 42u8,
+            VendorExtensions => 43u8,
+            NetBiosNameServers => 44u8,
+            NetBiosDatagramDistributionServer => 45u8,
+            NetBiosNodeType => 46u8,
+            NetBiosScope => 47u8,
+            XFontServer => 48u8,
+            XDisplayManager => 49u8,
+            RequestedIpAddress => 50u8,
+            IpAddressLeaseTime => 51u8,
+            OptionOverload => 52u8,
+            MessageType => 53u8,
+            ServerIdentifier => 54u8,
+            ParamRequestList => 55u8,
+            Message => 56u8,
+            MaxMessageSize => 57u8,
+            RenewalTimeValue => 58u8,
+            RebindingTimeValue => 59u8,
+            ClassIdentifier => 60u8,
+            ClientIdentifier => 61u8,
+            NetwareIpDomain => 62u8,
+            NetwareIpInformation => 63u8,
+            NisPlusDomain => 64u8,
+            NisPlusServers => 65u8,
+            TftpServerName => 66u8,
+            BootfileName => 67u8,
+            MobileIpHomeAgent => 68u8,
+            SmtpServers => 69u8,
+            Pop3Servers => 70u8,
+            NntpServers => 71u8,
+            WwwServers => 72u8,
+            FingerServers => 73u8,
+            IrcServers => 74u8,
+            StreetTalkServers => 75u8,
+            StdaServers => 76u8,
+            UserClass => 77u8,
+            DirectoryAgent => 78u8,
+            ServiceScope => 79u8,
+            RapidCommit => 80u8,
+            ClientFqdn => 81u8,
+            RelayAgentInformation => 82u8,
+            Isns => 83u8,
+            NdsServers => 85u8,
+            NdsTreeName => 86u8,
+            NdsContext => 87u8,
+            BcmcsControllerDomains => 88u8,
+            BcmcsControllerAddresses => 89u8,
+            Authentication => 90u8,
+            ClientLastTransactionTime => 91u8,
+            AssociatedIp => 92u8,
+            ClientSystem => 93u8,
+            ClientNdi => 94u8,
+            Ldap => 95u8,
+            ClientUuid => 97u8,
+            UserAuth => 98u8,
+            CivicLocation => 99u8,
+            PCode => 100u8,
+            TCode => 101u8,
+            Ipv6OnlyPreferred => 108u8,
+            Dhcp4o6S46Saddr => 109u8,
+            NetinfoParentServerAddress => 112u8,
+            NetinfoParentServerTag => 113u8,
+            CaptivePortal => 114u8,
+            AutoConfigure => 116u8,
+            NameServiceSearch => 117u8,
+            SubnetSelection => 118u8,
+            DomainSearch => 119u8,
+            SipServers => 120u8,
+            ClasslessStaticRoute => 121u8,
+            Ccc => 122u8,
+            GeoConf => 123u8,
+            VendorIdentifyingVendorClass => 124u8,
+            VendorIdentifyingVendorInformation => 125u8,
+            Pxe(n) => n,
+            PanaAgents => 136u8,
+            LostServer => 137u8,
+            CapwapAcAddresses => 138u8,
+            MosAddresses => 139u8,
+            MosFqdns => 140u8,
+            SipUaConfigDomains => 141u8,
+            AndsfAddresses => 142u8,
+            SztpRedirect => 143u8,
+            GeoLoc => 144u8,
+            ForcerenewNonceCapable => 145u8,
+            RdnssSelection => 146u8,
+            DotsReferenceIdentifier => 147u8,
+            DotsAddresses => 148u8,
+            TftpServerAddresses => 150u8,
+            StatusCode => 151u8,
+            BaseTime => 152u8,
+            StartTimeOfState => 153u8,
+            QueryStartTime => 154u8,
+            QueryEndTime => 155u8,
+            DhcpState => 156u8,
+            DataSource => 157u8,
+            PcpServer => 158u8,
+            PortParams => 159u8,
+            MudUrl => 161u8,
+            EncryptedDnsResolver => 162u8,
+            Etherboot => 175u8,
+            IpTelephone => 176u8,
+            PacketCable => 177u8,
+            PxelinuxMagic => 208u8,
+            PxelinuxConfigFile => 209u8,
+            PxelinuxPathPrefix => 210u8,
+            PxelinuxRebootTime => 211u8,
+            SixRd => 212u8,
+            AccessDomain => 213u8,
+            SubnetAllocation => 220u8,
+            VirtualSubnetSelection => 221u8,
+            ProxyAutoDiscovery => 252u8,
+            End => 255u8,
+            PrivateUse(n) => n,
+            Unassigned(n) => n,
+        }
+    }
+}
+
+impl OptionCode {
+    /// The name IANA registers, e.g. "Subnet Mask". Codes for private use
+    /// have none, except 252.
+    pub fn name(self) -> Option<&'static str> {
+        use self::OptionCode::*;
+        let name = match self {
+            Pad => "Pad",
+            SubnetMask => "Subnet Mask",
+            TimeOffset => "Time Offset",
+            Router => "Router",
+            TimeServer => "Time Server",
+            NameServer => "Name Server",
+            DomainNameServer => "Domain Server",
+            LogServer => "Log Server",
+            CookieServer => "Quotes Server",
+            LprServer => "LPR Server",
+            ImpressServer => "Impress Server",
+            ResourceLocationServer => "RLP Server",
+            HostName => "Hostname",
+            BootFileSize => "Boot File Size",
+            MeritDumpFile => "Merit Dump File",
+            DomainName => "Domain Name",
+            SwapServer => "Swap Server",
+            RootPath => "Root Path",
+            ExtensionsPath => "Extension File",
+            IPForwarding => "Forward On/Off",
+            NonLocalSourceRouting => "SrcRte On/Off",
+            PolicyFilter => "Policy Filter",
+            MaxDatagramReassemblySize => "Max DG Assembly",
+            DefaultIpTtl => "Default IP TTL",
+            PathMtuAgingTimeout => "MTU Timeout",
+            PathMtuPlateauTable => "MTU Plateau",
+            InterfaceMtu => "MTU Interface",
+            AllSubnetsAreLocal => "MTU Subnet",
+            BroadcastAddress => "Broadcast Address",
+            PerformMaskDiscovery => "Mask Discovery",
+            MaskSupplier => "Mask Supplier",
+            PerformRouterDiscovery => "Router Discovery",
+            RouterSolicitationAddress => "Router Request",
+            StaticRoute => "Static Route",
+            TrailerEncapsulation => "Trailers",
+            ArpCacheTimeout => "ARP Timeout",
+            EthernetEncapsulation => "Ethernet",
+            TcpDefaultTtl => "Default TCP TTL",
+            TcpKeepaliveInterval => "Keepalive Time",
+            TcpKeepaliveGarbage => "Keepalive Data",
+            NisDomain => "NIS Domain",
+            NetworkInformationServers => "NIS Servers",
+            NtpServers => "NTP Servers",
+            VendorExtensions => "Vendor Specific",
+            NetBiosNameServers => "NETBIOS Name Srv",
+            NetBiosDatagramDistributionServer => "NETBIOS Dist Srv",
+            NetBiosNodeType => "NETBIOS Node Type",
+            NetBiosScope => "NETBIOS Scope",
+            XFontServer => "X Window Font",
+            XDisplayManager => "X Window Manager",
+            RequestedIpAddress => "Address Request",
+            IpAddressLeaseTime => "Address Time",
+            OptionOverload => "Overload",
+            MessageType => "DHCP Msg Type",
+            ServerIdentifier => "DHCP Server Id",
+            ParamRequestList => "Parameter List",
+            Message => "DHCP Message",
+            MaxMessageSize => "DHCP Max Msg Size",
+            RenewalTimeValue => "Renewal Time",
+            RebindingTimeValue => "Rebinding Time",
+            ClassIdentifier => "Class Id",
+            ClientIdentifier => "Client Id",
+            NetwareIpDomain => "NetWare/IP Domain",
+            NetwareIpInformation => "NetWare/IP Option",
+            NisPlusDomain => "NIS-Domain-Name",
+            NisPlusServers => "NIS-Server-Addr",
+            TftpServerName => "Server-Name",
+            BootfileName => "Bootfile-Name",
+            MobileIpHomeAgent => "Home-Agent-Addrs",
+            SmtpServers => "SMTP-Server",
+            Pop3Servers => "POP3-Server",
+            NntpServers => "NNTP-Server",
+            WwwServers => "WWW-Server",
+            FingerServers => "Finger-Server",
+            IrcServers => "IRC-Server",
+            StreetTalkServers => "StreetTalk-Server",
+            StdaServers => "STDA-Server",
+            UserClass => "User-Class",
+            DirectoryAgent => "Directory Agent",
+            ServiceScope => "Service Scope",
+            RapidCommit => "Rapid Commit",
+            ClientFqdn => "Client FQDN",
+            RelayAgentInformation => "Relay Agent Information",
+            Isns => "iSNS",
+            NdsServers => "NDS Servers",
+            NdsTreeName => "NDS Tree Name",
+            NdsContext => "NDS Context",
+            BcmcsControllerDomains => "BCMCS Controller Domain Name list",
+            BcmcsControllerAddresses => "BCMCS Controller IPv4 address option",
+            Authentication => "Authentication",
+            ClientLastTransactionTime => "client-last-transaction-time option",
+            AssociatedIp => "associated-ip option",
+            ClientSystem => "Client System",
+            ClientNdi => "Client NDI",
+            Ldap => "LDAP",
+            ClientUuid => "UUID/GUID",
+            UserAuth => "User-Auth",
+            CivicLocation => "GEOCONF_CIVIC",
+            PCode => "PCode",
+            TCode => "TCode",
+            Ipv6OnlyPreferred => "IPv6-Only Preferred",
+            Dhcp4o6S46Saddr => "OPTION_DHCP4O6_S46_SADDR",
+            NetinfoParentServerAddress => "Netinfo Address",
+            NetinfoParentServerTag => "Netinfo Tag",
+            CaptivePortal => "DHCP Captive-Portal",
+            AutoConfigure => "Auto-Config",
+            NameServiceSearch => "Name Service Search",
+            SubnetSelection => "Subnet Selection Option",
+            DomainSearch => "Domain Search",
+            SipServers => "SIP Servers DHCP Option",
+            ClasslessStaticRoute => "Classless Static Route Option",
+            Ccc => "CCC",
+            GeoConf => "GeoConf Option",
+            VendorIdentifyingVendorClass => "V-I Vendor Class",
+            VendorIdentifyingVendorInformation => "V-I Vendor-Specific Information",
+            Pxe(_) => "PXE - undefined (vendor specific)",
+            PanaAgents => "OPTION_PANA_AGENT",
+            LostServer => "OPTION_V4_LOST",
+            CapwapAcAddresses => "OPTION_CAPWAP_AC_V4",
+            MosAddresses => "OPTION-IPv4_Address-MoS",
+            MosFqdns => "OPTION-IPv4_FQDN-MoS",
+            SipUaConfigDomains => "SIP UA Configuration Service Domains",
+            AndsfAddresses => "OPTION-IPv4_Address-ANDSF",
+            SztpRedirect => "OPTION_V4_SZTP_REDIRECT",
+            GeoLoc => "GeoLoc",
+            ForcerenewNonceCapable => "FORCERENEW_NONCE_CAPABLE",
+            RdnssSelection => "RDNSS Selection",
+            DotsReferenceIdentifier => "OPTION_V4_DOTS_RI",
+            DotsAddresses => "OPTION_V4_DOTS_ADDRESS",
+            TftpServerAddresses => "TFTP server address",
+            StatusCode => "status-code",
+            BaseTime => "base-time",
+            StartTimeOfState => "start-time-of-state",
+            QueryStartTime => "query-start-time",
+            QueryEndTime => "query-end-time",
+            DhcpState => "dhcp-state",
+            DataSource => "data-source",
+            PcpServer => "OPTION_V4_PCP_SERVER",
+            PortParams => "OPTION_V4_PORTPARAMS",
+            MudUrl => "OPTION_MUD_URL_V4",
+            EncryptedDnsResolver => "OPTION_V4_DNR",
+            Etherboot => "Etherboot",
+            IpTelephone => "IP Telephone",
+            PacketCable => "PacketCable and CableHome",
+            PxelinuxMagic => "PXELINUX Magic",
+            PxelinuxConfigFile => "Configuration File",
+            PxelinuxPathPrefix => "Path Prefix",
+            PxelinuxRebootTime => "Reboot Time",
+            SixRd => "OPTION_6RD",
+            AccessDomain => "OPTION_V4_ACCESS_DOMAIN",
+            SubnetAllocation => "Subnet Allocation Option",
+            VirtualSubnetSelection => "Virtual Subnet Selection (VSS) Option",
+            ProxyAutoDiscovery => "Web Proxy Auto-Discovery",
+            End => "End",
+            PrivateUse(_) | Unassigned(_) => return None,
+        };
+        Some(name)
+    }
+}
+
+/// The name IANA registers for option `code`; see `OptionCode::name`
+pub fn option_name(code: u8) -> Option<&'static str> {
+    OptionCode::from(code).name()
+}
+
+#[cfg(test)] mod tests {
+    use super::{OptionCode, option_name};
+
+    #[test]
+    fn test_option_code() {
+        for code in 0..=255u8 {
+            assert_eq!(u8::from(OptionCode::from(code)), code);
+        }
+        assert_eq!(OptionCode::from(1u8), OptionCode::SubnetMask);
+        assert_eq!(OptionCode::from(130u8), OptionCode::Pxe(130));
+        assert_eq!(OptionCode::from(230u8), OptionCode::PrivateUse(230));
+        assert_eq!(OptionCode::from(84u8), OptionCode::Unassigned(84));
+        assert_eq!(option_name(121u8), Some("Classless Static Route Option"));
+        assert_eq!(option_name(252u8), Some("Web Proxy Auto-Discovery"));
+        assert_eq!(option_name(230u8), None);
+    }
+}
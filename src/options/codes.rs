@@ -0,0 +1,314 @@
+//! Option and suboption codes, grouped by the RFC that defines them
+
+// RFC 2132
+pub const PAD: u8 = 0;
+pub const SUBNET_MASK: u8 = 1;
+pub const TIME_OFFSET: u8 = 2;
+pub const ROUTER: u8 = 3;
+pub const TIME_SERVER: u8 = 4;
+pub const NAME_SERVER: u8 = 5;
+pub const DOMAIN_NAME_SERVER: u8 = 6;
+pub const LOG_SERVER: u8 = 7;
+pub const COOKIE_SERVER: u8 = 8;
+pub const LPR_SERVER: u8 = 9;
+pub const IMPRESS_SERVER: u8 = 10;
+pub const RESOURCE_LOCATION_SERVER: u8 = 11;
+pub const HOST_NAME: u8 = 12;
+pub const BOOT_FILE_SIZE: u8 = 13;
+pub const MERIT_DUMP_FILE: u8 = 14;
+pub const DOMAIN_NAME: u8 = 15;
+pub const SWAP_SERVER: u8 = 16;
+pub const ROOT_PATH: u8 = 17;
+pub const EXTENSIONS_PATH: u8 = 18;
+pub const IP_FORWARDING: u8 = 19;
+pub const NON_LOCAL_SOURCE_ROUTING: u8 = 20;
+pub const POLICY_FILTER: u8 = 21;
+pub const MAX_DATAGRAM_REASSEMBLY_SIZE: u8 = 22;
+pub const DEFAULT_IP_TTL: u8 = 23;
+pub const PATH_MTU_AGING_TIMEOUT: u8 = 24;
+pub const PATH_MTU_PLATEAU_TABLE: u8 = 25;
+pub const INTERFACE_MTU: u8 = 26;
+pub const ALL_SUBNETS_ARE_LOCAL: u8 = 27;
+pub const BROADCAST_ADDRESS: u8 = 28;
+pub const PERFORM_MASK_DISCOVERY: u8 = 29;
+pub const MASK_SUPPLIER: u8 = 30;
+pub const PERFORM_ROUTER_DISCOVERY: u8 = 31;
+pub const ROUTER_SOLICITATION_ADDRESS: u8 = 32;
+pub const STATIC_ROUTE: u8 = 33;
+pub const TRAILER_ENCAPSULATION: u8 = 34;
+pub const ARP_CACHE_TIMEOUT: u8 = 35;
+pub const ETHERNET_ENCAPSULATION: u8 = 36;
+pub const TCP_DEFAULT_TTL: u8 = 37;
+pub const TCP_KEEPALIVE_INTERVAL: u8 = 38;
+pub const TCP_KEEPALIVE_GARBAGE: u8 = 39;
+pub const NIS_DOMAIN: u8 = 40;
+pub const NETWORK_INFORMATION_SERVERS: u8 = 41;
+pub const NTP_SERVERS: u8 = 42;
+pub const VENDOR_EXTENSIONS: u8 = 43;
+pub const NETBIOS_NAME_SERVERS: u8 = 44;
+pub const NETBIOS_DATAGRAM_DISTRIBUTION_SERVER: u8 = 45;
+pub const NETBIOS_NODE_TYPE: u8 = 46;
+pub const NETBIOS_SCOPE: u8 = 47;
+pub const X_FONT_SERVER: u8 = 48;
+pub const X_DISPLAY_MANAGER: u8 = 49;
+pub const REQUESTED_IP_ADDRESS: u8 = 50;
+pub const IP_ADDRESS_LEASE_TIME: u8 = 51;
+pub const OPTION_OVERLOAD: u8 = 52;
+pub const MESSAGE_TYPE: u8 = 53;
+pub const SERVER_IDENTIFIER: u8 = 54;
+pub const PARAM_REQUEST_LIST: u8 = 55;
+pub const MESSAGE: u8 = 56;
+pub const MAX_MESSAGE_SIZE: u8 = 57;
+pub const RENEWAL_TIME_VALUE: u8 = 58;
+pub const REBINDING_TIME_VALUE: u8 = 59;
+pub const CLASS_IDENTIFIER: u8 = 60;
+pub const CLIENT_IDENTIFIER: u8 = 61;
+pub const NIS_PLUS_DOMAIN: u8 = 64;
+pub const NIS_PLUS_SERVERS: u8 = 65;
+pub const TFTP_SERVER_NAME: u8 = 66;
+pub const BOOTFILE_NAME: u8 = 67;
+pub const MOBILE_IP_HOME_AGENT: u8 = 68;
+pub const SMTP_SERVERS: u8 = 69;
+pub const POP3_SERVERS: u8 = 70;
+pub const NNTP_SERVERS: u8 = 71;
+pub const WWW_SERVERS: u8 = 72;
+pub const FINGER_SERVERS: u8 = 73;
+pub const IRC_SERVERS: u8 = 74;
+pub const STREET_TALK_SERVERS: u8 = 75;
+pub const STDA_SERVERS: u8 = 76;
+pub const END: u8 = 255;
+
+// RFC 2242
+pub const NETWARE_IP_DOMAIN: u8 = 62;
+pub const NETWARE_IP_INFORMATION: u8 = 63;
+
+// RFC 3004
+pub const USER_CLASS: u8 = 77;
+
+// RFC 2610
+pub const DIRECTORY_AGENT: u8 = 78;
+pub const SERVICE_SCOPE: u8 = 79;
+
+// RFC 4039
+pub const RAPID_COMMIT: u8 = 80;
+
+// RFC 4702
+pub const CLIENT_FQDN: u8 = 81;
+
+// RFC 3046
+pub const RELAY_AGENT_INFORMATION: u8 = 82;
+
+// RFC 4174
+pub const ISNS: u8 = 83;
+
+// RFC 2241
+pub const NDS_SERVERS: u8 = 85;
+pub const NDS_TREE_NAME: u8 = 86;
+pub const NDS_CONTEXT: u8 = 87;
+
+// RFC 4280
+pub const BCMCS_CONTROLLER_DOMAINS: u8 = 88;
+pub const BCMCS_CONTROLLER_ADDRESSES: u8 = 89;
+
+// RFC 3118
+pub const AUTHENTICATION: u8 = 90;
+
+// RFC 4388
+pub const CLIENT_LAST_TRANSACTION_TIME: u8 = 91;
+pub const ASSOCIATED_IP: u8 = 92;
+
+// RFC 4578
+pub const CLIENT_SYSTEM: u8 = 93;
+pub const CLIENT_NDI: u8 = 94;
+pub const CLIENT_UUID: u8 = 97;
+
+// RFC 3679
+pub const LDAP: u8 = 95;
+pub const NETINFO_PARENT_SERVER_ADDRESS: u8 = 112;
+pub const NETINFO_PARENT_SERVER_TAG: u8 = 113;
+
+// RFC 2485
+pub const USER_AUTH: u8 = 98;
+
+// RFC 4776
+pub const CIVIC_LOCATION: u8 = 99;
+
+// RFC 4833
+pub const PCODE: u8 = 100;
+pub const TCODE: u8 = 101;
+
+// RFC 8925
+pub const IPV6_ONLY_PREFERRED: u8 = 108;
+
+// RFC 8539
+pub const DHCP4O6_S46_SADDR: u8 = 109;
+
+// RFC 8910
+pub const CAPTIVE_PORTAL: u8 = 114;
+
+// RFC 2563
+pub const AUTO_CONFIGURE: u8 = 116;
+
+// RFC 2937
+pub const NAME_SERVICE_SEARCH: u8 = 117;
+
+// RFC 3011
+pub const SUBNET_SELECTION: u8 = 118;
+
+// RFC 3397
+pub const DOMAIN_SEARCH: u8 = 119;
+
+// RFC 3361
+pub const SIP_SERVERS: u8 = 120;
+
+// RFC 3442
+pub const CLASSLESS_STATIC_ROUTE: u8 = 121;
+
+// RFC 3495
+pub const CCC: u8 = 122;
+
+// RFC 6225
+pub const GEO_CONF: u8 = 123;
+pub const GEO_LOC: u8 = 144;
+
+// RFC 3925
+pub const VENDOR_IDENTIFYING_VENDOR_CLASS: u8 = 124;
+pub const VENDOR_IDENTIFYING_VENDOR_INFORMATION: u8 = 125;
+
+// RFC 5192
+pub const PANA_AGENTS: u8 = 136;
+
+// RFC 5223
+pub const LOST_SERVER: u8 = 137;
+
+// RFC 5417
+pub const CAPWAP_AC_ADDRESSES: u8 = 138;
+
+// RFC 5678
+pub const MOS_ADDRESSES: u8 = 139;
+pub const MOS_FQDNS: u8 = 140;
+
+// RFC 6011
+pub const SIP_UA_CONFIG_DOMAINS: u8 = 141;
+
+// RFC 6153
+pub const ANDSF_ADDRESSES: u8 = 142;
+
+// RFC 8572
+pub const SZTP_REDIRECT: u8 = 143;
+
+// RFC 6704
+pub const FORCERENEW_NONCE_CAPABLE: u8 = 145;
+
+// RFC 6731
+pub const RDNSS_SELECTION: u8 = 146;
+
+// RFC 8973
+pub const DOTS_REFERENCE_IDENTIFIER: u8 = 147;
+pub const DOTS_ADDRESSES: u8 = 148;
+
+// RFC 5859
+pub const TFTP_SERVER_ADDRESSES: u8 = 150;
+
+// RFC 6926
+pub const STATUS_CODE: u8 = 151;
+pub const BASE_TIME: u8 = 152;
+pub const START_TIME_OF_STATE: u8 = 153;
+pub const QUERY_START_TIME: u8 = 154;
+pub const QUERY_END_TIME: u8 = 155;
+pub const DHCP_STATE: u8 = 156;
+pub const DATA_SOURCE: u8 = 157;
+
+// RFC 7291
+pub const PCP_SERVER: u8 = 158;
+
+// RFC 7618
+pub const PORT_PARAMS: u8 = 159;
+
+// RFC 8520
+pub const MUD_URL: u8 = 161;
+
+// RFC 9463
+pub const ENCRYPTED_DNS_RESOLVER: u8 = 162;
+
+// RFC 5071
+pub const PXELINUX_MAGIC: u8 = 208;
+pub const PXELINUX_CONFIG_FILE: u8 = 209;
+pub const PXELINUX_PATH_PREFIX: u8 = 210;
+pub const PXELINUX_REBOOT_TIME: u8 = 211;
+
+// RFC 5969
+pub const SIX_RD: u8 = 212;
+
+// RFC 5986
+pub const ACCESS_DOMAIN: u8 = 213;
+
+// RFC 6656
+pub const SUBNET_ALLOCATION: u8 = 220;
+
+// RFC 6607
+pub const VIRTUAL_SUBNET_SELECTION: u8 = 221;
+
+// Tentatively assigned, or private use but widely deployed
+pub const ETHERBOOT: u8 = 175;
+pub const IP_TELEPHONE: u8 = 176;
+pub const PACKET_CABLE: u8 = 177;
+pub const PROXY_AUTO_DISCOVERY: u8 = 252;
+
+/// Relay agent information suboptions, in option 82
+pub mod relay_agent {
+    // RFC 3046
+    pub const AGENT_CIRCUIT_ID: u8 = 1;
+    pub const AGENT_REMOTE_ID: u8 = 2;
+    // RFC 3256
+    pub const DOCSIS_DEVICE_CLASS: u8 = 4;
+    // RFC 3527
+    pub const LINK_SELECTION: u8 = 5;
+    // RFC 3993
+    pub const SUBSCRIBER_ID: u8 = 6;
+    // RFC 4014
+    pub const RADIUS_ATTRIBUTES: u8 = 7;
+    // RFC 4030
+    pub const AUTHENTICATION: u8 = 8;
+    // RFC 4243
+    pub const VENDOR_SPECIFIC_INFORMATION: u8 = 9;
+    // RFC 5010
+    pub const RELAY_AGENT_FLAGS: u8 = 10;
+    // RFC 5107
+    pub const SERVER_IDENTIFIER_OVERRIDE: u8 = 11;
+    // RFC 6925
+    pub const RELAY_AGENT_IDENTIFIER: u8 = 12;
+    // RFC 7839
+    pub const ACCESS_TECHNOLOGY_TYPE: u8 = 13;
+    pub const ACCESS_NETWORK_NAME: u8 = 14;
+    pub const ACCESS_POINT_NAME: u8 = 15;
+    pub const ACCESS_POINT_BSSID: u8 = 16;
+    pub const OPERATOR_IDENTIFIER: u8 = 17;
+    pub const OPERATOR_REALM: u8 = 18;
+    // RFC 8357
+    pub const RELAY_PORT: u8 = 19;
+    // RFC 6607
+    pub const VIRTUAL_SUBNET_SELECTION: u8 = 151;
+    pub const VIRTUAL_SUBNET_SELECTION_CONTROL: u8 = 152;
+}
+
+/// NetWare/IP suboptions, in option 63 (RFC 2242)
+pub mod netware_ip {
+    pub const NWIP_DOES_NOT_EXIST: u8 = 1;
+    pub const NWIP_EXIST_IN_OPTIONS_AREA: u8 = 2;
+    pub const NWIP_EXIST_IN_SNAME_FILE: u8 = 3;
+    pub const NWIP_EXIST_BUT_TOO_BIG: u8 = 4;
+    pub const NSQ_BROADCAST: u8 = 5;
+    pub const PREFERRED_DSS: u8 = 6;
+    pub const NEAREST_NWIP_SERVER: u8 = 7;
+    pub const AUTORETRIES: u8 = 8;
+    pub const AUTORETRY_SECS: u8 = 9;
+    pub const NWIP_1_1: u8 = 10;
+    pub const PRIMARY_DSS: u8 = 11;
+}
+
+/// Subnet allocation suboptions, in option 220 (RFC 6656)
+pub mod subnet_allocation {
+    pub const SUBNET_INFORMATION: u8 = 1;
+    pub const SUBNET_NAME: u8 = 2;
+}
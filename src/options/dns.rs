@@ -0,0 +1,332 @@
+//! RFC 1035 domain names, as carried by options 88, 119, 120, 137 and
+//! 141
+//!
+//! Names are decoded to dotted text without a trailing dot, e.g.
+//! `example.com`, with the root name as the empty string. Label bytes
+//! that aren't printable ASCII, and any `.` or `\` inside a label, are
+//! escaped the way RFC 1035 master files do it (`\.`, `\\`, `\DDD`), so
+//! every name that can be decoded encodes back to the same bytes.
+
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+use { Result, Error };
+
+/// Longest name on the wire, including length bytes and the terminator
+pub const MAX_NAME_LENGTH: usize = 255;
+pub const MAX_LABEL_LENGTH: usize = 63;
+
+/// Limit on pointers followed for one name, so a chain of pointers to
+/// pointers can't loop forever in lenient mode
+const MAX_POINTERS: usize = 64;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NameMode {
+    /// Reject compression pointers in options that don't allow them,
+    /// pointers that don't point strictly backwards, and a final name
+    /// that's cut off
+    Strict,
+    /// Follow any pointer and keep whatever is left of a cut off name
+    Lenient,
+}
+
+fn name_error(msg: &str, offset: usize) -> Error {
+    Error::ParseError(format!("Invalid domain name at offset {}: {}", offset, msg))
+}
+
+fn push_label(name: &mut String, label: &[u8]) {
+    if !name.is_empty() {
+        name.push('.');
+    }
+    for &b in label {
+        match b {
+            b'.' | b'\\' => {
+                name.push('\\');
+                name.push(b as char);
+            },
+            0x21..=0x7e => name.push(b as char),
+            _ => name.push_str(&format!("\\{:03}", b)),
+        }
+    }
+}
+
+/// Decode the name starting at `start` in `buf`. `compression` is
+/// whether the option allows compression pointers.
+///
+/// Returns the name and the offset just past it in `buf`, which is
+/// where the next name starts.
+pub fn decode_name(buf: &[u8], start: usize, compression: bool, mode: NameMode) -> Result<(String, usize)> {
+    let mut name = String::new();
+    let mut wire_length = 1;
+    let mut pos = start;
+    // Where the next name starts, once the first pointer has been followed
+    let mut next = None;
+    // Strict mode only follows pointers that go below every pointer
+    // followed so far, which always terminates
+    let mut lowest_pointer = pos;
+    let mut pointers = 0;
+
+    loop {
+        let len = match buf.get(pos) {
+            Some(&len) => len as usize,
+            None if mode == NameMode::Lenient && pos > start => break,
+            None => return Err(name_error("missing terminating zero", pos)),
+        };
+        match len & 0xc0 {
+            0x00 if len == 0 => {
+                pos += 1;
+                break;
+            },
+            0x00 => {
+                let end = pos + 1 + len;
+                if end > buf.len() && mode == NameMode::Strict {
+                    return Err(name_error("label runs past the end of the option", pos));
+                }
+                let end = ::std::cmp::min(end, buf.len());
+                if end == pos + 1 {
+                    // Only the length byte made it
+                    pos = end;
+                    break;
+                }
+                wire_length += 1 + end - (pos + 1);
+                if wire_length > MAX_NAME_LENGTH {
+                    return Err(name_error("name is longer than 255 bytes", start));
+                }
+                push_label(&mut name, buf.get(pos + 1..end).unwrap_or(&[]));
+                pos = end;
+            },
+            0xc0 => {
+                let low = match buf.get(pos + 1) {
+                    Some(&low) => low as usize,
+                    None => return Err(name_error("pointer runs past the end of the option", pos)),
+                };
+                if !compression && mode == NameMode::Strict {
+                    return Err(name_error("compression is not allowed in this option", pos));
+                }
+                let target = ((len & 0x3f) << 8) | low;
+                if target >= buf.len() {
+                    return Err(name_error("pointer target is outside the option", pos));
+                }
+                if mode == NameMode::Strict && target >= lowest_pointer {
+                    return Err(name_error("pointer does not point backwards", pos));
+                }
+                pointers += 1;
+                if pointers > MAX_POINTERS {
+                    return Err(name_error("too many pointers", pos));
+                }
+                if next.is_none() {
+                    next = Some(pos + 2);
+                }
+                lowest_pointer = ::std::cmp::min(lowest_pointer, pos);
+                pos = target;
+            },
+            _ => return Err(name_error("reserved label type", pos)),
+        }
+    }
+    Ok((name, next.unwrap_or(pos)))
+}
+
+/// Decode a list of names filling the whole of `buf`
+pub fn decode_names(buf: &[u8], compression: bool, mode: NameMode) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    let mut pos = 0;
+    while pos < buf.len() {
+        let (name, next) = decode_name(buf, pos, compression, mode)?;
+        names.push(name);
+        pos = next;
+    }
+    Ok(names)
+}
+
+/// Split dotted text into labels, undoing the escapes `decode_name` adds
+fn labels(name: &str) -> Vec<Vec<u8>> {
+    let bytes = name.as_bytes();
+    let mut labels = Vec::new();
+    let mut label = Vec::new();
+    let mut i = 0;
+    while let Some(&b) = bytes.get(i) {
+        match b {
+            b'.' => labels.push(::std::mem::take(&mut label)),
+            b'\\' if i + 1 < bytes.len() => {
+                let value = match bytes.get(i + 1..i + 4) {
+                    Some(digits) if digits.iter().all(|d| d.is_ascii_digit()) => {
+                        digits.iter().fold(0u32, |v, d| v * 10 + (d - b'0') as u32)
+                    },
+                    _ => 256,
+                };
+                if value < 256 {
+                    label.push(value as u8);
+                    i += 3;
+                } else {
+                    label.extend(bytes.get(i + 1));
+                    i += 1;
+                }
+            },
+            b => label.push(b),
+        }
+        i += 1;
+    }
+    labels.push(label);
+    labels
+}
+
+/// Append `name` uncompressed. Empty labels are dropped, longer labels
+/// are cut to 63 bytes and labels that would take the name past 255
+/// bytes are left off.
+pub fn encode_name(name: &str, buf: &mut Vec<u8>) {
+    let mut wire_length = 1;
+    for mut label in labels(name) {
+        if label.is_empty() {
+            continue;
+        }
+        label.truncate(MAX_LABEL_LENGTH);
+        if wire_length + 1 + label.len() > MAX_NAME_LENGTH {
+            break;
+        }
+        wire_length += 1 + label.len();
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(&label);
+    }
+    buf.push(0u8);
+}
+
+pub fn encode_names(names: &[String]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for name in names {
+        encode_name(name, &mut buf);
+    }
+    buf
+}
+
+#[cfg(test)] mod tests {
+    use super::{decode_name, decode_names, encode_name, encode_names, NameMode};
+    use super::NameMode::{Strict, Lenient};
+
+    fn both_modes<F: Fn(NameMode)>(f: F) {
+        f(Strict);
+        f(Lenient);
+    }
+
+    #[test]
+    fn test_decode_name() {
+        let buf = [7u8, 101, 120, 97, 109, 112, 108, 101, 3, 99, 111, 109, 0];
+        both_modes(|mode| {
+            assert_eq!(decode_name(&buf, 0, false, mode).unwrap(), ("example.com".to_string(), 13));
+        });
+        both_modes(|mode| {
+            assert_eq!(decode_name(&[0u8], 0, false, mode).unwrap(), ("".to_string(), 1));
+        });
+    }
+
+    #[test]
+    fn test_decode_names_rfc3397_example() {
+        // The example from RFC 3397 section 2
+        let buf = [
+            3u8, 101, 110, 103, 7, 101, 120, 97, 109, 112, 108, 101, 3, 99, 111, 109, 0,
+            5, 115, 97, 108, 101, 115, 0xc0, 4,
+        ];
+        both_modes(|mode| {
+            assert_eq!(decode_names(&buf, true, mode).unwrap(),
+                       vec!["eng.example.com".to_string(), "sales.example.com".to_string()]);
+        });
+    }
+
+    #[test]
+    fn test_pointer_not_allowed() {
+        let buf = [1u8, 97, 0, 0xc0, 0];
+        assert!(decode_names(&buf, false, Strict).is_err());
+        assert_eq!(decode_names(&buf, false, Lenient).unwrap(), vec!["a".to_string(), "a".to_string()]);
+    }
+
+    #[test]
+    fn test_forward_pointer() {
+        let buf = [0xc0u8, 2, 1, 97, 0];
+        assert!(decode_name(&buf, 0, true, Strict).is_err());
+        assert_eq!(decode_name(&buf, 0, true, Lenient).unwrap(), ("a".to_string(), 2));
+    }
+
+    #[test]
+    fn test_pointer_loops() {
+        both_modes(|mode| {
+            assert!(decode_name(&[0xc0u8, 0], 0, true, mode).is_err());
+            assert!(decode_name(&[1u8, 97, 0xc0, 0], 0, true, mode).is_err());
+            assert!(decode_name(&[0xc0u8, 2, 0xc0, 0], 0, true, mode).is_err());
+        });
+    }
+
+    #[test]
+    fn test_pointer_past_end() {
+        both_modes(|mode| {
+            assert!(decode_name(&[1u8, 97, 0xc0], 0, true, mode).is_err());
+            assert!(decode_name(&[0xc0u8, 9], 0, true, mode).is_err());
+        });
+    }
+
+    #[test]
+    fn test_reserved_label_types() {
+        both_modes(|mode| {
+            assert!(decode_name(&[0x40u8, 0], 0, true, mode).is_err());
+            assert!(decode_name(&[0x80u8, 0], 0, true, mode).is_err());
+        });
+    }
+
+    #[test]
+    fn test_truncated_name() {
+        let unterminated = [1u8, 97];
+        assert!(decode_name(&unterminated, 0, false, Strict).is_err());
+        assert_eq!(decode_name(&unterminated, 0, false, Lenient).unwrap(), ("a".to_string(), 2));
+
+        let short_label = [3u8, 97];
+        assert!(decode_name(&short_label, 0, false, Strict).is_err());
+        assert_eq!(decode_name(&short_label, 0, false, Lenient).unwrap(), ("a".to_string(), 2));
+
+        both_modes(|mode| assert!(decode_name(&[], 0, false, mode).is_err()));
+    }
+
+    #[test]
+    fn test_name_too_long() {
+        let mut buf = Vec::new();
+        for _ in 0..4 {
+            buf.push(63u8);
+            buf.extend_from_slice(&[97u8; 63]);
+        }
+        buf.push(0u8);
+        both_modes(|mode| assert!(decode_name(&buf, 0, false, mode).is_err()));
+        // 254 bytes of labels plus the terminator is the most allowed
+        buf.truncate(4 * 64 - 2);
+        buf[3 * 64] = 61u8;
+        buf.push(0u8);
+        both_modes(|mode| assert!(decode_name(&buf, 0, false, mode).is_ok()));
+    }
+
+    #[test]
+    fn test_escapes() {
+        let buf = [4u8, 97, 46, 92, 32, 1, 0xff, 0];
+        let (name, _) = decode_name(&buf, 0, false, Strict).unwrap();
+        assert_eq!(name, "a\\.\\\\\\032.\\255");
+        let mut encoded = Vec::new();
+        encode_name(&name, &mut encoded);
+        assert_eq!(encoded, buf.to_vec());
+    }
+
+    #[test]
+    fn test_encode_names() {
+        let names = vec!["eng.example.com".to_string(), "".to_string(), "a..b.".to_string()];
+        assert_eq!(encode_names(&names), vec![
+            3u8, 101, 110, 103, 7, 101, 120, 97, 109, 112, 108, 101, 3, 99, 111, 109, 0,
+            0,
+            1, 97, 1, 98, 0,
+        ]);
+    }
+
+    #[test]
+    fn test_encode_name_limits() {
+        let mut buf = Vec::new();
+        encode_name(&"a".repeat(70), &mut buf);
+        assert_eq!(buf.len(), 1 + 63 + 1);
+
+        let mut buf = Vec::new();
+        encode_name(&vec!["a".repeat(63); 5].join("."), &mut buf);
+        assert_eq!(buf.len(), 3 * 64 + 1);
+        assert!(decode_name(&buf, 0, false, Strict).is_ok());
+    }
+}
@@ -0,0 +1,154 @@
+//! Changing one option of an encoded message without decoding the rest
+//!
+//! Edits splice the option's bytes in or out of the buffer. The header,
+//! the other options and anything after End are left byte for byte as
+//! they were, so a relay can add or rewrite one option and forward the
+//! rest untouched.
+
+use { Result, Error, MAGIC_COOKIE, OPTIONS_OFFSET };
+use options::DhcpOption;
+use options::raw::next_raw_option;
+
+/// Edits the options of an encoded message, or of an options area alone
+pub struct OptionEditor<'a> {
+    buf: &'a mut Vec<u8>,
+    start: usize,
+}
+
+/// Where one option is in the buffer, including its code and length
+struct Location {
+    code: u8,
+    offset: usize,
+    len: usize,
+}
+
+impl<'a> OptionEditor<'a> {
+    /// For a whole message, whose options start after the magic cookie
+    pub fn new(msg: &'a mut Vec<u8>) -> Result<OptionEditor<'a>> {
+        if msg.len() < OPTIONS_OFFSET || msg[OPTIONS_OFFSET - MAGIC_COOKIE.len()..OPTIONS_OFFSET] != MAGIC_COOKIE {
+            return Err(Error::ParseError("No magic cookie at the start of the options".to_string()));
+        }
+        Ok(OptionEditor { buf: msg, start: OPTIONS_OFFSET })
+    }
+
+    /// For a buffer holding only options
+    pub fn for_options(options: &'a mut Vec<u8>) -> OptionEditor<'a> {
+        OptionEditor { buf: options, start: 0 }
+    }
+
+    /// Every option up to End, and where to insert new options: at End,
+    /// or where the options stop if there isn't one
+    fn locate(&self) -> (Vec<Location>, usize) {
+        let mut locations = Vec::new();
+        let mut offset = self.start;
+        while let Some((raw, _)) = next_raw_option(&self.buf[offset..]) {
+            if raw.code == 255u8 {
+                break;
+            }
+            let len = if raw.code == 0u8 { 1 } else { 2 + raw.data.len() };
+            locations.push(Location { code: raw.code, offset, len });
+            offset += len;
+        }
+        (locations, offset)
+    }
+
+    /// The value of the first option with `code`, as on the wire. Pad
+    /// has none.
+    pub fn get(&self, code: u8) -> Option<&[u8]> {
+        let (locations, _) = self.locate();
+        locations.iter().find(|l| l.code == code && l.code != 0u8).map(|l| &self.buf[l.offset + 2..l.offset + l.len])
+    }
+
+    /// Remove every option with `code`, including the fragments of a
+    /// split one. Returns whether there were any.
+    pub fn remove(&mut self, code: u8) -> bool {
+        let (locations, _) = self.locate();
+        let mut removed = false;
+        for l in locations.iter().rev().filter(|l| l.code == code) {
+            self.buf.drain(l.offset..l.offset + l.len);
+            removed = true;
+        }
+        removed
+    }
+
+    /// Put `option` where the first option with its code is, removing
+    /// any others with that code, or insert it if there isn't one
+    pub fn replace(&mut self, option: &DhcpOption) {
+        let code = option.code();
+        let (locations, _) = self.locate();
+        let mut matching = locations.iter().filter(|l| l.code == code);
+        let first = match matching.next() {
+            Some(first) => first,
+            None => return self.insert(option),
+        };
+        for l in matching.collect::<Vec<_>>().into_iter().rev() {
+            self.buf.drain(l.offset..l.offset + l.len);
+        }
+        let mut bytes = Vec::new();
+        option.encode(&mut bytes);
+        self.buf.splice(first.offset..first.offset + first.len, bytes);
+    }
+
+    /// Add `option` before End. If there are already options with its
+    /// code, it goes right after the last of them instead, so that `parse`
+    /// joins the values as RFC 3396 fragments. Options with a fixed
+    /// length aren't joined, so those end up as two copies.
+    pub fn insert(&mut self, option: &DhcpOption) {
+        let code = option.code();
+        let (locations, end) = self.locate();
+        let at = locations.iter().rev().find(|l| l.code == code && code != 0u8).map_or(end, |l| l.offset + l.len);
+        let mut bytes = Vec::new();
+        option.encode(&mut bytes);
+        self.buf.splice(at..at, bytes);
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::OptionEditor;
+    use options::{self, DhcpOption};
+
+    #[test]
+    fn test_option_editor() {
+        let mut bytes = vec![
+            53u8, 1u8, 1u8,
+            0u8,
+            12u8, 1u8, b'a',
+            61u8, 1u8, 1u8,
+            12u8, 1u8, b'b',
+            255u8,
+            0u8, 9u8,
+        ];
+        {
+            let mut editor = OptionEditor::for_options(&mut bytes);
+            assert_eq!(editor.get(12u8), Some(&b"a"[..]));
+            editor.replace(&DhcpOption::HostName("host".to_string()));
+        }
+        assert_eq!(bytes, vec![
+            53u8, 1u8, 1u8,
+            0u8,
+            12u8, 4u8, b'h', b'o', b's', b't',
+            61u8, 1u8, 1u8,
+            255u8,
+            0u8, 9u8,
+        ]);
+        {
+            let mut editor = OptionEditor::for_options(&mut bytes);
+            assert!(editor.remove(61u8));
+            assert!(!editor.remove(61u8));
+            editor.insert(&DhcpOption::MaxMessageSize(1500));
+        }
+        assert_eq!(&bytes[10..], &[57u8, 2u8, 5u8, 220u8, 255u8, 0u8, 9u8]);
+        assert_eq!(options::parse(&bytes).unwrap()[3], DhcpOption::MaxMessageSize(1500));
+
+        // Next to the existing Host Name, so the two are joined
+        {
+            let mut editor = OptionEditor::for_options(&mut bytes);
+            editor.insert(&DhcpOption::HostName(".lan".to_string()));
+        }
+        assert_eq!(&bytes[4..16], &[12u8, 4u8, b'h', b'o', b's', b't', 12u8, 4u8, b'.', b'l', b'a', b'n']);
+        assert_eq!(options::parse(&bytes).unwrap()[2], DhcpOption::HostName("host.lan".to_string()));
+
+        let mut short = vec![0u8; 100];
+        assert!(OptionEditor::new(&mut short).is_err());
+    }
+}
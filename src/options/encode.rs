@@ -0,0 +1,523 @@
+use {Result, Error};
+use options::{self, DhcpOption, SubnetAllocationSubOption};
+use options::dns::encode_name;
+use options::DhcpOption::*;
+use std::net::{IpAddr};
+
+/// Serialize a list of options, in order, into the wire format that
+/// `options::parse` reads. Nothing is added or reordered, so include
+/// `End` yourself if you want one. IPv6 addresses are written as 0.0.0.0;
+/// use `try_encode` to have them rejected instead.
+pub fn encode(options: &[DhcpOption]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for option in options {
+        option.encode(&mut buf);
+    }
+    buf
+}
+
+/// Like `encode`, but fail if an option holds an IPv6 address, which
+/// DHCPv4 address options have no room for
+pub fn try_encode(options: &[DhcpOption]) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    for option in options {
+        option.try_encode(&mut buf)?;
+    }
+    Ok(buf)
+}
+
+/// Put `options` in ascending code order, as some clients and servers
+/// insist on. The sort is stable, so options repeated with the same code
+/// keep their order; Pad moves to the front and End to the back.
+pub fn sort_by_code(options: &mut [DhcpOption]) {
+    options.sort_by_key(DhcpOption::code);
+}
+
+/// Write `[code, length, data...]`, splitting values longer than 255
+/// bytes across several options with the same code as in RFC 3396
+pub fn put_option(buf: &mut Vec<u8>, code: u8, data: &[u8]) {
+    if data.is_empty() {
+        buf.push(code);
+        buf.push(0u8);
+        return;
+    }
+    for chunk in data.chunks(255) {
+        buf.push(code);
+        buf.push(chunk.len() as u8);
+        buf.extend_from_slice(chunk);
+    }
+}
+
+/// Like `put_option` for a value made of `elements`, e.g. addresses or
+/// suboptions. Fragments end between elements, so receivers that decode
+/// each fragment on its own still get whole ones; only an element longer
+/// than 255 bytes is split.
+fn put_elements<I, T>(buf: &mut Vec<u8>, code: u8, elements: I) -> Result<()>
+    where I: IntoIterator<Item = Result<T>>, T: AsRef<[u8]> {
+    let start = buf.len();
+    let mut data = Vec::new();
+    for element in elements {
+        let element = element?;
+        let element = element.as_ref();
+        if !data.is_empty() && data.len() + element.len() > 255 {
+            put_option(buf, code, &data);
+            data.clear();
+        }
+        data.extend_from_slice(element);
+    }
+    if !data.is_empty() || buf.len() == start {
+        put_option(buf, code, &data);
+    }
+    Ok(())
+}
+
+/// How an address is written into a DHCPv4 address option: `put_ip` or
+/// `put_ip_lossy`
+pub type PutIp = fn(&mut Vec<u8>, &IpAddr) -> Result<()>;
+
+/// Write an IPv4 address. IPv6 addresses can't be carried in DHCPv4
+/// address options and are an error.
+pub fn put_ip(buf: &mut Vec<u8>, addr: &IpAddr) -> Result<()> {
+    match addr {
+        IpAddr::V4(a) => {
+            buf.extend_from_slice(&a.octets());
+            Ok(())
+        },
+        IpAddr::V6(a) => Err(Error::EncodeError(format!("{} is not an IPv4 address", a))),
+    }
+}
+
+/// Like `put_ip`, but IPv6 addresses are written as 0.0.0.0
+pub fn put_ip_lossy(buf: &mut Vec<u8>, addr: &IpAddr) -> Result<()> {
+    match addr {
+        IpAddr::V4(a) => buf.extend_from_slice(&a.octets()),
+        IpAddr::V6(_) => buf.extend_from_slice(&[0u8; 4]),
+    }
+    Ok(())
+}
+
+fn ip(addr: &IpAddr, put_ip: PutIp) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(4);
+    put_ip(&mut data, addr)?;
+    Ok(data)
+}
+
+fn put_ips(buf: &mut Vec<u8>, code: u8, addrs: &[IpAddr], put_ip: PutIp) -> Result<()> {
+    put_elements(buf, code, addrs.iter().map(|a| ip(a, put_ip)))
+}
+
+fn put_ip_pairs(buf: &mut Vec<u8>, code: u8, pairs: &[(IpAddr, IpAddr)], put_ip: PutIp) -> Result<()> {
+    put_elements(buf, code, pairs.iter().map(|(a, b)| {
+        let mut pair = ip(a, put_ip)?;
+        put_ip(&mut pair, b)?;
+        Ok(pair)
+    }))
+}
+
+fn put_u16s<I: IntoIterator<Item = u16>>(buf: &mut Vec<u8>, code: u8, values: I) -> Result<()> {
+    put_elements(buf, code, values.into_iter().map(|v| Ok(v.to_be_bytes())))
+}
+
+/// Options 88, 119 and 141
+fn put_names(buf: &mut Vec<u8>, code: u8, names: &[String]) -> Result<()> {
+    put_elements(buf, code, names.iter().map(|name| Ok(single_name(name))))
+}
+
+/// Each suboption of a container option, encoded on its own
+fn suboptions<'a, T, F>(subs: &'a [T], encode: F) -> impl Iterator<Item = Result<Vec<u8>>> + 'a
+    where F: Fn(&'a T, &mut Vec<u8>) -> Result<()> + 'a {
+    subs.iter().map(move |sub| {
+        let mut data = Vec::new();
+        encode(sub, &mut data)?;
+        Ok(data)
+    })
+}
+
+fn single_name(name: &str) -> Vec<u8> {
+    let mut data = Vec::new();
+    encode_name(name, &mut data);
+    data
+}
+
+fn bool_byte(b: bool) -> Vec<u8> {
+    vec![b as u8]
+}
+
+impl DhcpOption {
+    /// Append the wire form of this option to `buf`. IPv6 addresses are
+    /// written as 0.0.0.0; use `try_encode` to have them rejected instead.
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        // put_ip_lossy never fails
+        let _ = self.encode_with(buf, put_ip_lossy);
+    }
+
+    /// Like `encode`, but fail if the option holds an IPv6 address, which
+    /// DHCPv4 address options have no room for. `buf` is left as it was
+    /// on failure.
+    pub fn try_encode(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let start = buf.len();
+        self.encode_with(buf, put_ip).inspect_err(|_| buf.truncate(start))
+    }
+
+    fn encode_with(&self, buf: &mut Vec<u8>, put_ip: PutIp) -> Result<()> {
+        let ip = |a: &IpAddr| ip(a, put_ip);
+        let (code, data): (u8, Vec<u8>) = match self {
+            &Pad => { buf.push(0u8); return Ok(()); },
+            &End => { buf.push(255u8); return Ok(()); },
+            SubnetMask(a) => (1u8, ip(a)?),
+            &TimeOffset(t) => (2u8, t.to_be_bytes().to_vec()),
+            Router(a) => return put_ips(buf, 3u8, a, put_ip),
+            TimeServer(a) => return put_ips(buf, 4u8, a, put_ip),
+            NameServer(a) => return put_ips(buf, 5u8, a, put_ip),
+            DomainNameServer(a) => return put_ips(buf, 6u8, a, put_ip),
+            LogServer(a) => return put_ips(buf, 7u8, a, put_ip),
+            CookieServer(a) => return put_ips(buf, 8u8, a, put_ip),
+            LprServer(a) => return put_ips(buf, 9u8, a, put_ip),
+            ImpressServer(a) => return put_ips(buf, 10u8, a, put_ip),
+            ResourceLocationServer(a) => return put_ips(buf, 11u8, a, put_ip),
+            HostName(s) => (12u8, s.as_bytes().to_vec()),
+            &BootFileSize(s) => (13u8, s.to_be_bytes().to_vec()),
+            MeritDumpFile(s) => (14u8, s.as_bytes().to_vec()),
+            DomainName(s) => (15u8, s.as_bytes().to_vec()),
+            SwapServer(a) => (16u8, ip(a)?),
+            RootPath(s) => (17u8, s.as_bytes().to_vec()),
+            ExtensionsPath(s) => (18u8, s.as_bytes().to_vec()),
+            &IPForwarding(b) => (19u8, bool_byte(b)),
+
+            &NonLocalSourceRouting(b) => (20u8, bool_byte(b)),
+            PolicyFilter(p) => return put_ip_pairs(buf, 21u8, p, put_ip),
+            &MaxDatagramReassemblySize(s) => (22u8, s.to_be_bytes().to_vec()),
+            &DefaultIpTtl(t) => (23u8, vec![t]),
+            &PathMtuAgingTimeout(t) => (24u8, t.to_be_bytes().to_vec()),
+            PathMtuPlateauTable(sizes) => return put_u16s(buf, 25u8, sizes.iter().cloned()),
+
+            &InterfaceMtu(m) => (26u8, m.to_be_bytes().to_vec()),
+            &AllSubnetsAreLocal(b) => (27u8, bool_byte(b)),
+            BroadcastAddress(a) => (28u8, ip(a)?),
+            &PerformMaskDiscovery(b) => (29u8, bool_byte(b)),
+            &MaskSupplier(b) => (30u8, bool_byte(b)),
+            &PerformRouterDiscovery(b) => (31u8, bool_byte(b)),
+            RouterSolicitationAddress(a) => (32u8, ip(a)?),
+            StaticRoute(p) => return put_ip_pairs(buf, 33u8, p, put_ip),
+
+            &TrailerEncapsulation(b) => (34u8, bool_byte(b)),
+            &ArpCacheTimeout(t) => (35u8, t.to_be_bytes().to_vec()),
+            &EthernetEncapsulation(b) => (36u8, bool_byte(b)),
+
+            &TcpDefaultTtl(t) => (37u8, vec![t]),
+            &TcpKeepaliveInterval(t) => (38u8, t.to_be_bytes().to_vec()),
+            &TcpKeepaliveGarbage(b) => (39u8, bool_byte(b)),
+
+            NisDomain(s) => (40u8, s.as_bytes().to_vec()),
+            NetworkInformationServers(a) => return put_ips(buf, 41u8, a, put_ip),
+            NtpServers(a) => return put_ips(buf, 42u8, a, put_ip),
+            VendorExtensions(d) => (43u8, d.clone()),
+            NetBiosNameServers(a) => return put_ips(buf, 44u8, a, put_ip),
+            NetBiosDatagramDistributionServer(a) => return put_ips(buf, 45u8, a, put_ip),
+            &NetBiosNodeType(t) => (46u8, vec![t as u8]),
+            NetBiosScope(s) => (47u8, s.as_bytes().to_vec()),
+            XFontServer(a) => return put_ips(buf, 48u8, a, put_ip),
+            XDisplayManager(a) => return put_ips(buf, 49u8, a, put_ip),
+
+            RequestedIpAddress(a) => (50u8, ip(a)?),
+            &IpAddressLeaseTime(t) => (51u8, t.to_be_bytes().to_vec()),
+            &OptionOverload(t) => (52u8, vec![t as u8]),
+            &MessageType(t) => (53u8, vec![u8::from(t)]),
+            ServerIdentifier(a) => (54u8, ip(a)?),
+            ParamRequestList(d) => (55u8, d.clone()),
+            Message(s) => (56u8, s.as_bytes().to_vec()),
+            &MaxMessageSize(s) => (57u8, s.to_be_bytes().to_vec()),
+            &RenewalTimeValue(t) => (58u8, t.to_be_bytes().to_vec()),
+            &RebindingTimeValue(t) => (59u8, t.to_be_bytes().to_vec()),
+            ClassIdentifier(d) => (60u8, d.clone()),
+            ClientIdentifier(id) => (61u8, id.to_bytes()),
+            NetwareIpDomain(s) => (62u8, s.as_bytes().to_vec()),
+            NetwareIpInformation(subs) => return put_elements(buf, 63u8, suboptions(subs, |sub, data| sub.encode_with(data, put_ip))),
+            NisPlusDomain(s) => (64u8, s.as_bytes().to_vec()),
+            NisPlusServers(a) => return put_ips(buf, 65u8, a, put_ip),
+            TftpServerName(s) => (66u8, s.as_bytes().to_vec()),
+            BootfileName(s) => (67u8, s.as_bytes().to_vec()),
+            MobileIpHomeAgent(a) => return put_ips(buf, 68u8, a, put_ip),
+            SmtpServers(a) => return put_ips(buf, 69u8, a, put_ip),
+            Pop3Servers(a) => return put_ips(buf, 70u8, a, put_ip),
+            NntpServers(a) => return put_ips(buf, 71u8, a, put_ip),
+            WwwServers(a) => return put_ips(buf, 72u8, a, put_ip),
+            FingerServers(a) => return put_ips(buf, 73u8, a, put_ip),
+            IrcServers(a) => return put_ips(buf, 74u8, a, put_ip),
+            StreetTalkServers(a) => return put_ips(buf, 75u8, a, put_ip),
+            StdaServers(a) => return put_ips(buf, 76u8, a, put_ip),
+
+            UserClass(classes) => (77u8, classes.to_bytes()),
+
+            BcmcsControllerDomains(names) => return put_names(buf, 88u8, names),
+            BcmcsControllerAddresses(a) => return put_ips(buf, 89u8, a, put_ip),
+            CivicLocation(location) => (99u8, location.to_bytes()),
+            PCode(s) => (100u8, s.as_bytes().to_vec()),
+            TCode(s) => (101u8, s.as_bytes().to_vec()),
+            NetinfoParentServerAddress(a) => return put_ips(buf, 112u8, a, put_ip),
+            NetinfoParentServerTag(s) => (113u8, s.as_bytes().to_vec()),
+            &AutoConfigure(value) => (116u8, vec![u8::from(value)]),
+            NameServiceSearch(services) => return put_u16s(buf, 117u8, services.iter().map(|&s| u16::from(s))),
+            GeoConf(location) => (123u8, location.to_bytes()),
+            DomainSearch(names) => return put_names(buf, 119u8, names),
+            // The encoding byte only comes once, in the first fragment
+            SipServers(options::SipServers::Names(names)) => {
+                return put_elements(buf, 120u8, ::std::iter::once(Ok(vec![0u8])).chain(names.iter().map(|name| Ok(single_name(name)))));
+            },
+            SipServers(options::SipServers::Addresses(addrs)) => {
+                return put_elements(buf, 120u8, ::std::iter::once(Ok(vec![1u8])).chain(addrs.iter().map(ip)));
+            },
+            PanaAgents(a) => return put_ips(buf, 136u8, a, put_ip),
+            LostServer(name) => (137u8, single_name(name)),
+            CapwapAcAddresses(a) => return put_ips(buf, 138u8, a, put_ip),
+            SipUaConfigDomains(names) => return put_names(buf, 141u8, names),
+            TftpServerAddresses(a) => return put_ips(buf, 150u8, a, put_ip),
+            StatusCode { status, message } => {
+                let mut data = vec![u8::from(*status)];
+                data.extend_from_slice(message.as_bytes());
+                (151u8, data)
+            },
+            &BaseTime(t) => (152u8, t.to_be_bytes().to_vec()),
+            &StartTimeOfState(t) => (153u8, t.to_be_bytes().to_vec()),
+            &QueryStartTime(t) => (154u8, t.to_be_bytes().to_vec()),
+            &QueryEndTime(t) => (155u8, t.to_be_bytes().to_vec()),
+            &DhcpState(state) => (156u8, vec![u8::from(state)]),
+            &DataSource { remote } => (157u8, vec![remote as u8]),
+            &PortParams { offset, psid_len, psid } => {
+                let psid = psid.to_be_bytes();
+                (159u8, vec![offset, psid_len, psid[0], psid[1]])
+            },
+            PxelinuxMagic(magic) => (208u8, magic.to_vec()),
+            PxelinuxConfigFile(s) => (209u8, s.as_bytes().to_vec()),
+            PxelinuxPathPrefix(s) => (210u8, s.as_bytes().to_vec()),
+            &PxelinuxRebootTime(t) => (211u8, t.to_be_bytes().to_vec()),
+            SixRd { ipv4_mask_len, prefix_len, prefix, border_relays } => {
+                let mut header = vec![*ipv4_mask_len, *prefix_len];
+                header.extend_from_slice(&prefix.octets());
+                return put_elements(buf, 212u8, ::std::iter::once(Ok(header)).chain(border_relays.iter().map(ip)));
+            },
+            SubnetAllocation { flags, suboptions: subs } => {
+                return put_elements(buf, 220u8, ::std::iter::once(Ok(vec![*flags])).chain(suboptions(subs, |sub: &SubnetAllocationSubOption, data| {
+                    sub.encode(data);
+                    Ok(())
+                })));
+            },
+            VirtualSubnetSelection(vss) => (221u8, vss.to_bytes()),
+            ProxyAutoDiscovery(s) => (252u8, s.as_bytes().to_vec()),
+
+            RelayAgentInformation(subs) => return put_elements(buf, 82u8, suboptions(subs, |sub, data| sub.encode_with(data, put_ip))),
+
+            Unknown { code, data } => (*code, data.clone()),
+        };
+        put_option(buf, code, &data);
+        Ok(())
+    }
+
+    /// Bytes this option takes up when encoded, including code and length
+    /// bytes and any extra fragments for values over 255 bytes
+    pub fn wire_size(&self) -> usize {
+        let mut buf = Vec::new();
+        self.encode(&mut buf);
+        buf.len()
+    }
+}
+
+#[cfg(test)] mod tests {
+    use options::{self, parse, DhcpOption, DhcpMessageTypes, RelayAgentInformationSubOption, NetwareIpSubOption};
+    use options::DhcpOption::*;
+    use super::{encode, sort_by_code};
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_encode_round_trip() {
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1));
+        let options = vec![
+            MessageType(DhcpMessageTypes::Offer),
+            SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+            Router(ip_list![ip, ip]),
+            HostName("Test".to_string()),
+            IpAddressLeaseTime(1200),
+            TimeOffset(-3600),
+            PathMtuPlateauTable(vec![576, 1500]),
+            PolicyFilter(vec![(ip, ip)]),
+            StaticRoute(vec![(ip, ip)]),
+            IPForwarding(true),
+            ParamRequestList(vec![1u8, 3u8, 6u8]),
+            NetwareIpDomain("corp".to_string()),
+            NetwareIpInformation(vec![NetwareIpSubOption::NsqBroadcast(true), NetwareIpSubOption::PrimaryDss(ip)]),
+            NisPlusDomain("corp".to_string()),
+            NisPlusServers(ip_list![ip]),
+            TftpServerName("tftp.example.com".to_string()),
+            BootfileName("pxelinux.0".to_string()),
+            MobileIpHomeAgent(ip_list![]),
+            SmtpServers(ip_list![ip]),
+            StdaServers(ip_list![ip, ip]),
+            UserClass(options::UserClasses::Rfc3004(vec![vec![65u8, 66u8]])),
+            BcmcsControllerDomains(vec!["bcmcs.example.com".to_string()]),
+            BcmcsControllerAddresses(ip_list![ip]),
+            TCode("Europe/Zurich".to_string()),
+            AutoConfigure(options::AutoConfigure::DoNotAutoConfigure),
+            NameServiceSearch(vec![options::NameService::DomainNameServer, options::NameService::NetBios]),
+            DomainSearch(vec!["eng.example.com".to_string(), "example.com".to_string()]),
+            SipServers(options::SipServers::Names(vec!["sip.example.com".to_string()])),
+            LostServer("lost.example.com".to_string()),
+            SipServers(options::SipServers::Addresses(ip_list![ip])),
+            CapwapAcAddresses(ip_list![ip]),
+            SipUaConfigDomains(vec!["example.com".to_string()]),
+            TftpServerAddresses(ip_list![ip, ip]),
+            StatusCode { status: options::LeasequeryStatus::QueryTerminated, message: "done".to_string() },
+            BaseTime(1600000000),
+            DhcpState(options::LeaseState::Released),
+            DataSource { remote: false },
+            PortParams { offset: 6, psid_len: 4, psid: 0x5000 },
+            PxelinuxMagic([0xf1, 0x00, 0x74, 0x7e]),
+            PxelinuxRebootTime(60),
+            SixRd {
+                ipv4_mask_len: 0,
+                prefix_len: 32,
+                prefix: Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0),
+                border_relays: ip_list![ip],
+            },
+            ProxyAutoDiscovery("http://wpad.example.com/wpad.dat".to_string()),
+            Unknown { code: 254u8, data: vec![1u8, 2u8] },
+            RelayAgentInformation(vec![
+                RelayAgentInformationSubOption::AgentCircuitID(vec![0u8, 1u8]),
+                RelayAgentInformationSubOption::LinkSelection(ip),
+            ]),
+            Pad,
+            End,
+        ];
+        assert_eq!(parse(&encode(&options)).unwrap(), options);
+        for option in &options {
+            let mut bytes = Vec::new();
+            option.encode(&mut bytes);
+            assert_eq!(bytes[0], option.code());
+            if option.code() != 254u8 {
+                assert_ne!(option.name(), "Unknown");
+            }
+        }
+        assert_eq!(SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))).name(), "Subnet Mask");
+        assert_eq!(Unknown { code: 82u8, data: vec![] }.name(), "Relay Agent Information");
+        assert_eq!(Unknown { code: 230u8, data: vec![] }.name(), "Unknown");
+    }
+
+    #[test]
+    fn test_sort_by_code() {
+        let mut options = vec![
+            MessageType(DhcpMessageTypes::Ack),
+            Router(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]),
+            DomainName("b.example".to_string()),
+            SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+            DomainName("a.example".to_string()),
+            End,
+            Pad,
+        ];
+        sort_by_code(&mut options);
+        assert_eq!(options, vec![
+            Pad,
+            SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+            Router(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]),
+            DomainName("b.example".to_string()),
+            DomainName("a.example".to_string()),
+            MessageType(DhcpMessageTypes::Ack),
+            End,
+        ]);
+    }
+
+    #[test]
+    fn test_encode_user_class_keeps_its_form() {
+        // iPXE sends one class in the RFC 3004 form
+        let ipxe = vec![77u8, 5u8, 4u8, b'i', b'P', b'X', b'E'];
+        assert_eq!(parse(&ipxe).unwrap(), vec![UserClass(options::UserClasses::Rfc3004(vec![b"iPXE".to_vec()]))]);
+        assert_eq!(encode(&parse(&ipxe).unwrap()), ipxe);
+
+        let flat = vec![77u8, 4u8, b'T', b'e', b's', b't'];
+        assert_eq!(parse(&flat).unwrap(), vec![UserClass(options::UserClasses::Flat(b"Test".to_vec()))]);
+        assert_eq!(encode(&parse(&flat).unwrap()), flat);
+    }
+
+    #[test]
+    fn test_encode_long_domain_search_splits_between_names() {
+        let names = vec!["a".repeat(60); 5];
+        let bytes = encode(&[DomainSearch(names.clone())]);
+        assert_eq!(&bytes[..2], &[119u8, 4 * 62]);
+        assert_eq!(&bytes[250..252], &[119u8, 62]);
+        assert_eq!(parse(&bytes).unwrap(), vec![DomainSearch(names)]);
+    }
+
+    #[test]
+    fn test_encode_long_option_is_split() {
+        let option = DhcpOption::VendorExtensions(vec![7u8; 300]);
+        let bytes = encode(&[option]);
+        assert_eq!(bytes.len(), 2 + 255 + 2 + 45);
+        assert_eq!(DhcpOption::VendorExtensions(vec![7u8; 300]).wire_size(), bytes.len());
+        assert_eq!(&bytes[..2], &[43u8, 255u8]);
+        assert_eq!(&bytes[257..259], &[43u8, 45u8]);
+        assert_eq!(parse(&bytes).unwrap(), vec![DhcpOption::VendorExtensions(vec![7u8; 300])]);
+    }
+
+    #[test]
+    fn test_encode_long_lists_split_between_elements() {
+        let ips: options::IpAddrList = (0..70u8).map(|i| IpAddr::V4(Ipv4Addr::new(10, 0, 0, i))).collect();
+        let router = vec![Router(ips.clone())];
+        let bytes = encode(&router);
+        assert_eq!(&bytes[..2], &[3u8, 252u8]);
+        assert_eq!(&bytes[254..256], &[3u8, 28u8]);
+        assert_eq!(parse(&bytes).unwrap(), router);
+
+        let routes = vec![StaticRoute(ips.iter().map(|&ip| (ip, ip)).collect())];
+        let bytes = encode(&routes);
+        assert_eq!(&bytes[..2], &[33u8, 248u8]);
+        assert_eq!(parse(&bytes).unwrap(), routes);
+
+        let sizes = vec![PathMtuPlateauTable((0..200u16).collect())];
+        let bytes = encode(&sizes);
+        assert_eq!(&bytes[..2], &[25u8, 254u8]);
+        assert_eq!(parse(&bytes).unwrap(), sizes);
+    }
+
+    #[test]
+    fn test_encode_long_sip_servers_keeps_everything() {
+        let ips: options::IpAddrList = (0..70u8).map(|i| IpAddr::V4(Ipv4Addr::new(10, 0, 0, i))).collect();
+        let addrs = vec![SipServers(options::SipServers::Addresses(ips))];
+        let bytes = encode(&addrs);
+        // The encoding byte and 63 addresses, then the other 7
+        assert_eq!(&bytes[..3], &[120u8, 253u8, 1u8]);
+        assert_eq!(&bytes[255..257], &[120u8, 28u8]);
+        assert_eq!(parse(&bytes).unwrap(), addrs);
+
+        let names = vec![SipServers(options::SipServers::Names(vec!["sip.example.com".to_string(); 20]))];
+        assert_eq!(parse(&encode(&names)).unwrap(), names);
+    }
+
+    #[test]
+    fn test_encode_long_relay_agent_information() {
+        let subs: Vec<RelayAgentInformationSubOption> = (0..3).map(|_| RelayAgentInformationSubOption::AgentCircuitID(vec![1u8; 100])).collect();
+        let options = vec![RelayAgentInformation(subs)];
+        let bytes = encode(&options);
+        // Two suboptions fit in the first fragment, the third starts the next
+        assert_eq!(&bytes[..2], &[82u8, 204u8]);
+        assert_eq!(parse(&bytes).unwrap(), options);
+    }
+
+    #[test]
+    fn test_try_encode_rejects_ipv6() {
+        let v4 = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1));
+        let v6 = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+        assert_eq!(options::try_encode(&[Router(ip_list![v4])]).unwrap(), encode(&[Router(ip_list![v4])]));
+        for option in [
+            Router(ip_list![v4, v6]),
+            ServerIdentifier(v6),
+            StaticRoute(vec![(v4, v6)]),
+            SipServers(options::SipServers::Addresses(ip_list![v6])),
+            RelayAgentInformation(vec![RelayAgentInformationSubOption::LinkSelection(v6)]),
+            NetwareIpInformation(vec![NetwareIpSubOption::PrimaryDss(v6)]),
+        ] {
+            let mut buf = vec![1u8];
+            assert!(option.try_encode(&mut buf).is_err(), "{:?}", option);
+            assert_eq!(buf, vec![1u8]);
+        }
+        // The lossy encoding writes 0.0.0.0 instead
+        assert_eq!(encode(&[ServerIdentifier(v6)]), vec![54u8, 4, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Option 123, coordinate-based location (RFC 6225)
+//!
+//! The fields are kept as they are on the wire, as fixed-point numbers;
+//! `latitude_degrees`, `longitude_degrees` and `altitude_value` convert
+//! them. The resolution fields are uncertainties in version 1 and
+//! resolutions (RFC 3825) in version 0.
+
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+use { Result, Error };
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum AltitudeType {
+    NoAltitude,
+    Meters,
+    Floors,
+    Other(u8),
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum Datum {
+    Wgs84,
+    /// NAD83 with NAVD88 heights
+    Nad83Navd88,
+    /// NAD83 with heights above mean lower low water
+    Nad83Mllw,
+    Other(u8),
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct GeoLocation {
+    /// 6 bits
+    pub latitude_resolution: u8,
+    /// 34 bit two's complement degrees, 25 of them fraction
+    pub latitude: i64,
+    /// 6 bits
+    pub longitude_resolution: u8,
+    /// 34 bit two's complement degrees, 25 of them fraction
+    pub longitude: i64,
+    pub altitude_type: AltitudeType,
+    /// 6 bits
+    pub altitude_resolution: u8,
+    /// 30 bit two's complement, 8 of them fraction, in `altitude_type` units
+    pub altitude: i32,
+    /// 2 bits
+    pub version: u8,
+    pub datum: Datum,
+}
+
+/// Sign-extend the low `bits` bits of `value`
+fn signed(value: u64, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    ((value << shift) as i64) >> shift
+}
+
+/// The 40-bit field starting at `bytes[start]`
+fn be_u40(bytes: &[u8], start: usize) -> u64 {
+    bytes.iter().skip(start).take(5).fold(0u64, |acc, &b| acc << 8 | b as u64)
+}
+
+fn put_u40(buf: &mut Vec<u8>, value: u64) {
+    buf.extend_from_slice(&value.to_be_bytes()[3..]);
+}
+
+impl GeoLocation {
+    pub fn from_bytes(bytes: &[u8]) -> Result<GeoLocation> {
+        let last = match (bytes.len(), bytes.last()) {
+            (16, Some(&last)) => last,
+            _ => return Err(Error::ParseError(format!("GeoConf option is {} bytes, not 16", bytes.len()))),
+        };
+        let latitude = be_u40(bytes, 0);
+        let longitude = be_u40(bytes, 5);
+        let altitude = be_u40(bytes, 10);
+        let altitude_type = match (altitude >> 36) as u8 {
+            0u8 => AltitudeType::NoAltitude,
+            1u8 => AltitudeType::Meters,
+            2u8 => AltitudeType::Floors,
+            n => AltitudeType::Other(n),
+        };
+        let datum = match last & 0x07 {
+            1u8 => Datum::Wgs84,
+            2u8 => Datum::Nad83Navd88,
+            3u8 => Datum::Nad83Mllw,
+            n => Datum::Other(n),
+        };
+        Ok(GeoLocation {
+            latitude_resolution: (latitude >> 34) as u8,
+            latitude: signed(latitude, 34),
+            longitude_resolution: (longitude >> 34) as u8,
+            longitude: signed(longitude, 34),
+            altitude_type,
+            altitude_resolution: (altitude >> 30) as u8 & 0x3f,
+            altitude: signed(altitude, 30) as i32,
+            version: last >> 6,
+            datum,
+        })
+    }
+
+    /// Fields are cut to their widths on the wire
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let resolution = |r: u8| (r & 0x3f) as u64;
+        let altitude_type = match self.altitude_type {
+            AltitudeType::NoAltitude => 0u8,
+            AltitudeType::Meters => 1u8,
+            AltitudeType::Floors => 2u8,
+            AltitudeType::Other(n) => n,
+        };
+        let datum = match self.datum {
+            Datum::Wgs84 => 1u8,
+            Datum::Nad83Navd88 => 2u8,
+            Datum::Nad83Mllw => 3u8,
+            Datum::Other(n) => n,
+        };
+        let mut data = Vec::with_capacity(16);
+        put_u40(&mut data, resolution(self.latitude_resolution) << 34 | self.latitude as u64 & 0x3_ffff_ffff);
+        put_u40(&mut data, resolution(self.longitude_resolution) << 34 | self.longitude as u64 & 0x3_ffff_ffff);
+        put_u40(&mut data, ((altitude_type & 0x0f) as u64) << 36
+            | resolution(self.altitude_resolution) << 30
+            | self.altitude as u64 & 0x3fff_ffff);
+        data.push(self.version << 6 | datum & 0x07);
+        data
+    }
+
+    pub fn latitude_degrees(&self) -> f64 {
+        self.latitude as f64 / (1u64 << 25) as f64
+    }
+
+    pub fn longitude_degrees(&self) -> f64 {
+        self.longitude as f64 / (1u64 << 25) as f64
+    }
+
+    /// In meters or floors, as `altitude_type` says
+    pub fn altitude_value(&self) -> f64 {
+        self.altitude as f64 / 256.0
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{GeoLocation, AltitudeType, Datum};
+
+    #[test]
+    fn test_geo_location() {
+        // 33.857 S, 151.215 E, 13.2 m up
+        let data = [
+            0x0bu8, 0xbcu8, 0x49u8, 0x37u8, 0x4cu8,
+            0x09u8, 0x2eu8, 0x6eu8, 0x14u8, 0x7bu8,
+            0x1au8, 0x80u8, 0x00u8, 0x0du8, 0x33u8,
+            0x41u8,
+        ];
+        let location = GeoLocation::from_bytes(&data).unwrap();
+        assert_eq!(location.latitude_resolution, 2);
+        assert_eq!(location.longitude_resolution, 2);
+        assert_eq!(location.altitude_type, AltitudeType::Meters);
+        assert_eq!(location.altitude_resolution, 42);
+        assert_eq!(location.version, 1);
+        assert_eq!(location.datum, Datum::Wgs84);
+        assert!((location.latitude_degrees() - -33.857).abs() < 0.001);
+        assert!((location.longitude_degrees() - 151.215).abs() < 0.001);
+        assert!((location.altitude_value() - 13.2).abs() < 0.01);
+        assert_eq!(location.to_bytes(), data.to_vec());
+    }
+
+    #[test]
+    fn test_invalid_geo_location() {
+        assert!(GeoLocation::from_bytes(&[0u8; 15]).is_err());
+        assert!(GeoLocation::from_bytes(&[0u8; 17]).is_err());
+    }
+}
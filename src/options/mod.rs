@@ -1,26 +1,72 @@
+pub mod agent_id;
+pub mod civic;
+mod code;
+pub mod codes;
+pub mod dns;
+pub mod geoconf;
+mod edit;
+mod encode;
+pub mod netware;
+mod normalize;
 mod parse;
 pub mod option82;
+pub mod raw;
+pub mod subnet_alloc;
 
-use std::net::{IpAddr};
-pub use self::parse::parse;
-pub use self::option82::RelayAgentInformationSubOption;
+use std::net::{IpAddr, Ipv6Addr};
+use htype::Htype;
+use hwaddr::HardwareAddr;
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+pub use self::code::{OptionCode, option_name};
+pub use self::edit::OptionEditor;
+pub use self::encode::{encode, try_encode, put_option, sort_by_code};
+pub use self::parse::{parse, parse_into, parse_single_option, parse_with_quirks, parse_with_config, parse_after_cookie, parse_with_diagnostics, parse_spanned, find_magic_cookie, ParseConfig, PadHandling, OptionsIter, ParseWarning, WarningKind, Spanned, Limits};
+pub(crate) use self::parse::fixed_length;
+pub use self::option82::{RelayAgentInformationSubOption, RelayAgentInformationBuilder, insert_relay_agent_info, strip_relay_agent_info, VirtualSubnetSelection, DeviceClass, AgentFlags};
+pub use self::civic::{CivicLocation, CivicAddressElement, CivicWhat, CaType};
+pub use self::geoconf::{GeoLocation, AltitudeType, Datum};
+pub use self::netware::NetwareIpSubOption;
+pub use self::normalize::{normalize, conflicts, Conflict};
+pub use self::subnet_alloc::{SubnetAllocationSubOption, SubnetPrefix};
+pub use self::raw::{RawOption, RawOptionsIter, OptionSize, option_sizes, end_offset, trailing_data};
+#[cfg(feature = "heapless")]
+pub use self::raw::parse_into_heapless;
+#[cfg(feature = "bumpalo")]
+pub use self::raw::split_in;
+
+/// The addresses of an option listing servers or relays. Most carry one
+/// or two, so with the `smallvec` feature up to four are kept inline
+/// rather than in an allocation of their own.
+#[cfg(not(feature = "smallvec"))]
+pub type IpAddrList = Vec<IpAddr>;
+#[cfg(feature = "smallvec")]
+pub type IpAddrList = ::smallvec::SmallVec<[IpAddr; 4]>;
 
 #[allow(dead_code)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[non_exhaustive]
 pub enum DhcpOption {
     Pad,
     End,
     SubnetMask(IpAddr),
     TimeOffset(i32),
-    Router(Vec<IpAddr>),
-    TimeServer(Vec<IpAddr>),
-    NameServer(Vec<IpAddr>),
-    DomainNameServer(Vec<IpAddr>),
-    LogServer(Vec<IpAddr>),
-    CookieServer(Vec<IpAddr>),
-    LprServer(Vec<IpAddr>),
-    ImpressServer(Vec<IpAddr>),
-    ResourceLocationServer(Vec<IpAddr>),
+    Router(IpAddrList),
+    TimeServer(IpAddrList),
+    NameServer(IpAddrList),
+    DomainNameServer(IpAddrList),
+    LogServer(IpAddrList),
+    CookieServer(IpAddrList),
+    LprServer(IpAddrList),
+    ImpressServer(IpAddrList),
+    ResourceLocationServer(IpAddrList),
     HostName(String),
     BootFileSize(u16),
     MeritDumpFile(String),
@@ -55,15 +101,15 @@ pub enum DhcpOption {
     TcpKeepaliveGarbage(bool),
 
     NisDomain(String),
-    NetworkInformationServers(Vec<IpAddr>),
-    NtpServers(Vec<IpAddr>),
+    NetworkInformationServers(IpAddrList),
+    NtpServers(IpAddrList),
     VendorExtensions(Vec<u8>),
-    NetBiosNameServers(Vec<IpAddr>),
-    NetBiosDatagramDistributionServer(Vec<IpAddr>),
+    NetBiosNameServers(IpAddrList),
+    NetBiosDatagramDistributionServer(IpAddrList),
     NetBiosNodeType(NodeType),
     NetBiosScope(String),
-    XFontServer(Vec<IpAddr>),
-    XDisplayManager(Vec<IpAddr>),
+    XFontServer(IpAddrList),
+    XDisplayManager(IpAddrList),
 
     // DHCP-specific options
     RequestedIpAddress(IpAddr),
@@ -76,15 +122,513 @@ pub enum DhcpOption {
     MaxMessageSize(u16),
     RenewalTimeValue(u32),
     RebindingTimeValue(u32),
-    ClassIdentifier,
-    ClientIdentifier,
+    ClassIdentifier(Vec<u8>),
+    ClientIdentifier(ClientId),
+    // RFC 2242
+    NetwareIpDomain(String),
+    NetwareIpInformation(Vec<NetwareIpSubOption>),
+    NisPlusDomain(String),
+    NisPlusServers(IpAddrList),
+    TftpServerName(String),
+    BootfileName(String),
+    MobileIpHomeAgent(IpAddrList),
+    SmtpServers(IpAddrList),
+    Pop3Servers(IpAddrList),
+    NntpServers(IpAddrList),
+    WwwServers(IpAddrList),
+    FingerServers(IpAddrList),
+    IrcServers(IpAddrList),
+    StreetTalkServers(IpAddrList),
+    StdaServers(IpAddrList),
+
+    // RFC 3004
+    UserClass(UserClasses),
+
+    // RFC 4280
+    BcmcsControllerDomains(Vec<String>),
+    BcmcsControllerAddresses(IpAddrList),
+
+    // RFC 4776
+    CivicLocation(CivicLocation),
+
+    // RFC 4833: a POSIX TZ string and a tz database name
+    PCode(String),
+    TCode(String),
+
+    // Apple NetInfo, never documented in an RFC
+    NetinfoParentServerAddress(IpAddrList),
+    NetinfoParentServerTag(String),
+
+    // RFC 2563
+    AutoConfigure(AutoConfigure),
+
+    // RFC 2937: name services to try, in order
+    NameServiceSearch(Vec<NameService>),
+
+    // RFC 6225
+    GeoConf(GeoLocation),
+
+    // RFC 3397
+    DomainSearch(Vec<String>),
+    // RFC 3361
+    SipServers(SipServers),
+    // RFC 5192
+    PanaAgents(IpAddrList),
+    // RFC 5223
+    LostServer(String),
+    // RFC 5417
+    CapwapAcAddresses(IpAddrList),
+    // RFC 6011
+    SipUaConfigDomains(Vec<String>),
+    // Cisco, e.g. for IP phones to fetch their configuration from
+    TftpServerAddresses(IpAddrList),
+
+    // RFC 6926, Bulk Leasequery. Times are seconds since the epoch,
+    // except StartTimeOfState which is seconds before BaseTime.
+    StatusCode { status: LeasequeryStatus, message: String },
+    BaseTime(u32),
+    StartTimeOfState(u32),
+    QueryStartTime(u32),
+    QueryEndTime(u32),
+    DhcpState(LeaseState),
+    /// Whether the lease data came from another server
+    DataSource { remote: bool },
+
+    // RFC 7618: the port set is the ports whose bits after the first
+    // `offset` start with the `psid_len` high bits of `psid`
+    PortParams { offset: u8, psid_len: u8, psid: u16 },
+    // RFC 5071
+    /// F1:00:74:7E from a server that knows the other PXELINUX options
+    PxelinuxMagic([u8; 4]),
+    PxelinuxConfigFile(String),
+    PxelinuxPathPrefix(String),
+    /// Seconds to wait before rebooting when the boot fails
+    PxelinuxRebootTime(u32),
+    // RFC 5969: the IPv6 prefix and border relays for 6rd, with how
+    // many high bits of the IPv4 address are common to the domain
+    SixRd { ipv4_mask_len: u8, prefix_len: u8, prefix: Ipv6Addr, border_relays: IpAddrList },
+    // RFC 6656
+    SubnetAllocation { flags: u8, suboptions: Vec<SubnetAllocationSubOption> },
+    // RFC 6607, as a top-level option rather than an option 82 suboption
+    VirtualSubnetSelection(VirtualSubnetSelection),
+    // Web Proxy Auto-Discovery (draft-ietf-wrec-wpad): a PAC file URL
+    ProxyAutoDiscovery(String),
 
     // Option 82
     RelayAgentInformation(Vec<RelayAgentInformationSubOption>),
+
+    /// An option we don't have a parser for, with its raw value
+    Unknown { code: u8, data: Vec<u8> },
+}
+
+impl DhcpOption {
+    /// The option code this is written with
+    pub fn code(&self) -> u8 {
+        use self::DhcpOption::*;
+        match *self {
+            Pad => 0u8,
+            End => 255u8,
+            SubnetMask(..) => 1u8,
+            TimeOffset(..) => 2u8,
+            Router(..) => 3u8,
+            TimeServer(..) => 4u8,
+            NameServer(..) => 5u8,
+            DomainNameServer(..) => 6u8,
+            LogServer(..) => 7u8,
+            CookieServer(..) => 8u8,
+            LprServer(..) => 9u8,
+            ImpressServer(..) => 10u8,
+            ResourceLocationServer(..) => 11u8,
+            HostName(..) => 12u8,
+            BootFileSize(..) => 13u8,
+            MeritDumpFile(..) => 14u8,
+            DomainName(..) => 15u8,
+            SwapServer(..) => 16u8,
+            RootPath(..) => 17u8,
+            ExtensionsPath(..) => 18u8,
+            IPForwarding(..) => 19u8,
+            NonLocalSourceRouting(..) => 20u8,
+            PolicyFilter(..) => 21u8,
+            MaxDatagramReassemblySize(..) => 22u8,
+            DefaultIpTtl(..) => 23u8,
+            PathMtuAgingTimeout(..) => 24u8,
+            PathMtuPlateauTable(..) => 25u8,
+            InterfaceMtu(..) => 26u8,
+            AllSubnetsAreLocal(..) => 27u8,
+            BroadcastAddress(..) => 28u8,
+            PerformMaskDiscovery(..) => 29u8,
+            MaskSupplier(..) => 30u8,
+            PerformRouterDiscovery(..) => 31u8,
+            RouterSolicitationAddress(..) => 32u8,
+            StaticRoute(..) => 33u8,
+            TrailerEncapsulation(..) => 34u8,
+            ArpCacheTimeout(..) => 35u8,
+            EthernetEncapsulation(..) => 36u8,
+            TcpDefaultTtl(..) => 37u8,
+            TcpKeepaliveInterval(..) => 38u8,
+            TcpKeepaliveGarbage(..) => 39u8,
+            NisDomain(..) => 40u8,
+            NetworkInformationServers(..) => 41u8,
+            NtpServers(..) => 42u8,
+            VendorExtensions(..) => 43u8,
+            NetBiosNameServers(..) => 44u8,
+            NetBiosDatagramDistributionServer(..) => 45u8,
+            NetBiosNodeType(..) => 46u8,
+            NetBiosScope(..) => 47u8,
+            XFontServer(..) => 48u8,
+            XDisplayManager(..) => 49u8,
+            RequestedIpAddress(..) => 50u8,
+            IpAddressLeaseTime(..) => 51u8,
+            OptionOverload(..) => 52u8,
+            MessageType(..) => 53u8,
+            ServerIdentifier(..) => 54u8,
+            ParamRequestList(..) => 55u8,
+            Message(..) => 56u8,
+            MaxMessageSize(..) => 57u8,
+            RenewalTimeValue(..) => 58u8,
+            RebindingTimeValue(..) => 59u8,
+            ClassIdentifier(..) => 60u8,
+            ClientIdentifier(..) => 61u8,
+            NetwareIpDomain(..) => 62u8,
+            NetwareIpInformation(..) => 63u8,
+            NisPlusDomain(..) => 64u8,
+            NisPlusServers(..) => 65u8,
+            TftpServerName(..) => 66u8,
+            BootfileName(..) => 67u8,
+            MobileIpHomeAgent(..) => 68u8,
+            SmtpServers(..) => 69u8,
+            Pop3Servers(..) => 70u8,
+            NntpServers(..) => 71u8,
+            WwwServers(..) => 72u8,
+            FingerServers(..) => 73u8,
+            IrcServers(..) => 74u8,
+            StreetTalkServers(..) => 75u8,
+            StdaServers(..) => 76u8,
+            UserClass(..) => 77u8,
+            BcmcsControllerDomains(..) => 88u8,
+            BcmcsControllerAddresses(..) => 89u8,
+            CivicLocation(..) => 99u8,
+            PCode(..) => 100u8,
+            TCode(..) => 101u8,
+            NetinfoParentServerAddress(..) => 112u8,
+            NetinfoParentServerTag(..) => 113u8,
+            AutoConfigure(..) => 116u8,
+            NameServiceSearch(..) => 117u8,
+            GeoConf(..) => 123u8,
+            DomainSearch(..) => 119u8,
+            SipServers(..) => 120u8,
+            PanaAgents(..) => 136u8,
+            LostServer(..) => 137u8,
+            CapwapAcAddresses(..) => 138u8,
+            SipUaConfigDomains(..) => 141u8,
+            TftpServerAddresses(..) => 150u8,
+            StatusCode { .. } => 151u8,
+            BaseTime(..) => 152u8,
+            StartTimeOfState(..) => 153u8,
+            QueryStartTime(..) => 154u8,
+            QueryEndTime(..) => 155u8,
+            DhcpState(..) => 156u8,
+            DataSource { .. } => 157u8,
+            PortParams { .. } => 159u8,
+            PxelinuxMagic(..) => 208u8,
+            PxelinuxConfigFile(..) => 209u8,
+            PxelinuxPathPrefix(..) => 210u8,
+            PxelinuxRebootTime(..) => 211u8,
+            SixRd { .. } => 212u8,
+            SubnetAllocation { .. } => 220u8,
+            VirtualSubnetSelection(..) => 221u8,
+            ProxyAutoDiscovery(..) => 252u8,
+            RelayAgentInformation(..) => 82u8,
+            Unknown { code, .. } => code,
+        }
+    }
+
+    /// The option's name in the IANA registry, e.g. "Subnet Mask"; see
+    /// `option_name`
+    pub fn name(&self) -> &'static str {
+        option_name(self.code()).unwrap_or("Unknown")
+    }
+}
+
+/// Option 61. The type byte is a hardware type for a hardware address
+/// (usually the client's chaddr), 255 for an RFC 4361 IAID and DUID, or
+/// anything else, e.g. 0 for a name.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum ClientId {
+    Hardware(HardwareAddr),
+    Duid { iaid: u32, duid: Vec<u8> },
+    Other { id_type: u8, data: Vec<u8> },
+}
+
+impl ClientId {
+    pub fn from_bytes(bytes: &[u8]) -> Option<ClientId> {
+        let (&id_type, data) = bytes.split_first()?;
+        match Htype::from_byte(id_type) {
+            Htype::Other(_) => {},
+            htype => return Some(ClientId::Hardware(HardwareAddr::new(htype, data.to_vec()))),
+        }
+        if id_type == 255u8 && data.len() >= 4 {
+            let iaid = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+            return Some(ClientId::Duid { iaid, duid: data[4..].to_vec() });
+        }
+        Some(ClientId::Other { id_type, data: data.to_vec() })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match *self {
+            ClientId::Hardware(ref addr) => {
+                bytes.push(addr.htype.to_byte());
+                bytes.extend_from_slice(&addr.addr);
+            },
+            ClientId::Duid { iaid, ref duid } => {
+                bytes.push(255u8);
+                bytes.extend_from_slice(&iaid.to_be_bytes());
+                bytes.extend_from_slice(duid);
+            },
+            ClientId::Other { id_type, ref data } => {
+                bytes.push(id_type);
+                bytes.extend_from_slice(data);
+            },
+        }
+        bytes
+    }
+}
+
+/// Option 77. RFC 3004 makes it a list of `[length, class...]` entries,
+/// but plenty of clients just send a single bare class string. Which
+/// form a value arrived in is kept, so it encodes back to the same bytes.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum UserClasses {
+    Rfc3004(Vec<Vec<u8>>),
+    Flat(Vec<u8>),
+}
+
+impl UserClasses {
+    /// The RFC 3004 form if `bytes` splits cleanly into non-empty
+    /// entries, otherwise the whole of `bytes` as one flat class
+    pub fn from_bytes(bytes: &[u8]) -> UserClasses {
+        let mut classes = Vec::new();
+        let mut rest = bytes;
+        while let Some((&len, after)) = rest.split_first() {
+            match after.get(..len as usize) {
+                Some(class) if len > 0 => classes.push(class.to_vec()),
+                _ => return UserClasses::Flat(bytes.to_vec()),
+            }
+            rest = after.get(len as usize..).unwrap_or(&[]);
+        }
+        UserClasses::Rfc3004(classes)
+    }
+
+    /// The value as sent, in the form it arrived in. RFC 3004 classes
+    /// longer than 255 bytes are truncated.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            UserClasses::Rfc3004(classes) => {
+                let mut bytes = Vec::new();
+                for class in classes {
+                    let class = class.get(..255).unwrap_or(class);
+                    bytes.push(class.len() as u8);
+                    bytes.extend_from_slice(class);
+                }
+                bytes
+            },
+            UserClasses::Flat(class) => class.clone(),
+        }
+    }
+
+    /// Each class, whichever form they came in
+    pub fn classes(&self) -> Vec<&[u8]> {
+        match self {
+            UserClasses::Rfc3004(classes) => classes.iter().map(|c| &c[..]).collect(),
+            UserClasses::Flat(class) => vec![&class[..]],
+        }
+    }
+}
+
+/// Option 120 carries either names or addresses, never both
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum SipServers {
+    Names(Vec<String>),
+    Addresses(IpAddrList),
+}
+
+/// Option 116, whether the client may pick a link-local address when
+/// the server has none for it
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum AutoConfigure {
+    DoNotAutoConfigure,
+    AutoConfigure,
+    Other(u8),
+}
+
+impl From<u8> for AutoConfigure {
+    fn from(value: u8) -> AutoConfigure {
+        match value {
+            0u8 => AutoConfigure::DoNotAutoConfigure,
+            1u8 => AutoConfigure::AutoConfigure,
+            n => AutoConfigure::Other(n),
+        }
+    }
+}
+
+impl From<AutoConfigure> for u8 {
+    fn from(value: AutoConfigure) -> u8 {
+        match value {
+            AutoConfigure::DoNotAutoConfigure => 0u8,
+            AutoConfigure::AutoConfigure => 1u8,
+            AutoConfigure::Other(n) => n,
+        }
+    }
+}
+
+/// Option 151
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum LeasequeryStatus {
+    Success,
+    UnspecFail,
+    QueryTerminated,
+    MalformedQuery,
+    NotAllowed,
+    Other(u8),
+}
+
+impl From<u8> for LeasequeryStatus {
+    fn from(status: u8) -> LeasequeryStatus {
+        match status {
+            0u8 => LeasequeryStatus::Success,
+            1u8 => LeasequeryStatus::UnspecFail,
+            2u8 => LeasequeryStatus::QueryTerminated,
+            3u8 => LeasequeryStatus::MalformedQuery,
+            4u8 => LeasequeryStatus::NotAllowed,
+            n => LeasequeryStatus::Other(n),
+        }
+    }
+}
+
+impl From<LeasequeryStatus> for u8 {
+    fn from(status: LeasequeryStatus) -> u8 {
+        match status {
+            LeasequeryStatus::Success => 0u8,
+            LeasequeryStatus::UnspecFail => 1u8,
+            LeasequeryStatus::QueryTerminated => 2u8,
+            LeasequeryStatus::MalformedQuery => 3u8,
+            LeasequeryStatus::NotAllowed => 4u8,
+            LeasequeryStatus::Other(n) => n,
+        }
+    }
+}
+
+/// Option 156, the state of an address on the server
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum LeaseState {
+    Available,
+    Active,
+    Expired,
+    Released,
+    Abandoned,
+    Reset,
+    Remote,
+    Transitioning,
+    Other(u8),
+}
+
+impl From<u8> for LeaseState {
+    fn from(state: u8) -> LeaseState {
+        match state {
+            1u8 => LeaseState::Available,
+            2u8 => LeaseState::Active,
+            3u8 => LeaseState::Expired,
+            4u8 => LeaseState::Released,
+            5u8 => LeaseState::Abandoned,
+            6u8 => LeaseState::Reset,
+            7u8 => LeaseState::Remote,
+            8u8 => LeaseState::Transitioning,
+            n => LeaseState::Other(n),
+        }
+    }
+}
+
+impl From<LeaseState> for u8 {
+    fn from(state: LeaseState) -> u8 {
+        match state {
+            LeaseState::Available => 1u8,
+            LeaseState::Active => 2u8,
+            LeaseState::Expired => 3u8,
+            LeaseState::Released => 4u8,
+            LeaseState::Abandoned => 5u8,
+            LeaseState::Reset => 6u8,
+            LeaseState::Remote => 7u8,
+            LeaseState::Transitioning => 8u8,
+            LeaseState::Other(n) => n,
+        }
+    }
+}
+
+/// A name service in option 117, named by the option that configures it
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum NameService {
+    /// Option 6
+    DomainNameServer,
+    /// Option 41
+    Nis,
+    /// Option 44
+    NetBios,
+    /// Option 65
+    NisPlus,
+    Other(u16),
+}
+
+impl From<u16> for NameService {
+    fn from(code: u16) -> NameService {
+        match code {
+            6u16 => NameService::DomainNameServer,
+            41u16 => NameService::Nis,
+            44u16 => NameService::NetBios,
+            65u16 => NameService::NisPlus,
+            n => NameService::Other(n),
+        }
+    }
+}
+
+impl From<NameService> for u16 {
+    fn from(service: NameService) -> u16 {
+        match service {
+            NameService::DomainNameServer => 6u16,
+            NameService::Nis => 41u16,
+            NameService::NetBios => 44u16,
+            NameService::NisPlus => 65u16,
+            NameService::Other(n) => n,
+        }
+    }
 }
 
 enum_from_primitive! {
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub enum NodeType {
     B = 1,
     P = 2,
@@ -94,7 +638,10 @@ pub enum NodeType {
 }
 
 enum_from_primitive! {
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub enum OptionOverloadType {
     File = 1,
     Sname = 2,
@@ -102,17 +649,53 @@ pub enum OptionOverloadType {
 }
 }
 
-enum_from_primitive! {
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[non_exhaustive]
 pub enum DhcpMessageTypes {
-    Discover = 1,
-    Offer = 2,
-    Request = 3,
-    Decline = 4,
-    Ack = 5,
-    Nak = 6,
-    Release = 7,
+    Discover,
+    Offer,
+    Request,
+    Decline,
+    Ack,
+    Nak,
+    Release,
+    /// A message type this crate doesn't know yet
+    Other(u8),
 }
+
+impl From<u8> for DhcpMessageTypes {
+    fn from(value: u8) -> DhcpMessageTypes {
+        use self::DhcpMessageTypes::*;
+        match value {
+            1u8 => Discover,
+            2u8 => Offer,
+            3u8 => Request,
+            4u8 => Decline,
+            5u8 => Ack,
+            6u8 => Nak,
+            7u8 => Release,
+            n => Other(n),
+        }
+    }
+}
+
+impl From<DhcpMessageTypes> for u8 {
+    fn from(value: DhcpMessageTypes) -> u8 {
+        use self::DhcpMessageTypes::*;
+        match value {
+            Discover => 1u8,
+            Offer => 2u8,
+            Request => 3u8,
+            Decline => 4u8,
+            Ack => 5u8,
+            Nak => 6u8,
+            Release => 7u8,
+            Other(n) => n,
+        }
+    }
 }
 
 //impl DhcpOption {
@@ -0,0 +1,176 @@
+//! Option 63, NetWare/IP Information (RFC 2242)
+
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+use { Result, Error };
+use std::net::{IpAddr, Ipv4Addr};
+use options::IpAddrList;
+use options::encode::{put_ip, put_ip_lossy, PutIp};
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum NetwareIpSubOption {
+    /// Suboptions 1 to 4 say where the NetWare/IP options are, if anywhere
+    DoesNotExist,
+    ExistsInOptionsArea,
+    ExistsInSnameFile,
+    ExistsButTooBig,
+    NsqBroadcast(bool),
+    /// Up to 5 preferred NetWare Domain SAP/RIP servers
+    PreferredDss(IpAddrList),
+    /// Up to 5 nearest NetWare/IP servers
+    NearestNwipServer(IpAddrList),
+    AutoRetries(u8),
+    /// Seconds between retries
+    AutoRetrySecs(u8),
+    /// Whether the client should support NetWare/IP 1.1
+    Nwip11(bool),
+    PrimaryDss(IpAddr),
+    /// A suboption we don't know, or one with the wrong length
+    Unknown { code: u8, data: Vec<u8> },
+}
+use self::NetwareIpSubOption::*;
+
+fn addr(a: u8, b: u8, c: u8, d: u8) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(a, b, c, d))
+}
+
+fn addrs(data: &[u8]) -> IpAddrList {
+    data.chunks_exact(4).filter_map(|a| match a {
+        &[a, b, c, d] => Some(addr(a, b, c, d)),
+        _ => None,
+    }).collect()
+}
+
+impl NetwareIpSubOption {
+    fn from_code_and_data(code: u8, data: &[u8]) -> NetwareIpSubOption {
+        match (code, data) {
+            (1u8, &[]) => DoesNotExist,
+            (2u8, &[]) => ExistsInOptionsArea,
+            (3u8, &[]) => ExistsInSnameFile,
+            (4u8, &[]) => ExistsButTooBig,
+            (5u8, &[b]) => NsqBroadcast(b != 0),
+            (6u8, _) if data.len().is_multiple_of(4) => PreferredDss(addrs(data)),
+            (7u8, _) if data.len().is_multiple_of(4) => NearestNwipServer(addrs(data)),
+            (8u8, &[n]) => AutoRetries(n),
+            (9u8, &[n]) => AutoRetrySecs(n),
+            (10u8, &[b]) => Nwip11(b != 0),
+            (11u8, &[a, b, c, d]) => PrimaryDss(addr(a, b, c, d)),
+            _ => Unknown { code, data: data.to_vec() },
+        }
+    }
+
+    fn code_and_data(&self, put_ip: PutIp) -> Result<(u8, Vec<u8>)> {
+        let ips = |addrs: &[IpAddr]| {
+            let mut data = Vec::with_capacity(addrs.len() * 4);
+            for addr in addrs {
+                put_ip(&mut data, addr)?;
+            }
+            Ok(data)
+        };
+        Ok(match *self {
+            DoesNotExist => (1u8, vec![]),
+            ExistsInOptionsArea => (2u8, vec![]),
+            ExistsInSnameFile => (3u8, vec![]),
+            ExistsButTooBig => (4u8, vec![]),
+            NsqBroadcast(b) => (5u8, vec![b as u8]),
+            PreferredDss(ref a) => (6u8, ips(a)?),
+            NearestNwipServer(ref a) => (7u8, ips(a)?),
+            AutoRetries(n) => (8u8, vec![n]),
+            AutoRetrySecs(n) => (9u8, vec![n]),
+            Nwip11(b) => (10u8, vec![b as u8]),
+            PrimaryDss(ref a) => (11u8, ips(&[*a])?),
+            Unknown { code, ref data } => (code, data.clone()),
+        })
+    }
+
+    /// Append the wire form of this suboption to `buf`. Values longer
+    /// than 255 bytes are truncated. IPv6 addresses are written as
+    /// 0.0.0.0; use `try_encode` to have them rejected instead.
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        // put_ip_lossy never fails
+        let _ = self.encode_with(buf, put_ip_lossy);
+    }
+
+    /// Like `encode`, but fail on an IPv6 address
+    pub fn try_encode(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.encode_with(buf, put_ip)
+    }
+
+    pub(crate) fn encode_with(&self, buf: &mut Vec<u8>, put_ip: PutIp) -> Result<()> {
+        let (code, data) = self.code_and_data(put_ip)?;
+        let len = ::std::cmp::min(data.len(), 255);
+        buf.push(code);
+        buf.push(len as u8);
+        buf.extend(data.iter().take(len));
+        Ok(())
+    }
+}
+
+/// Split the value of option 63 into its suboptions
+pub fn netware_ip_suboptions(mut data: &[u8]) -> Result<Vec<NetwareIpSubOption>> {
+    #[cfg(feature = "tracing")]
+    let _span = ::tracing::trace_span!("netware_ip", len = data.len()).entered();
+    let mut suboptions = Vec::new();
+    while let Some((&code, rest)) = data.split_first() {
+        let (value, rest) = match rest.split_first() {
+            Some((&len, rest)) if rest.len() >= len as usize => rest.split_at(len as usize),
+            _ => return Err(Error::ParseError(format!("Truncated NetWare/IP suboption {}", code))),
+        };
+        let suboption = NetwareIpSubOption::from_code_and_data(code, value);
+        #[cfg(feature = "tracing")]
+        ::tracing::trace!(code, "parsed {:?}", suboption);
+        suboptions.push(suboption);
+        data = rest;
+    }
+    Ok(suboptions)
+}
+
+#[cfg(test)] mod tests {
+    use super::{netware_ip_suboptions, NetwareIpSubOption};
+    use super::NetwareIpSubOption::*;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn test_netware_ip_suboptions() {
+        let data = [
+            2u8, 0u8,
+            5u8, 1u8, 1u8,
+            6u8, 8u8, 10u8, 0u8, 0u8, 1u8, 10u8, 0u8, 0u8, 2u8,
+            9u8, 1u8, 30u8,
+            11u8, 4u8, 10u8, 0u8, 0u8, 3u8,
+            11u8, 2u8, 10u8, 0u8,
+            99u8, 1u8, 7u8,
+        ];
+        let suboptions = netware_ip_suboptions(&data).unwrap();
+        assert_eq!(suboptions, vec![
+            ExistsInOptionsArea,
+            NsqBroadcast(true),
+            PreferredDss(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))]),
+            AutoRetrySecs(30),
+            PrimaryDss(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3))),
+            Unknown { code: 11u8, data: vec![10u8, 0u8] },
+            Unknown { code: 99u8, data: vec![7u8] },
+        ]);
+        let mut encoded = Vec::new();
+        for sub in &suboptions {
+            sub.encode(&mut encoded);
+        }
+        assert_eq!(encoded, data.to_vec());
+    }
+
+    #[test]
+    fn test_truncated_suboption() {
+        assert!(netware_ip_suboptions(&[5u8]).is_err());
+        assert!(netware_ip_suboptions(&[6u8, 4u8, 10u8]).is_err());
+        assert_eq!(netware_ip_suboptions(&[]).unwrap(), Vec::<NetwareIpSubOption>::new());
+    }
+}
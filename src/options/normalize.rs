@@ -0,0 +1,215 @@
+//! Merging options that appear more than once
+//!
+//! RFC 3396 splits values longer than 255 bytes over back-to-back
+//! repeats of the option, to be concatenated in order. Relays that
+//! re-insert options and buggy clients also produce exact copies, and
+//! sometimes a second, different value for an option that only holds one.
+
+use options::{parse, put_option, DhcpOption};
+use options::raw::next_raw_option;
+
+/// An option that appeared with values that can't be merged into one
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Conflict {
+    pub code: u8,
+    /// Every distinct value, in order. `normalize` keeps the first.
+    pub values: Vec<DhcpOption>,
+}
+
+/// Leave each option code in `options` once, at its first appearance.
+/// A repeat that directly follows a full 255-byte fragment of the same
+/// code is joined onto it as RFC 3396 asks, and exact duplicates are
+/// dropped. Any other repeat, e.g. a second subnet mask or a second
+/// router list further on, is a conflict: the first value is kept and the
+/// code is reported. Pad and End are left alone.
+pub fn normalize(options: &mut Vec<DhcpOption>) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let mut normalized = Vec::with_capacity(options.len());
+    let mut seen = Vec::new();
+    for (i, option) in options.iter().enumerate() {
+        let code = option.code();
+        if code == 0u8 || code == 255u8 {
+            normalized.push(option.clone());
+            continue;
+        }
+        if seen.contains(&code) {
+            continue;
+        }
+        seen.push(code);
+
+        let mut values: Vec<DhcpOption> = Vec::new();
+        let mut previous = None;
+        for (j, repeat) in options.iter().enumerate().skip(i).filter(|&(_, o)| o.code() == code) {
+            let joined = match values.last() {
+                Some(last) if previous == Some(j - 1) && is_full_fragment(last) => merge(code, last, repeat),
+                _ => None,
+            };
+            match joined {
+                Some(joined) => {
+                    values.pop();
+                    values.push(joined);
+                },
+                None => values.push(repeat.clone()),
+            }
+            previous = Some(j);
+        }
+        let mut distinct: Vec<DhcpOption> = Vec::new();
+        for value in values {
+            if !distinct.contains(&value) {
+                distinct.push(value);
+            }
+        }
+        normalized.push(distinct[0].clone());
+        if distinct.len() > 1 {
+            conflicts.push(Conflict { code, values: distinct });
+        }
+    }
+    *options = normalized;
+    conflicts
+}
+
+/// The conflicts `normalize` would report for `options`, leaving them as
+/// they are. Exact copies and RFC 3396 continuations of a full fragment
+/// aren't conflicts, so anything reported here means two senders
+/// disagree, which is what spoofed replies and middleboxes rewriting
+/// options usually look like.
+pub fn conflicts(options: &[DhcpOption]) -> Vec<Conflict> {
+    normalize(&mut options.to_vec())
+}
+
+/// Whether `option` ends on a full 255-byte fragment, so that a repeat
+/// right after it carries on the same value
+fn is_full_fragment(option: &DhcpOption) -> bool {
+    let len = value_bytes(option).len();
+    len > 0 && len.is_multiple_of(255)
+}
+
+/// `first` with `next` joined onto it, or `None` if that isn't a valid value
+fn merge(code: u8, first: &DhcpOption, next: &DhcpOption) -> Option<DhcpOption> {
+    let mut data = value_bytes(first);
+    data.extend(value_bytes(next));
+    let mut wire = Vec::new();
+    put_option(&mut wire, code, &data);
+    let merged = match parse(&wire) {
+        Ok(ref parsed) if parsed.len() == 1 => parsed[0].clone(),
+        _ => return None,
+    };
+    // A parser that reads a fixed size ignores anything after it, so
+    // only a value that encodes back to all of `data` counts as merged
+    if value_bytes(&merged) != data {
+        return None;
+    }
+    Some(merged)
+}
+
+/// The value `option` encodes to, with its fragments joined
+fn value_bytes(option: &DhcpOption) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    option.encode(&mut bytes);
+    let mut data = Vec::new();
+    let mut rest = &bytes[..];
+    while let Some((raw, next)) = next_raw_option(rest) {
+        data.extend_from_slice(raw.data);
+        rest = next;
+    }
+    data
+}
+
+#[cfg(test)] mod tests {
+    use super::{normalize, conflicts, Conflict};
+    use options::DhcpOption::*;
+    use options::DhcpMessageTypes;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn ip(d: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, d))
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut options = vec![
+            MessageType(DhcpMessageTypes::Ack),
+            Router(ip_list![ip(1)]),
+            SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+            MessageType(DhcpMessageTypes::Ack),
+            Unknown { code: 254u8, data: vec![7u8; 255] },
+            Unknown { code: 254u8, data: vec![1u8, 2u8] },
+            SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 0, 0))),
+            Router(ip_list![ip(1)]),
+            End,
+        ];
+        let conflicts = normalize(&mut options);
+        let mut joined = vec![7u8; 255];
+        joined.extend_from_slice(&[1u8, 2u8]);
+        assert_eq!(options, vec![
+            MessageType(DhcpMessageTypes::Ack),
+            Router(ip_list![ip(1)]),
+            SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+            Unknown { code: 254u8, data: joined },
+            End,
+        ]);
+        assert_eq!(conflicts, vec![Conflict {
+            code: 1u8,
+            values: vec![
+                SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+                SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 0, 0))),
+            ],
+        }]);
+    }
+
+    #[test]
+    fn test_normalize_short_fragment() {
+        // Only a repeat after a full 255-byte fragment is a continuation
+        let mut options = vec![Router((0..40u8).map(ip).collect()), Router(ip_list![ip(200); 30]), Pad, Pad];
+        let conflicts = normalize(&mut options);
+        assert_eq!(options, vec![Router((0..40u8).map(ip).collect()), Pad, Pad]);
+        assert_eq!(conflicts.len(), 1);
+
+        let mut options = vec![
+            Unknown { code: 254u8, data: vec![1u8; 255] },
+            Unknown { code: 254u8, data: vec![2u8; 255] },
+            Unknown { code: 254u8, data: vec![3u8] },
+        ];
+        assert_eq!(normalize(&mut options), vec![]);
+        let mut joined = vec![1u8; 255];
+        joined.extend(vec![2u8; 255]);
+        joined.push(3u8);
+        assert_eq!(options, vec![Unknown { code: 254u8, data: joined }]);
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let server = |d| ServerIdentifier(ip(d));
+        let options = vec![
+            MessageType(DhcpMessageTypes::Offer),
+            server(1),
+            Router(ip_list![ip(1)]),
+            MessageType(DhcpMessageTypes::Offer),
+            Router(ip_list![ip(1)]),
+            server(9),
+            End,
+        ];
+        assert_eq!(conflicts(&options), vec![Conflict { code: 54u8, values: vec![server(1), server(9)] }]);
+        assert_eq!(options.len(), 7);
+        assert_eq!(conflicts(&options[..5]), vec![]);
+    }
+
+    #[test]
+    fn test_conflicting_host_names() {
+        let options = vec![HostName("laptop".to_string()), HostName("printer".to_string())];
+        assert_eq!(conflicts(&options), vec![Conflict { code: 12u8, values: options.clone() }]);
+    }
+
+    #[test]
+    fn test_conflicting_dns_servers() {
+        let options = vec![
+            DomainNameServer(ip_list![ip(1), ip(2)]),
+            MessageType(DhcpMessageTypes::Ack),
+            DomainNameServer(ip_list![ip(53)]),
+        ];
+        assert_eq!(conflicts(&options), vec![Conflict {
+            code: 6u8,
+            values: vec![DomainNameServer(ip_list![ip(1), ip(2)]), DomainNameServer(ip_list![ip(53)])],
+        }]);
+    }
+}
@@ -1,27 +1,418 @@
-use { Result };
-use nom::{be_u8, be_u32, be_i32, IResult, sized_buffer};
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+use { Result, Error, OPTIONS_OFFSET };
+use nom::{be_u8, be_u16, be_u32, sized_buffer};
 use std::str;
 use std::convert::{From};
 use std::net::{IpAddr, Ipv4Addr};
 use self::RelayAgentInformationSubOption::*;
 use options::DhcpOption;
 use options::DhcpOption::RelayAgentInformation;
+use options::encode::{put_ip, put_ip_lossy, PutIp};
+use options::edit::OptionEditor;
+use options::parse::parse_single_option;
+use options::raw::end_offset;
+use options::agent_id::{CircuitId, RemoteId};
+use hwaddr::MacAddress;
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 
 #[allow(dead_code)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[non_exhaustive]
 pub enum RelayAgentInformationSubOption {
     AgentCircuitID(Vec<u8>), // RFC 3046
     AgentRemoteID(Vec<u8>), // RFC 3046 	 	 
-    DOCSISDeviceClass(i32), // RFC 3256
+    DOCSISDeviceClass(DeviceClass), // RFC 3256
     LinkSelection(IpAddr), // RFC 3527
     SubscriberID(String), // RFC 3993
     RADIUSattributes(Vec<u8>), // RFC 4014
     Authentication(Vec<u8>), // RFC 4030
     VendorSpecificInformation(Vec<u8>), // RFC 4243
-    RelayAgentFlags(u8), // RFC 5010
-    ServerIdentifierOverride(i32), // RFC 5107
-    DHCPv4VirtualSubnetSelection(Vec<u8>), // RFC 6607
-    DHCPv4VirtualSubnetSelectionControl(Vec<u8>), // RFC 6607
+    RelayAgentFlags(AgentFlags), // RFC 5010
+    ServerIdentifierOverride(IpAddr), // RFC 5107
+    RelayAgentIdentifier(Vec<u8>), // RFC 6925
+    AccessTechnologyType(u8), // RFC 7839
+    AccessNetworkName(String), // RFC 7839
+    AccessPointName(String), // RFC 7839
+    AccessPointBssid(MacAddress), // RFC 7839
+    OperatorIdentifier { operator_type: u8, identifier: Vec<u8> }, // RFC 7839
+    OperatorRealm(String), // RFC 7839
+    RelayPort(u16), // RFC 8357
+    DHCPv4VirtualSubnetSelection(VirtualSubnetSelection), // RFC 6607
+    DHCPv4VirtualSubnetSelectionControl(VirtualSubnetSelection), // RFC 6607
+    /// A suboption we don't know, or one we couldn't decode
+    Unknown { code: u8, data: Vec<u8> },
+}
+
+/// The 32 bit device class a DOCSIS cable modem reports, as flags
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct DeviceClass(pub u32);
+
+impl DeviceClass {
+    /// CPE Controlled Cable Modem: the modem's software runs on the
+    /// customer's computer, e.g. a USB modem
+    pub const CPE_CONTROLLED_CABLE_MODEM: DeviceClass = DeviceClass(0x0000_0001);
+
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Whether every bit set in `flags` is set here
+    pub fn contains(self, flags: DeviceClass) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+
+    pub fn is_cpe_controlled_cable_modem(self) -> bool {
+        self.contains(DeviceClass::CPE_CONTROLLED_CABLE_MODEM)
+    }
+}
+
+impl ::std::ops::BitOr for DeviceClass {
+    type Output = DeviceClass;
+
+    fn bitor(self, other: DeviceClass) -> DeviceClass {
+        DeviceClass(self.0 | other.0)
+    }
+}
+
+/// The flags byte of the Relay Agent Flags suboption. Reserved bits are
+/// kept as they were received.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct AgentFlags(pub u8);
+
+impl AgentFlags {
+    /// The relay received the client's message as unicast
+    pub const UNICAST: AgentFlags = AgentFlags(0x80);
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Whether every bit set in `flags` is set here
+    pub fn contains(self, flags: AgentFlags) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+
+    pub fn is_unicast(self) -> bool {
+        self.contains(AgentFlags::UNICAST)
+    }
+
+    /// The bits RFC 5010 leaves reserved
+    pub fn reserved(self) -> u8 {
+        self.0 & !AgentFlags::UNICAST.0
+    }
+}
+
+impl ::std::ops::BitOr for AgentFlags {
+    type Output = AgentFlags;
+
+    fn bitor(self, other: AgentFlags) -> AgentFlags {
+        AgentFlags(self.0 | other.0)
+    }
+}
+
+/// The names of the flags set, separated by `|`, with any reserved bits
+/// last in hex, e.g. `UNICAST | 0x01`. No flags at all is `0x00`.
+impl ::std::fmt::Display for AgentFlags {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        let mut parts = Vec::new();
+        if self.is_unicast() {
+            parts.push("UNICAST".to_string());
+        }
+        if self.reserved() != 0 || parts.is_empty() {
+            parts.push(format!("{:#04x}", self.reserved()));
+        }
+        f.write_str(&parts.join(" | "))
+    }
+}
+
+/// The VSS type byte and the VSS information that follows it
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum VirtualSubnetSelection {
+    /// Type 0, the name of a VRF in NVT ASCII
+    VrfName(String),
+    /// Type 1, an RFC 2685 VPN-ID: the VPN's OUI and index
+    VpnId { oui: [u8; 3], index: u32 },
+    /// Type 255, the global (default) VPN
+    Global,
+    /// No data at all, which is what the VSS-Control suboption carries
+    Empty,
+    /// An unassigned type, or data that doesn't fit its type
+    Other { vss_type: u8, data: Vec<u8> },
+}
+
+impl VirtualSubnetSelection {
+    pub fn from_bytes(bytes: &[u8]) -> VirtualSubnetSelection {
+        match bytes.split_first() {
+            None => VirtualSubnetSelection::Empty,
+            Some((&0u8, name)) if str::from_utf8(name).is_ok() => {
+                VirtualSubnetSelection::VrfName(String::from_utf8_lossy(name).into_owned())
+            },
+            Some((&1u8, &[o1, o2, o3, i1, i2, i3, i4])) => VirtualSubnetSelection::VpnId {
+                oui: [o1, o2, o3],
+                index: u32::from_be_bytes([i1, i2, i3, i4]),
+            },
+            Some((&255u8, &[])) => VirtualSubnetSelection::Global,
+            Some((&vss_type, data)) => VirtualSubnetSelection::Other { vss_type, data: data.to_vec() },
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match *self {
+            VirtualSubnetSelection::VrfName(ref name) => {
+                let mut data = vec![0u8];
+                data.extend_from_slice(name.as_bytes());
+                data
+            },
+            VirtualSubnetSelection::VpnId { oui, index } => {
+                let mut data = vec![1u8];
+                data.extend_from_slice(&oui);
+                data.extend_from_slice(&index.to_be_bytes());
+                data
+            },
+            VirtualSubnetSelection::Global => vec![255u8],
+            VirtualSubnetSelection::Empty => vec![],
+            VirtualSubnetSelection::Other { vss_type, ref data } => {
+                let mut bytes = vec![vss_type];
+                bytes.extend_from_slice(data);
+                bytes
+            },
+        }
+    }
+}
+
+impl RelayAgentInformationSubOption {
+    fn code_and_data(&self, put_ip: PutIp) -> Result<(u8, Vec<u8>)> {
+        Ok(match *self {
+            AgentCircuitID(ref d) => (1u8, d.clone()),
+            AgentRemoteID(ref d) => (2u8, d.clone()),
+            DOCSISDeviceClass(c) => (4u8, c.0.to_be_bytes().to_vec()),
+            LinkSelection(ref a) => {
+                let mut data = Vec::with_capacity(4);
+                put_ip(&mut data, a)?;
+                (5u8, data)
+            },
+            SubscriberID(ref s) => (6u8, s.as_bytes().to_vec()),
+            RADIUSattributes(ref d) => (7u8, d.clone()),
+            Authentication(ref d) => (8u8, d.clone()),
+            VendorSpecificInformation(ref d) => (9u8, d.clone()),
+            RelayAgentFlags(f) => (10u8, vec![f.0]),
+            ServerIdentifierOverride(ref a) => {
+                let mut data = Vec::with_capacity(4);
+                put_ip(&mut data, a)?;
+                (11u8, data)
+            },
+            RelayAgentIdentifier(ref d) => (12u8, d.clone()),
+            // The second byte is reserved
+            AccessTechnologyType(t) => (13u8, vec![t, 0u8]),
+            AccessNetworkName(ref s) => (14u8, s.as_bytes().to_vec()),
+            AccessPointName(ref s) => (15u8, s.as_bytes().to_vec()),
+            AccessPointBssid(ref mac) => (16u8, mac.0.to_vec()),
+            OperatorIdentifier { operator_type, ref identifier } => {
+                let mut data = vec![operator_type];
+                data.extend_from_slice(identifier);
+                (17u8, data)
+            },
+            OperatorRealm(ref s) => (18u8, s.as_bytes().to_vec()),
+            RelayPort(p) => (19u8, p.to_be_bytes().to_vec()),
+            DHCPv4VirtualSubnetSelection(ref v) => (151u8, v.to_bytes()),
+            DHCPv4VirtualSubnetSelectionControl(ref v) => (152u8, v.to_bytes()),
+            Unknown { code, ref data } => (code, data.clone()),
+        })
+    }
+
+    /// The Agent Circuit ID, decoded as far as its format can be
+    /// recognized, or `None` for other suboptions
+    pub fn circuit_id(&self) -> Option<CircuitId> {
+        match *self {
+            AgentCircuitID(ref d) => Some(CircuitId::from_bytes(d)),
+            _ => None,
+        }
+    }
+
+    /// The Agent Remote ID, decoded as far as its format can be
+    /// recognized, or `None` for other suboptions
+    pub fn remote_id(&self) -> Option<RemoteId> {
+        match *self {
+            AgentRemoteID(ref d) => Some(RemoteId::from_bytes(d)),
+            _ => None,
+        }
+    }
+
+    /// Append the wire form of this suboption to `buf`. Suboptions can't
+    /// be split like top-level options, so values longer than 255 bytes
+    /// are truncated. IPv6 addresses are written as 0.0.0.0; use
+    /// `try_encode` to have them rejected instead.
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        // put_ip_lossy never fails
+        let _ = self.encode_with(buf, put_ip_lossy);
+    }
+
+    /// Like `encode`, but fail on an IPv6 address
+    pub fn try_encode(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.encode_with(buf, put_ip)
+    }
+
+    pub(crate) fn encode_with(&self, buf: &mut Vec<u8>, put_ip: PutIp) -> Result<()> {
+        let (code, data) = self.code_and_data(put_ip)?;
+        let len = ::std::cmp::min(data.len(), 255);
+        buf.push(code);
+        buf.push(len as u8);
+        buf.extend(data.iter().take(len));
+        Ok(())
+    }
+}
+
+/// Builds option 82 for a relay agent to add to the messages it forwards.
+///
+/// Suboptions are written in the order they are added. Unlike
+/// `DhcpOption::encode`, which splits or truncates long values, `build`
+/// and `encode` fail with `Error::CapacityExceeded` if a suboption value
+/// or the option as a whole is longer than 255 bytes, since relays and
+/// servers don't reassemble a split option 82, and with
+/// `Error::EncodeError` on an IPv6 address.
+#[derive(Debug, Default, Clone)]
+pub struct RelayAgentInformationBuilder {
+    suboptions: Vec<RelayAgentInformationSubOption>,
+}
+
+impl RelayAgentInformationBuilder {
+    pub fn new() -> RelayAgentInformationBuilder {
+        RelayAgentInformationBuilder { suboptions: Vec::new() }
+    }
+
+    pub fn suboption(mut self, suboption: RelayAgentInformationSubOption) -> RelayAgentInformationBuilder {
+        self.suboptions.push(suboption);
+        self
+    }
+
+    pub fn circuit_id<T: Into<Vec<u8>>>(self, id: T) -> RelayAgentInformationBuilder {
+        self.suboption(AgentCircuitID(id.into()))
+    }
+
+    pub fn remote_id<T: Into<Vec<u8>>>(self, id: T) -> RelayAgentInformationBuilder {
+        self.suboption(AgentRemoteID(id.into()))
+    }
+
+    pub fn link_selection(self, subnet: Ipv4Addr) -> RelayAgentInformationBuilder {
+        self.suboption(LinkSelection(IpAddr::V4(subnet)))
+    }
+
+    pub fn subscriber_id(self, id: &str) -> RelayAgentInformationBuilder {
+        self.suboption(SubscriberID(id.to_owned()))
+    }
+
+    pub fn relay_agent_flags(self, flags: AgentFlags) -> RelayAgentInformationBuilder {
+        self.suboption(RelayAgentFlags(flags))
+    }
+
+    pub fn server_identifier_override(self, server: Ipv4Addr) -> RelayAgentInformationBuilder {
+        self.suboption(ServerIdentifierOverride(IpAddr::V4(server)))
+    }
+
+    /// The UDP port the relay listens on, if it isn't 67 (RFC 8357)
+    pub fn relay_port(self, port: u16) -> RelayAgentInformationBuilder {
+        self.suboption(RelayPort(port))
+    }
+
+    fn check(&self) -> Result<()> {
+        let mut total = 0;
+        for suboption in &self.suboptions {
+            let (_, data) = suboption.code_and_data(put_ip)?;
+            if data.len() > 255 {
+                return Err(Error::CapacityExceeded);
+            }
+            total += 2 + data.len();
+        }
+        if total > 255 {
+            return Err(Error::CapacityExceeded);
+        }
+        Ok(())
+    }
+
+    pub fn build(self) -> Result<DhcpOption> {
+        self.check()?;
+        Ok(RelayAgentInformation(self.suboptions))
+    }
+
+    /// The complete option, `[82, length, suboptions...]`
+    pub fn encode(self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.build()?.try_encode(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Add option 82 to the encoded message `packet`, as a relay does before
+/// forwarding a client's message to a server.
+///
+/// It goes last, before End, with End added if there wasn't one. Pad
+/// after End gives up as many bytes as the option takes, so a message
+/// padded to a minimum size doesn't grow unless it has to. Fails if the
+/// message already has option 82, which RFC 3046 leaves to the relay's
+/// policy, or if the suboptions don't fit in 255 bytes.
+pub fn insert_relay_agent_info(packet: &mut Vec<u8>, info: &RelayAgentInformationBuilder) -> Result<()> {
+    let option = info.clone().build()?;
+    let end = end_offset(packet.get(OPTIONS_OFFSET..).unwrap_or(&[])).map(|end| OPTIONS_OFFSET + end);
+    let before = packet.len();
+    {
+        let mut editor = OptionEditor::new(packet)?;
+        if editor.get(82u8).is_some() {
+            return Err(Error::ParseError("Message already has option 82".to_string()));
+        }
+        editor.insert(&option);
+        if end.is_none() {
+            editor.insert(&DhcpOption::End);
+        }
+    }
+    if let Some(end) = end {
+        let added = packet.len() - before;
+        let after_end = end + added + 1;
+        if let Some(padding) = packet.get(after_end..) {
+            if padding.iter().all(|&b| b == 0u8) {
+                let trim = ::std::cmp::min(padding.len(), added);
+                packet.truncate(packet.len() - trim);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Take option 82 out of the encoded message `packet`, as a relay does
+/// before forwarding a server's reply to the client. Returns its
+/// suboptions, or `None` if there was no option 82.
+pub fn strip_relay_agent_info(packet: &mut Vec<u8>) -> Result<Option<Vec<RelayAgentInformationSubOption>>> {
+    let mut editor = OptionEditor::new(packet)?;
+    let suboptions = match editor.get(82u8) {
+        Some(data) => {
+            let mut bytes = vec![82u8, data.len() as u8];
+            bytes.extend_from_slice(data);
+            match parse_single_option(&bytes) {
+                Ok((RelayAgentInformation(suboptions), _)) => Some(suboptions),
+                _ => Some(Vec::new()),
+            }
+        },
+        None => None,
+    };
+    editor.remove(82u8);
+    Ok(suboptions)
 }
 
 fn u32_to_ip(a: u32) -> IpAddr {
@@ -30,7 +421,9 @@ fn u32_to_ip(a: u32) -> IpAddr {
 
 /// A macro for options that are of the form:
 ///
-///     [tag, length, somestring]
+/// ```text
+/// [tag, length, somestring]
+/// ```
 ///
 /// , since I haven't figured out a way to
 /// easily construct a parser to take the length
@@ -82,8 +475,8 @@ named!(docsis_device_class<&[u8], RelayAgentInformationSubOption>,
         tag!([4u8]) >>
         // length field, always 4
         be_u8 >>
-        device_class: be_i32 >>
-        ({ DOCSISDeviceClass(device_class) })
+        device_class: be_u32 >>
+        ({ DOCSISDeviceClass(DeviceClass(device_class)) })
     )
 );
 single_ip!(link_selection, 5u8, LinkSelection);
@@ -114,30 +507,72 @@ named!(relay_agent_flags<&[u8], RelayAgentInformationSubOption>,
         tag!([10u8]) >>
         _length: be_u8 >>
         relay_agent_flag: be_u8 >>
-        ({ RelayAgentFlags(relay_agent_flag) })
+        ({ RelayAgentFlags(AgentFlags(relay_agent_flag)) })
     )
 );
-named!(server_identifier_override<&[u8], RelayAgentInformationSubOption>,
+single_ip!(server_identifier_override, 11u8, ServerIdentifierOverride);
+named!(relay_agent_identifier<&[u8], RelayAgentInformationSubOption>,
     do_parse!(
-        tag!([11u8]) >>
-        // length field, always 4
+        tag!([12u8]) >>
+        data: length_count!(be_u8, be_u8) >>
+        ({ RelayAgentIdentifier(data) })
+    )
+);
+named!(access_technology_type<&[u8], RelayAgentInformationSubOption>,
+    do_parse!(
+        tag!([13u8]) >>
+        // length field, always 2
+        be_u8 >>
+        att: be_u8 >>
+        _reserved: be_u8 >>
+        ({ AccessTechnologyType(att) })
+    )
+);
+length_specific_string!(access_network_name, 14u8, AccessNetworkName);
+length_specific_string!(access_point_name, 15u8, AccessPointName);
+named!(access_point_bssid<&[u8], RelayAgentInformationSubOption>,
+    do_parse!(
+        tag!([16u8]) >>
+        bssid: map_opt!(sized_buffer, MacAddress::from_slice) >>
+        ({ AccessPointBssid(bssid) })
+    )
+);
+named!(operator_identifier<&[u8], RelayAgentInformationSubOption>,
+    do_parse!(
+        tag!([17u8]) >>
+        data: map_opt!(sized_buffer, <[u8]>::split_first) >>
+        ({ OperatorIdentifier { operator_type: *data.0, identifier: data.1.to_vec() } })
+    )
+);
+length_specific_string!(operator_realm, 18u8, OperatorRealm);
+named!(relay_port<&[u8], RelayAgentInformationSubOption>,
+    do_parse!(
+        tag!([19u8]) >>
+        // length field, always 2
         be_u8 >>
-        identifier: be_i32 >>
-        ({ ServerIdentifierOverride(identifier) })
+        port: be_u16 >>
+        ({ RelayPort(port) })
     )
 );
 named!(dhcp_v4_virtual_subnet_selection<&[u8], RelayAgentInformationSubOption>,
     do_parse!(
         tag!([151u8]) >>
-        data: length_count!(be_u8, be_u8) >>
-        ({ DHCPv4VirtualSubnetSelection(data) })
+        vss: map!(sized_buffer, VirtualSubnetSelection::from_bytes) >>
+        ({ DHCPv4VirtualSubnetSelection(vss) })
     )
 );
 named!(dhcp_v4_virtual_subnet_selection_control<&[u8], RelayAgentInformationSubOption>,
     do_parse!(
         tag!([152u8]) >>
+        vss: map!(sized_buffer, VirtualSubnetSelection::from_bytes) >>
+        ({ DHCPv4VirtualSubnetSelectionControl(vss) })
+    )
+);
+named!(unknown_suboption<&[u8], RelayAgentInformationSubOption>,
+    do_parse!(
+        code: be_u8 >>
         data: length_count!(be_u8, be_u8) >>
-        ({ DHCPv4VirtualSubnetSelectionControl(data) })
+        ({ Unknown { code, data } })
     )
 );
 
@@ -153,68 +588,49 @@ named!(option_82_parser<&[u8], RelayAgentInformationSubOption>, alt!(
         | vendor_specific_information
         | relay_agent_flags
         | server_identifier_override
+        | relay_agent_identifier
+        | access_technology_type
+        | access_network_name
+        | access_point_name
+        | access_point_bssid
+        | operator_identifier
+        | operator_realm
+        | relay_port
         | dhcp_v4_virtual_subnet_selection
         | dhcp_v4_virtual_subnet_selection_control
+        | unknown_suboption
     )
 );
 
-fn parse(bytes: &[u8]) -> Result<Vec<RelayAgentInformationSubOption>> {
+/// Split the value of option 82 into its suboptions
+pub fn relay_agent_suboptions(bytes: &[u8]) -> Result<Vec<RelayAgentInformationSubOption>> {
+    #[cfg(feature = "tracing")]
+    let _span = ::tracing::trace_span!("relay_agent_information", len = bytes.len()).entered();
     let mut vec = Vec::new();
-    if bytes.len() > 0 {
-        let mut remaining = Some(bytes);
-        while let Some(unparsed) = remaining {
-            // Do some basic sanity checks before actually parsing
-            match unparsed.len() {
-                0 | 1 | 2 => {
-                    // Shouldn't get here, but just in case
-                    remaining = None;
-                    continue;
-                },
-                _ => {
-                    // Assume it's an option in the standard format:
-                    // [Option Number Field] (1 byte) [Length Field] (1 byte) [Rest of option...]
-                    // and calculte the option length as:
-                    // opt_num_field (1 byte) + opt_len_field (1 byte) + value_in_opt_len_field
-                    let option_length: usize = 2 + (unparsed[1] as usize);
-                    // Sanity check the option is actually within bounds of
-                    // remaining byte array
-                    if option_length > unparsed.len() {
-                        remaining = None;
-                        continue;
-                    }
-                },
-            }
-
-            // If an option was successfully parsed
-            if let IResult::Done(rest, opt) = option_82_parser(unparsed) {
-                // If this is the end of options
-                if rest.len() < 3 {
-                    remaining = None;
-                } else {
-                    remaining = Some(rest);
-                }
-                vec.push(opt);
-            } else {
-                // It's either an:
-                //   • error/invalid option
-                //   • option we don't know
-                // In either case, assume initially that there's nothing left we can parse
-                remaining = None;
-
-                // See if we can recover gracefully and continue parsing any remaining options
-                if unparsed.len() > 2 {
-                    // Skip this option but assume it's an option in the
-                    // standard format & parse the remaining options if possible
-                    let start_of_next_option: usize = 2 + (unparsed[1] as usize);
-
-                    // Sanity check the start of (any) remaning options are within
-                    // the bounds of remaining byte array
-                    if unparsed.len() > start_of_next_option {
-                        remaining = Some(&unparsed[start_of_next_option..]);
-                    }
-                }
-            }
+    let mut remaining = bytes;
+    // Assume each is a suboption in the standard format:
+    // [Code] (1 byte) [Length] (1 byte) [Value] (Length bytes)
+    // A lone byte at the end isn't a suboption.
+    while let Some(&len) = remaining.get(1) {
+        #[cfg(feature = "tracing")]
+        let code = remaining.first().copied();
+        if remaining.len() < 2 + len as usize {
+            #[cfg(feature = "tracing")]
+            ::tracing::debug!(code, "suboption runs past the end of option 82; stopped there");
+            break;
         }
+        let (suboption, rest) = remaining.split_at(2 + len as usize);
+        if let Ok((_, opt)) = option_82_parser(suboption) {
+            #[cfg(feature = "tracing")]
+            ::tracing::trace!(code, "parsed {:?}", opt);
+            vec.push(opt);
+        } else {
+            // It's either an invalid suboption or one we don't know;
+            // skip it and carry on with the next
+            #[cfg(feature = "tracing")]
+            ::tracing::debug!(code, "could not parse suboption; skipped it");
+        }
+        remaining = rest;
     }
     Ok(vec)
 }
@@ -222,7 +638,7 @@ fn parse(bytes: &[u8]) -> Result<Vec<RelayAgentInformationSubOption>> {
 named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
     do_parse!(
         tag!([82u8]) >>
-        data: map_res!(sized_buffer, parse) >>
+        data: map_res!(sized_buffer, relay_agent_suboptions) >>
         ({ RelayAgentInformation(data) })
     )
 );
@@ -231,9 +647,81 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
     use super::RelayAgentInformationSubOption::*;
     use super::relay_agent_information_option_rfc3046;
     use std::net::{IpAddr, Ipv4Addr};
-    use nom::IResult;
     use options::DhcpOption::RelayAgentInformation;
+    use super::{RelayAgentInformationBuilder, VirtualSubnetSelection, DeviceClass, AgentFlags};
+    use hwaddr::MacAddress;
+    use Error;
+
+    fn message(options: &[u8]) -> Vec<u8> {
+        let mut msg = vec![0u8; 236];
+        msg.extend_from_slice(&[99u8, 130u8, 83u8, 99u8]);
+        msg.extend_from_slice(options);
+        msg
+    }
+
+    #[test]
+    fn test_insert_and_strip_relay_agent_info() {
+        use super::{insert_relay_agent_info, strip_relay_agent_info};
+        let info = RelayAgentInformationBuilder::new().circuit_id(vec![0u8, 1u8]);
+
+        // Pad after End makes room
+        let mut packet = message(&[53u8, 1u8, 1u8, 255u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8]);
+        insert_relay_agent_info(&mut packet, &info).unwrap();
+        assert_eq!(&packet[240..], &[53u8, 1u8, 1u8, 82u8, 4u8, 1u8, 2u8, 0u8, 1u8, 255u8, 0u8]);
+        assert!(insert_relay_agent_info(&mut packet, &info).is_err());
+
+        assert_eq!(strip_relay_agent_info(&mut packet).unwrap(), Some(vec![AgentCircuitID(vec![0u8, 1u8])]));
+        assert_eq!(&packet[240..], &[53u8, 1u8, 1u8, 255u8, 0u8]);
+        assert_eq!(strip_relay_agent_info(&mut packet).unwrap(), None);
+
+        // No End
+        let mut packet = message(&[53u8, 1u8, 1u8]);
+        insert_relay_agent_info(&mut packet, &info).unwrap();
+        assert_eq!(&packet[240..], &[53u8, 1u8, 1u8, 82u8, 4u8, 1u8, 2u8, 0u8, 1u8, 255u8]);
+
+        let too_long = RelayAgentInformationBuilder::new().circuit_id(vec![0u8; 200]).remote_id(vec![0u8; 100]);
+        assert!(insert_relay_agent_info(&mut message(&[255u8]), &too_long).is_err());
+    }
 
+    #[test]
+    fn test_builder() {
+        let option = RelayAgentInformationBuilder::new()
+            .circuit_id(vec![0u8, 1u8])
+            .remote_id(&b"AB"[..])
+            .link_selection(Ipv4Addr::new(10, 0, 0, 0))
+            .build()
+            .unwrap();
+        assert_eq!(option, RelayAgentInformation(vec![
+            AgentCircuitID(vec![0u8, 1u8]),
+            AgentRemoteID(vec![65u8, 66u8]),
+            LinkSelection(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0))),
+        ]));
+
+        let bytes = RelayAgentInformationBuilder::new()
+            .circuit_id(vec![0u8, 1u8])
+            .server_identifier_override(Ipv4Addr::new(192, 168, 1, 1))
+            .encode()
+            .unwrap();
+        assert_eq!(bytes, vec![
+            82u8, 10u8,
+            1u8, 2u8, 0u8, 1u8,
+            11u8, 4u8, 192u8, 168u8, 1u8, 1u8,
+        ]);
+    }
+
+    #[test]
+    fn test_builder_length_limits() {
+        match RelayAgentInformationBuilder::new().circuit_id(vec![0u8; 256]).build() {
+            Err(Error::CapacityExceeded) => {},
+            e => panic!("Result was {:?}", e),
+        }
+        match RelayAgentInformationBuilder::new().circuit_id(vec![0u8; 200]).remote_id(vec![0u8; 60]).encode() {
+            Err(Error::CapacityExceeded) => {},
+            e => panic!("Result was {:?}", e),
+        }
+        let bytes = RelayAgentInformationBuilder::new().circuit_id(vec![0u8; 200]).remote_id(vec![0u8; 51]).encode();
+        assert_eq!(bytes.unwrap().len(), 2 + 255);
+    }
 
     #[test]
     fn test_unknown_option() {
@@ -247,10 +735,13 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
             6u8,    // Suboption Length
             0u8, 1u8, 2u8, 3u8, 4u8, 5u8,
         ];
-        let expected = RelayAgentInformation(vec![ AgentCircuitID(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]) ]);
+        let expected = RelayAgentInformation(vec![
+            Unknown { code: 150u8, data: vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ] },
+            AgentCircuitID(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]),
+        ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -271,8 +762,8 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
         ];
         let expected = RelayAgentInformation(vec![ AgentCircuitID(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -296,8 +787,8 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
         ];
         let expected = RelayAgentInformation(vec![ AgentCircuitID(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -315,8 +806,28 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
         ];
         let expected = RelayAgentInformation(vec![ AgentCircuitID(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
+                assert_eq!(expected, actual);
+            },
+            e => panic!("Result was {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_zero_length_suboptions() {
+        let option = [
+            82u8,   // Option 82
+            4u8,    // Option 82 Length
+            9u8,    // Suboption
+            0u8,    // Suboption Length
+            1u8,    // Suboption
+            0u8,    // Suboption Length
+        ];
+        let expected = RelayAgentInformation(vec![ VendorSpecificInformation(vec![]), AgentCircuitID(vec![]) ]);
+        match relay_agent_information_option_rfc3046(&option) {
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -334,8 +845,8 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
         ];
         let expected = RelayAgentInformation(vec![ AgentCircuitID(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -353,8 +864,8 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
         ];
         let expected = RelayAgentInformation(vec![ AgentRemoteID(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -368,18 +879,27 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
             6u8,    // Option 82 Length
             4u8,    // Suboption
             4u8,    // Suboption Length
-            0u8, 0u8, 0u8, 1u8
+            0x80u8, 0u8, 0u8, 1u8
         ];
-        let expected = RelayAgentInformation(vec![ DOCSISDeviceClass(1) ]);
+        let expected = RelayAgentInformation(vec![ DOCSISDeviceClass(DeviceClass(0x80000001)) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
         }
     }
 
+    #[test]
+    fn test_device_class_flags() {
+        let class = DeviceClass(0x80000001);
+        assert!(class.is_cpe_controlled_cable_modem());
+        assert!(class.contains(DeviceClass(0x80000000) | DeviceClass::CPE_CONTROLLED_CABLE_MODEM));
+        assert!(!DeviceClass(0x2).is_cpe_controlled_cable_modem());
+        assert_eq!(class.bits(), 0x80000001);
+    }
+
     #[test]
     fn test_suboption_005_link_selection() {
         let option = [
@@ -391,8 +911,8 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
         ];
         let expected = RelayAgentInformation(vec![ LinkSelection(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -410,8 +930,8 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
         ];
         let expected = RelayAgentInformation(vec![ SubscriberID("Test".to_string()) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -429,8 +949,8 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
         ];
         let expected = RelayAgentInformation(vec![ RADIUSattributes(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -448,8 +968,8 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
         ];
         let expected = RelayAgentInformation(vec![ Authentication(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -467,8 +987,8 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
         ];
         let expected = RelayAgentInformation(vec![ VendorSpecificInformation(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -484,16 +1004,28 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
             1u8,    // Suboption Length
             123u8
         ];
-        let expected = RelayAgentInformation(vec![ RelayAgentFlags(123u8) ]);
+        let expected = RelayAgentInformation(vec![ RelayAgentFlags(AgentFlags(123u8)) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
         }
     }
 
+    #[test]
+    fn test_agent_flags() {
+        let flags = AgentFlags(0x81);
+        assert!(flags.is_unicast());
+        assert_eq!(flags.reserved(), 0x01);
+        assert_eq!(flags.to_string(), "UNICAST | 0x01");
+        assert_eq!(AgentFlags::UNICAST.to_string(), "UNICAST");
+        assert_eq!(AgentFlags(0).to_string(), "0x00");
+        assert!(!AgentFlags(0x01).is_unicast());
+        assert_eq!(AgentFlags::UNICAST | AgentFlags(0x02), AgentFlags(0x82));
+    }
+
     #[test]
     fn test_suboption_011_server_identifier_override() {
         let option = [
@@ -501,12 +1033,12 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
             6u8,    // Option 82 Length
             11u8,    // Suboption
             4u8,    // Suboption Length
-            0u8, 0u8, 0u8, 1u8
+            10u8, 0u8, 0u8, 1u8
         ];
-        let expected = RelayAgentInformation(vec![ ServerIdentifierOverride(1) ]);
+        let expected = RelayAgentInformation(vec![ ServerIdentifierOverride(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
@@ -514,37 +1046,203 @@ named!(pub relay_agent_information_option_rfc3046<&[u8], DhcpOption>,
     }
 
     #[test]
-    fn test_suboption_151_dhcp_v4_virtual_subnet_selection() {
+    fn test_suboption_012_relay_agent_identifier() {
+        let option = [
+            82u8,   // Option 82
+            6u8,    // Option 82 Length
+            12u8,    // Suboption
+            4u8,    // Suboption Length
+            0u8, 1u8, 2u8, 3u8
+        ];
+        let expected = RelayAgentInformation(vec![ RelayAgentIdentifier(vec![ 0u8, 1u8, 2u8, 3u8 ]) ]);
+        match relay_agent_information_option_rfc3046(&option) {
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
+                assert_eq!(expected, actual);
+            },
+            e => panic!("Result was {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_suboption_013_access_technology_type() {
+        let option = [
+            82u8,   // Option 82
+            4u8,    // Option 82 Length
+            13u8,    // Suboption
+            2u8,    // Suboption Length
+            2u8, 0u8
+        ];
+        let expected = RelayAgentInformation(vec![ AccessTechnologyType(2u8) ]);
+        match relay_agent_information_option_rfc3046(&option) {
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
+                assert_eq!(expected, actual);
+            },
+            e => panic!("Result was {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_suboption_014_access_network_name() {
+        let option = [
+            82u8,   // Option 82
+            6u8,    // Option 82 Length
+            14u8,    // Suboption
+            4u8,    // Suboption Length
+            84u8, 101u8, 115u8, 116u8
+        ];
+        let expected = RelayAgentInformation(vec![ AccessNetworkName("Test".to_string()) ]);
+        match relay_agent_information_option_rfc3046(&option) {
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
+                assert_eq!(expected, actual);
+            },
+            e => panic!("Result was {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_suboption_015_access_point_name() {
+        let option = [
+            82u8,   // Option 82
+            6u8,    // Option 82 Length
+            15u8,    // Suboption
+            4u8,    // Suboption Length
+            84u8, 101u8, 115u8, 116u8
+        ];
+        let expected = RelayAgentInformation(vec![ AccessPointName("Test".to_string()) ]);
+        match relay_agent_information_option_rfc3046(&option) {
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
+                assert_eq!(expected, actual);
+            },
+            e => panic!("Result was {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_suboption_016_access_point_bssid() {
         let option = [
             82u8,   // Option 82
             8u8,    // Option 82 Length
-            151u8,    // Suboption
+            16u8,    // Suboption
             6u8,    // Suboption Length
             0u8, 1u8, 2u8, 3u8, 4u8, 5u8
         ];
-        let expected = RelayAgentInformation(vec![ DHCPv4VirtualSubnetSelection(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]) ]);
+        let expected = RelayAgentInformation(vec![ AccessPointBssid(MacAddress([ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ])) ]);
+        match relay_agent_information_option_rfc3046(&option) {
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
+                assert_eq!(expected, actual);
+            },
+            e => panic!("Result was {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_suboption_017_operator_identifier() {
+        let option = [
+            82u8,   // Option 82
+            6u8,    // Option 82 Length
+            17u8,    // Suboption
+            4u8,    // Suboption Length
+            1u8,    // Operator type
+            0u8, 1u8, 2u8
+        ];
+        let expected = RelayAgentInformation(vec![ OperatorIdentifier { operator_type: 1u8, identifier: vec![ 0u8, 1u8, 2u8 ] } ]);
+        match relay_agent_information_option_rfc3046(&option) {
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
+                assert_eq!(expected, actual);
+            },
+            e => panic!("Result was {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_suboption_018_operator_realm() {
+        let option = [
+            82u8,   // Option 82
+            6u8,    // Option 82 Length
+            18u8,    // Suboption
+            4u8,    // Suboption Length
+            84u8, 101u8, 115u8, 116u8
+        ];
+        let expected = RelayAgentInformation(vec![ OperatorRealm("Test".to_string()) ]);
+        match relay_agent_information_option_rfc3046(&option) {
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
+                assert_eq!(expected, actual);
+            },
+            e => panic!("Result was {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_suboption_019_relay_port() {
+        let option = [
+            82u8,   // Option 82
+            4u8,    // Option 82 Length
+            19u8,    // Suboption
+            2u8,    // Suboption Length
+            10u8, 67u8
+        ];
+        let expected = RelayAgentInformation(vec![ RelayPort(2627u16) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
         }
     }
 
+    #[test]
+    fn test_suboption_151_dhcp_v4_virtual_subnet_selection() {
+        let option = [
+            82u8,   // Option 82
+            10u8,    // Option 82 Length
+            151u8,    // Suboption
+            8u8,    // Suboption Length
+            1u8,    // VSS Type (VPN-ID)
+            0u8, 1u8, 2u8, 0u8, 0u8, 0u8, 5u8
+        ];
+        let expected = RelayAgentInformation(vec![
+            DHCPv4VirtualSubnetSelection(VirtualSubnetSelection::VpnId { oui: [0u8, 1u8, 2u8], index: 5 })
+        ]);
+        match relay_agent_information_option_rfc3046(&option) {
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
+                assert_eq!(expected, actual);
+            },
+            e => panic!("Result was {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_virtual_subnet_selection_types() {
+        assert_eq!(VirtualSubnetSelection::from_bytes(&[0u8, 114u8, 101u8, 100u8]),
+                   VirtualSubnetSelection::VrfName("red".to_string()));
+        assert_eq!(VirtualSubnetSelection::from_bytes(&[255u8]), VirtualSubnetSelection::Global);
+        assert_eq!(VirtualSubnetSelection::from_bytes(&[]), VirtualSubnetSelection::Empty);
+        assert_eq!(VirtualSubnetSelection::from_bytes(&[1u8, 2u8]),
+                   VirtualSubnetSelection::Other { vss_type: 1u8, data: vec![2u8] });
+        assert_eq!(VirtualSubnetSelection::from_bytes(&[7u8, 2u8]).to_bytes(), vec![7u8, 2u8]);
+    }
+
     #[test]
     fn test_suboption_152_dhcp_v4_virtual_subnet_selection_control() {
         let option = [
             82u8,   // Option 82
-            8u8,    // Option 82 Length
+            2u8,    // Option 82 Length
             152u8,    // Suboption
-            6u8,    // Suboption Length
-            0u8, 1u8, 2u8, 3u8, 4u8, 5u8
+            0u8,    // Suboption Length
         ];
-        let expected = RelayAgentInformation(vec![ DHCPv4VirtualSubnetSelectionControl(vec![ 0u8, 1u8, 2u8, 3u8, 4u8, 5u8 ]) ]);
+        let expected = RelayAgentInformation(vec![ DHCPv4VirtualSubnetSelectionControl(VirtualSubnetSelection::Empty) ]);
         match relay_agent_information_option_rfc3046(&option) {
-            IResult::Done(remaning, actual) => {
-                if remaning.len() > 0 { panic!("Remaining input was {:?}", remaning); }
+            Ok((remaning, actual)) => {
+                if !remaning.is_empty() { panic!("Remaining input was {:?}", remaning); }
                 assert_eq!(expected, actual);
             },
             e => panic!("Result was {:?}", e),
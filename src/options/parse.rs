@@ -1,120 +1,649 @@
-use options::{DhcpOption};
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+use options::{self, DhcpOption, ClientId, IpAddrList, NameService, GeoLocation, LeasequeryStatus, LeaseState, UserClasses};
 use options::DhcpOption::*;
-use { Result };
-use nom::{be_u8, be_u16, be_u32, be_i32, IResult, sized_buffer};
+use { Result, Error, MAGIC_COOKIE };
+use nom::{be_u8, be_u16, be_u32, be_i32, Context, Err, IResult, Needed, sized_buffer};
 use std::borrow::{ToOwned};
 use std::str;
 use std::convert::{From};
-use std::net::{IpAddr, Ipv4Addr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use num::{FromPrimitive};
-use options::option82::relay_agent_information_option_rfc3046;
-
-pub fn parse(bytes: &[u8]) -> Result<Vec<DhcpOption>> {
-    let mut vec = Vec::new();
-    if bytes.len() > 0 {
-        let mut remaining = Some(bytes);
-        while let Some(unparsed) = remaining {
-            // Do some basic sanity checks before actually parsing
-            match unparsed.len() {
-                0 => {
-                    // Shouldn't get here, but just in case
-                    remaining = None;
-                    continue;
-                },
-                1 => {
-                    // If there's only a single byte left
-                    // make sure it's a valid single byte option
-                    if unparsed[0] != 0u8 && unparsed[0] != 255u8 {
-                        remaining = None;
-                        continue;
-                    }
-                },
-                // TODO: Any 2 byte options?
-                _ => {
-                    // If there are several bytes left and the next byte
-                    // is not a single byte option
-                    if unparsed[0] != 0u8 && unparsed[0] != 255u8 {
-                        // Assume it's an option in the standard format:
-                        // [Option Number Field] (1 byte) [Length Field] (1 byte) [Rest of option...]
-                        // and calculte the option length as:
-                        // opt_num_field (1 byte) + opt_len_field (1 byte) + value_in_opt_len_field
-                        let option_length: usize = 2 + (unparsed[1] as usize);
-                        // Sanity check the option is actually within bounds of
-                        // remaining byte array
-                        if option_length > unparsed.len() {
-                            remaining = None;
-                            continue;
-                        }
+use options::option82::{relay_agent_information_option_rfc3046, relay_agent_suboptions};
+use options::raw::next_raw_option;
+use options::dns::{decode_name, decode_names, NameMode};
+use options::netware::netware_ip_suboptions;
+use options::subnet_alloc::subnet_allocation;
+use quirks::Quirks;
+
+pub fn parse<T: AsRef<[u8]> + ?Sized>(bytes: &T) -> Result<Vec<DhcpOption>> {
+    parse_with_quirks(bytes.as_ref(), &NO_QUIRKS)
+}
+
+/// Parse the option at the start of `bytes`, returning it and the bytes
+/// after it. The option's decoder only sees as many bytes as its length
+/// field gives, and the rest starts right after them, so an option with
+/// the wrong length is an error rather than a misread.
+pub fn parse_single_option(bytes: &[u8]) -> Result<(DhcpOption, &[u8])> {
+    let (option, rest) = match (bytes.first(), split_option(bytes)) {
+        (None, _) => return Err(Error::ParseError("No option".to_string())),
+        (Some(&code), None) => return Err(Error::ParseError(format!("Option {} runs past the end of the buffer", code))),
+        (_, Some(split)) => split,
+    };
+    match dhcp_option(option) {
+        Ok((_, opt)) => Ok((opt, rest)),
+        _ => Err(Error::ParseError(format!("Could not parse option {}", option.first().cloned().unwrap_or(0u8)))),
+    }
+}
+
+/// Where the magic cookie (99.130.83.99) first appears in `bytes`, e.g.
+/// at 0 in a BOOTP vendor area that holds options
+pub fn find_magic_cookie(bytes: &[u8]) -> Option<usize> {
+    bytes.windows(MAGIC_COOKIE.len()).position(|w| w == MAGIC_COOKIE)
+}
+
+/// Find the magic cookie in `bytes` and parse the options after it.
+/// Returns the cookie's offset with the options.
+pub fn parse_after_cookie(bytes: &[u8]) -> Result<(usize, Vec<DhcpOption>)> {
+    let offset = match find_magic_cookie(bytes) {
+        Some(offset) => offset,
+        None => return Err(Error::ParseError("No magic cookie".to_string())),
+    };
+    Ok((offset, parse(bytes.get(offset + MAGIC_COOKIE.len()..).unwrap_or(&[]))?))
+}
+
+/// Like `parse`, but clears `out` and fills it, so a long-running caller
+/// can reuse one allocation for every message
+pub fn parse_into(bytes: &[u8], out: &mut Vec<DhcpOption>) -> Result<()> {
+    out.clear();
+    out.extend(OptionsIter::new(bytes).filter_map(|opt| opt.ok()));
+    Ok(())
+}
+
+/// Like `parse`, but with device-specific workarounds applied to each
+/// option before it is decoded
+pub fn parse_with_quirks(bytes: &[u8], quirks: &Quirks) -> Result<Vec<DhcpOption>> {
+    let mut options = Vec::with_capacity(option_count(bytes));
+    options.extend(OptionsIter::with_quirks(bytes, quirks).filter_map(|opt| opt.ok()));
+    Ok(options)
+}
+
+/// Like `parse_with_quirks`, with the rest of `config` applied too. Fails
+/// with `Error::CapacityExceeded` if the options go over `config.limits`.
+pub fn parse_with_config(bytes: &[u8], config: &ParseConfig) -> Result<Vec<DhcpOption>> {
+    let iter = OptionsIter::with_quirks(bytes, &config.quirks)
+        .pads(config.pads)
+        .strict(config.strict)
+        .limits(config.limits);
+    let mut options = Vec::with_capacity(::std::cmp::min(option_count(bytes), config.limits.max_options));
+    for opt in iter {
+        match opt {
+            Ok(opt) => options.push(opt),
+            Err(Error::CapacityExceeded) => return Err(Error::CapacityExceeded),
+            Err(_) => {},
+        }
+    }
+    Ok(options)
+}
+
+/// Like `parse`, but with a warning for each option that was dropped and
+/// why, so recovery from a malformed capture isn't silent
+pub fn parse_with_diagnostics(bytes: &[u8]) -> (Vec<DhcpOption>, Vec<ParseWarning>) {
+    let mut options = Vec::with_capacity(option_count(bytes));
+    let mut warnings = Vec::new();
+    let mut iter = OptionsIter::new(bytes);
+    while let Some(opt) = iter.next_checked() {
+        match opt {
+            Ok(opt) => options.push(opt),
+            Err(warning) => warnings.push(warning),
+        }
+    }
+    (options, warnings)
+}
+
+/// Like `parse`, but with where each option was found, e.g. to highlight
+/// the bytes behind it. Offsets are from the start of `bytes`; in a
+/// whole message the options start at byte 240, after the magic cookie.
+pub fn parse_spanned(bytes: &[u8]) -> Result<Vec<Spanned<DhcpOption>>> {
+    let mut options = Vec::with_capacity(option_count(bytes));
+    let mut iter = OptionsIter::new(bytes);
+    while let Some(opt) = iter.next_checked() {
+        if let Ok(value) = opt {
+            let (offset, len) = iter.span;
+            options.push(Spanned { value, offset, len });
+        }
+    }
+    Ok(options)
+}
+
+/// A value and the bytes it was parsed from
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub offset: usize,
+    /// Including the code and length fields
+    pub len: usize,
+}
+
+impl<T> Spanned<T> {
+    pub fn range(&self) -> ::std::ops::Range<usize> {
+        self.offset..self.offset + self.len
+    }
+}
+
+/// An option `parse_with_diagnostics` couldn't use
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseWarning {
+    /// Where the option starts in the bytes given
+    pub offset: usize,
+    pub code: u8,
+    /// The option's length field, if there was one
+    pub length: Option<u8>,
+    pub kind: WarningKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WarningKind {
+    /// A known option whose value couldn't be decoded. It was skipped and
+    /// parsing carried on after it.
+    Malformed,
+    /// The length field doesn't match the size the RFCs fix for the
+    /// option (only with `ParseConfig::strict`). It was skipped.
+    BadLength { expected: usize },
+    /// The option runs past the end of the buffer. Parsing stopped there.
+    Truncated,
+    /// The option would go over the `Limits` given. Parsing stopped there.
+    LimitExceeded,
+}
+
+impl ParseWarning {
+    fn kind_message(&self) -> String {
+        match self.kind {
+            WarningKind::Malformed => format!("Could not parse option {}", self.code),
+            WarningKind::BadLength { expected } => {
+                format!("Option {} has length {}, not {}", self.code, self.length.unwrap_or(0), expected)
+            },
+            WarningKind::Truncated if self.length.is_none() => format!("Option {} is truncated", self.code),
+            WarningKind::Truncated => format!("Option {} runs past the end of the buffer", self.code),
+            WarningKind::LimitExceeded => format!("Option {} goes over the parse limits", self.code),
+        }
+    }
+}
+
+impl ::std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        let action = match self.kind {
+            WarningKind::Malformed | WarningKind::BadLength { .. } => "skipped it",
+            WarningKind::Truncated | WarningKind::LimitExceeded => "stopped there",
+        };
+        write!(f, "{} at offset {}; {}", self.kind_message(), self.offset, action)
+    }
+}
+
+/// How many options there are up to `End`, going by the length fields
+/// alone, so the output can be allocated once
+fn option_count(bytes: &[u8]) -> usize {
+    let mut count = 0;
+    let mut remaining = bytes;
+    while let Some((opt, rest)) = next_raw_option(remaining) {
+        count += 1;
+        if opt.code == 255u8 {
+            break;
+        }
+        remaining = rest;
+    }
+    count
+}
+
+/// What to do with Pad (option 0)
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum PadHandling {
+    /// One `Pad` per byte, so the options encode back to the same bytes
+    #[default]
+    Keep,
+    /// One `Pad` for each run of them
+    Collapse,
+    /// Leave them out
+    Skip,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ParseConfig {
+    pub pads: PadHandling,
+    pub quirks: Quirks,
+    /// Reject options whose length field doesn't match the size the RFCs
+    /// fix for them, e.g. option 53 with a length other than 1, and
+    /// decode DNS names with `NameMode::Strict` rather than leniently
+    pub strict: bool,
+    pub limits: Limits,
+}
+
+/// Bounds on how much parsing a message may do, for callers that take
+/// messages from anyone. The default has none.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Limits {
+    /// Options other than Pad and End
+    pub max_options: usize,
+    /// 1 allows only plain options; 2 also allows options made of
+    /// suboptions, such as option 82
+    pub max_depth: usize,
+    /// The total length of the option values handed to decoders
+    pub max_decoded_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits { max_options: usize::MAX, max_depth: usize::MAX, max_decoded_bytes: usize::MAX }
+    }
+}
+
+/// How deeply the option with `code` nests: 2 for options made of
+/// suboptions, 1 for the rest
+fn nesting_depth(code: u8) -> usize {
+    match code {
+        63u8 | 82u8 | 220u8 => 2,
+        _ => 1,
+    }
+}
+
+static NO_QUIRKS: Quirks = Quirks {
+    nul_terminated: Vec::new(),
+    swapped_byte_order: Vec::new(),
+    lossy_utf8: Vec::new(),
+    lossy_utf8_all: false,
+    trust_length_field: false,
+};
+
+/// Lazily parses options one at a time, so callers can stop as soon as
+/// they have found what they need.
+///
+/// Iteration ends after `End`, as with `parse`. Known options that fail
+/// to parse, and an option that runs past the end of the buffer, are
+/// yielded as errors where `parse` silently drops them.
+pub struct OptionsIter<'a> {
+    remaining: Option<&'a [u8]>,
+    len: usize,
+    /// Offset and length of the last option parsed
+    span: (usize, usize),
+    quirks: &'a Quirks,
+    pads: PadHandling,
+    after_pad: bool,
+    strict: bool,
+    limits: Limits,
+    /// Options other than Pad and End, and their value bytes, so far
+    count: usize,
+    decoded: usize,
+}
+
+impl<'a> OptionsIter<'a> {
+    pub fn new(bytes: &'a [u8]) -> OptionsIter<'a> {
+        OptionsIter::with_quirks(bytes, &NO_QUIRKS)
+    }
+
+    pub fn with_quirks(bytes: &'a [u8], quirks: &'a Quirks) -> OptionsIter<'a> {
+        OptionsIter { remaining: Some(bytes), len: bytes.len(), span: (0, 0), quirks, pads: PadHandling::Keep, after_pad: false, strict: false,
+            limits: Limits::default(), count: 0, decoded: 0 }
+    }
+
+    pub fn pads(mut self, pads: PadHandling) -> OptionsIter<'a> {
+        self.pads = pads;
+        self
+    }
+
+    /// See `ParseConfig::strict`
+    pub fn strict(mut self, strict: bool) -> OptionsIter<'a> {
+        self.strict = strict;
+        self
+    }
+
+    /// Stop with an error at the first option that would go over `limits`
+    pub fn limits(mut self, limits: Limits) -> OptionsIter<'a> {
+        self.limits = limits;
+        self
+    }
+
+    fn warning(&self, unparsed: &[u8], kind: WarningKind) -> ParseWarning {
+        ParseWarning {
+            offset: self.len - unparsed.len(),
+            code: unparsed.first().cloned().unwrap_or(0u8),
+            length: unparsed.get(1).cloned(),
+            kind,
+        }
+    }
+
+    fn next_option(&mut self) -> Option<::std::result::Result<DhcpOption, ParseWarning>> {
+        let unparsed = self.remaining.take()?;
+        let (option, rest) = match split_option(unparsed) {
+            Some(split) => split,
+            None if unparsed.is_empty() => return None,
+            // A code without its length, or an option that runs past the
+            // end of the buffer
+            None => return Some(Err(self.warning(unparsed, WarningKind::Truncated))),
+        };
+        let (joined, rest) = join_fragments(option, rest);
+        // Options holding names always go through `decode_joined`, which
+        // decodes them strictly or leniently as this iterator does, and so
+        // does text that the quirks may take past 255 bytes
+        let joined = joined.or_else(|| match next_raw_option(option) {
+            Some((raw, _)) if holds_names(raw.code) || self.quirks.is_lossy_utf8(raw.code) => {
+                Some((raw.code, raw.data.to_vec()))
+            },
+            _ => None,
+        });
+        let next = if rest.is_empty() { None } else { Some(rest) };
+
+        // Pad and End have neither a length nor a value to check
+        if let Some(&[code, len]) = option.get(..2) {
+            let len = joined.as_ref().map_or(len as usize, |(_, data)| data.len());
+            self.count += 1;
+            self.decoded += len;
+            if self.count > self.limits.max_options
+                || self.decoded > self.limits.max_decoded_bytes
+                || nesting_depth(code) > self.limits.max_depth {
+                return Some(Err(self.warning(unparsed, WarningKind::LimitExceeded)));
+            }
+            if self.strict {
+                if let Some(expected) = fixed_length(code) {
+                    if len != expected {
+                        self.remaining = next;
+                        return Some(Err(self.warning(unparsed, WarningKind::BadLength { expected })));
                     }
-                },
+                }
             }
+        }
+
+        if let Some((code, data)) = joined {
+            self.remaining = next;
+            let mode = if self.strict { NameMode::Strict } else { NameMode::Lenient };
+            return match decode_joined(code, &data, self.quirks, mode) {
+                Some(opt) => {
+                    self.span = (self.len - unparsed.len(), unparsed.len() - rest.len());
+                    #[cfg(feature = "tracing")]
+                    ::tracing::trace!(code, offset = self.span.0, "parsed {:?}", opt);
+                    Some(Ok(opt))
+                },
+                None => Some(Err(self.warning(unparsed, WarningKind::Malformed))),
+            };
+        }
 
-            // If an option was successfully parsed
-            if let IResult::Done(rest, opt) = dhcp_option(unparsed) {
-                // If this is the end of options
-                if opt == DhcpOption::End || rest.len() == 0 {
-                    remaining = None;
-                } else {
-                    remaining = Some(rest);
+        // The next option always starts after this one's declared length.
+        // A decoder that stops short of it would lose the bytes it left,
+        // so unless the quirks say to trust the decoded part, the whole
+        // value is passed on undecoded.
+        match dhcp_option_with_quirks(option, self.quirks) {
+            Ok((left, opt)) => {
+                self.span = (self.len - unparsed.len(), option.len());
+                let opt = match (option, left.is_empty()) {
+                    (&[code, _, ref data @ ..], false) => DhcpOption::Unknown { code, data: data.to_vec() },
+                    _ => opt,
+                };
+                #[cfg(feature = "tracing")]
+                ::tracing::trace!(code = opt.code(), offset = self.span.0, "parsed {:?}", opt);
+                if opt != DhcpOption::End {
+                    self.remaining = next;
                 }
-                vec.push(opt);
-            } else {
-                // It's either an:
-                //   • error/invalid option
-                //   • option we don't know
-                // In either case, assume initially that there's nothing left we can parse
-                remaining = None;
-
-                // See if we can recover gracefully and continue parsing any remaining options
-                if unparsed.len() > 2 {
-                    // Skip this option but assume it's an option in the
-                    // standard format & parse the remaining options if possible
-                    let start_of_next_option: usize = 2 + (unparsed[1] as usize);
-
-                    // Sanity check the start of (any) remaning options are within
-                    // the bounds of remaining byte array
-                    if unparsed.len() > start_of_next_option {
-                        remaining = Some(&unparsed[start_of_next_option..]);
-                    }
+                Some(Ok(opt))
+            },
+            Err(_) => {
+                // It's an option we know but couldn't parse, so skip it
+                // and carry on with the next one
+                self.remaining = next;
+                Some(Err(self.warning(unparsed, WarningKind::Malformed)))
+            },
+        }
+    }
+}
+
+impl<'a> Iterator for OptionsIter<'a> {
+    type Item = Result<DhcpOption>;
+
+    fn next(&mut self) -> Option<Result<DhcpOption>> {
+        self.next_checked().map(|opt| opt.map_err(|w| match w.kind {
+            WarningKind::LimitExceeded => Error::CapacityExceeded,
+            _ => Error::ParseError(w.kind_message()),
+        }))
+    }
+}
+
+impl<'a> OptionsIter<'a> {
+    fn next_checked(&mut self) -> Option<::std::result::Result<DhcpOption, ParseWarning>> {
+        loop {
+            let option = self.next_option();
+            #[cfg(feature = "tracing")]
+            {
+                if let Some(Err(ref warning)) = option {
+                    ::tracing::debug!(code = warning.code, offset = warning.offset, "{}", warning);
                 }
             }
+            let is_pad = matches!(option, Some(Ok(DhcpOption::Pad)));
+            let after_pad = self.after_pad;
+            self.after_pad = is_pad;
+            match self.pads {
+                PadHandling::Skip if is_pad => continue,
+                PadHandling::Collapse if is_pad && after_pad => continue,
+                _ => return option,
+            }
+        }
+    }
+}
+
+/// The value length the RFCs fix for `code`, for options that have one
+pub(crate) fn fixed_length(code: u8) -> Option<usize> {
+    match code {
+        19u8 | 20u8 | 23u8 | 27u8 | 29u8 | 30u8 | 31u8 | 34u8 | 36u8 | 37u8 | 39u8 | 46u8
+            | 52u8 | 53u8 | 116u8 | 156u8 | 157u8 => Some(1),
+        13u8 | 22u8 | 26u8 | 57u8 => Some(2),
+        1u8 | 2u8 | 16u8 | 24u8 | 28u8 | 32u8 | 35u8 | 38u8 | 50u8 | 51u8 | 54u8 | 58u8 | 59u8
+            | 152u8 | 153u8 | 154u8 | 155u8 | 159u8 | 208u8 | 211u8 => Some(4),
+        123u8 => Some(16),
+        _ => None,
+    }
+}
+
+/// Splits the first option, code and length included, off `bytes`
+fn split_option(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (_, rest) = next_raw_option(bytes)?;
+    Some((bytes.get(..bytes.len() - rest.len())?, rest))
+}
+
+/// RFC 3396: options repeated back to back are fragments of one value.
+/// If `option` is followed by more with its code, gives the code and the
+/// joined value, with the bytes after the last fragment. Options with a
+/// fixed length aren't joined, as repeats of those are copies rather than
+/// fragments.
+fn join_fragments<'a>(option: &[u8], mut rest: &'a [u8]) -> (Option<(u8, Vec<u8>)>, &'a [u8]) {
+    let first = match next_raw_option(option) {
+        Some((first, _)) if first.code != 0u8 && first.code != 255u8 && fixed_length(first.code).is_none() => first,
+        _ => return (None, rest),
+    };
+    let mut joined: Option<Vec<u8>> = None;
+    while let Some((fragment, after)) = next_raw_option(rest) {
+        if fragment.code != first.code {
+            break;
         }
+        joined.get_or_insert_with(|| first.data.to_vec()).extend_from_slice(fragment.data);
+        rest = after;
+    }
+    (joined.map(|data| (first.code, data)), rest)
+}
+
+/// Options whose values hold DNS names
+fn holds_names(code: u8) -> bool {
+    matches!(code, 88u8 | 119u8 | 120u8 | 137u8 | 141u8)
+}
+
+/// Decodes a value joined by `join_fragments`, or any option holding
+/// names, with `quirks` applied and names decoded in `mode`
+fn decode_joined(code: u8, data: &[u8], quirks: &Quirks, mode: NameMode) -> Option<DhcpOption> {
+    let data = quirks.rewrite(code, data);
+    if data.len() > 255 || holds_names(code) {
+        return decode_long(code, &data, mode);
+    }
+    let mut wire = vec![code, data.len() as u8];
+    wire.extend_from_slice(&data);
+    dhcp_option(&wire).ok().map(|(_, opt)| opt)
+}
+
+/// Decodes a value too long for a single option, which only options
+/// holding lists, text, names or suboptions can have. Names are decoded
+/// in `mode`.
+fn decode_long(code: u8, data: &[u8], mode: NameMode) -> Option<DhcpOption> {
+    let text = || str::from_utf8(data).ok().map(str::to_owned);
+    let names = || decode_names(data, true, mode).ok();
+    let addrs = || many_ip_addrs(data);
+    Some(match code {
+        3u8 => Router(addrs()),
+        4u8 => TimeServer(addrs()),
+        5u8 => NameServer(addrs()),
+        6u8 => DomainNameServer(addrs()),
+        7u8 => LogServer(addrs()),
+        8u8 => CookieServer(addrs()),
+        9u8 => LprServer(addrs()),
+        10u8 => ImpressServer(addrs()),
+        11u8 => ResourceLocationServer(addrs()),
+        12u8 => HostName(text()?),
+        14u8 => MeritDumpFile(text()?),
+        15u8 => DomainName(text()?),
+        17u8 => RootPath(text()?),
+        18u8 => ExtensionsPath(text()?),
+        21u8 => PolicyFilter(ip_addr_pairs(data)),
+        25u8 => PathMtuPlateauTable(many_u16s(data)),
+        33u8 => StaticRoute(ip_addr_pairs(data)),
+        40u8 => NisDomain(text()?),
+        41u8 => NetworkInformationServers(addrs()),
+        42u8 => NtpServers(addrs()),
+        43u8 => VendorExtensions(data.to_vec()),
+        44u8 => NetBiosNameServers(addrs()),
+        45u8 => NetBiosDatagramDistributionServer(addrs()),
+        47u8 => NetBiosScope(text()?),
+        48u8 => XFontServer(addrs()),
+        49u8 => XDisplayManager(addrs()),
+        55u8 => ParamRequestList(data.to_vec()),
+        56u8 => Message(text()?),
+        60u8 => ClassIdentifier(data.to_vec()),
+        61u8 => ClientIdentifier(ClientId::from_bytes(data)?),
+        62u8 => NetwareIpDomain(text()?),
+        63u8 => NetwareIpInformation(netware_ip_suboptions(data).ok()?),
+        64u8 => NisPlusDomain(text()?),
+        65u8 => NisPlusServers(addrs()),
+        66u8 => TftpServerName(text()?),
+        67u8 => BootfileName(text()?),
+        68u8 => MobileIpHomeAgent(addrs()),
+        69u8 => SmtpServers(addrs()),
+        70u8 => Pop3Servers(addrs()),
+        71u8 => NntpServers(addrs()),
+        72u8 => WwwServers(addrs()),
+        73u8 => FingerServers(addrs()),
+        74u8 => IrcServers(addrs()),
+        75u8 => StreetTalkServers(addrs()),
+        76u8 => StdaServers(addrs()),
+        77u8 => UserClass(UserClasses::from_bytes(data)),
+        82u8 => RelayAgentInformation(relay_agent_suboptions(data).ok()?),
+        88u8 => BcmcsControllerDomains(names()?),
+        89u8 => BcmcsControllerAddresses(addrs()),
+        99u8 => CivicLocation(options::CivicLocation::from_bytes(data).ok()?),
+        100u8 => PCode(text()?),
+        101u8 => TCode(text()?),
+        112u8 => NetinfoParentServerAddress(addrs()),
+        113u8 => NetinfoParentServerTag(text()?),
+        117u8 => NameServiceSearch(many_u16s(data).into_iter().map(NameService::from).collect()),
+        119u8 => DomainSearch(names()?),
+        120u8 => SipServers(sip_servers_value(data, mode).ok()?),
+        136u8 => PanaAgents(addrs()),
+        137u8 => LostServer(single_name(data, mode).ok()?),
+        138u8 => CapwapAcAddresses(addrs()),
+        141u8 => SipUaConfigDomains(names()?),
+        150u8 => TftpServerAddresses(addrs()),
+        151u8 => {
+            let (&status, message) = data.split_first()?;
+            StatusCode { status: LeasequeryStatus::from(status), message: str::from_utf8(message).ok()?.to_owned() }
+        },
+        209u8 => PxelinuxConfigFile(text()?),
+        210u8 => PxelinuxPathPrefix(text()?),
+        212u8 => {
+            let (header, relays) = (data.get(..18)?, data.get(18..)?);
+            if relays.len() % 4 != 0 {
+                return None;
+            }
+            let (&ipv4_mask_len, rest) = header.split_first()?;
+            let (&prefix_len, prefix) = rest.split_first()?;
+            SixRd { ipv4_mask_len, prefix_len, prefix: slice_to_ipv6(prefix), border_relays: many_ip_addrs(relays) }
+        },
+        220u8 => {
+            let (flags, suboptions) = subnet_allocation(data).ok()?;
+            SubnetAllocation { flags, suboptions }
+        },
+        221u8 => VirtualSubnetSelection(options::VirtualSubnetSelection::from_bytes(data)),
+        252u8 => ProxyAutoDiscovery(text()?),
+        // Options with a fixed size, or a single name
+        1u8 | 2u8 | 13u8 | 16u8 | 19u8 | 20u8 | 22u8 | 23u8 | 24u8 | 26u8..=32u8 | 34u8..=39u8 | 46u8
+            | 50u8..=54u8 | 57u8..=59u8 | 116u8 | 123u8 | 152u8..=157u8 | 159u8 | 208u8
+            | 211u8 => return None,
+        _ => Unknown { code, data: data.to_vec() },
+    })
+}
+
+/// Whole values only; an odd byte at the end is ignored
+fn many_u16s(bytes: &[u8]) -> Vec<u16> {
+    bytes.chunks_exact(2).filter_map(|b| match b {
+        &[hi, lo] => Some(u16::from_be_bytes([hi, lo])),
+        _ => None,
+    }).collect()
+}
+
+/// Decodes `bytes`, which hold exactly one option as split off by
+/// `split_option`, with `quirks` applied
+fn dhcp_option_with_quirks<'a>(bytes: &'a [u8], quirks: &Quirks) -> IResult<&'a [u8], DhcpOption> {
+    match quirks.rewrite_option(bytes) {
+        Some(rewritten) => match dhcp_option(&rewritten) {
+            Ok((_, opt)) => Ok((&[][..], opt)),
+            Err(Err::Incomplete(n)) => Err(Err::Incomplete(n)),
+            // The error points into `rewritten`, so point it at the original
+            Err(e) => Err(Err::Error(Context::Code(bytes, e.into_error_kind()))),
+        },
+        None => match dhcp_option(bytes) {
+            Ok((_, opt)) if quirks.trust_length_field => Ok((&[][..], opt)),
+            other => other,
+        },
     }
-    Ok(vec)
 }
 
 fn u32_to_ip(a: u32) -> IpAddr {
     IpAddr::V4(Ipv4Addr::from(a))
 }
 
-fn many_ip_addrs(addrs: Vec<u32>) -> Vec<IpAddr> {
-    addrs.into_iter().map(|a| u32_to_ip(a)).collect()
+/// Any bytes past the first 16 are ignored, and missing ones are zero
+fn slice_to_ipv6(bytes: &[u8]) -> Ipv6Addr {
+    let mut octets = [0u8; 16];
+    for (octet, &b) in octets.iter_mut().zip(bytes) {
+        *octet = b;
+    }
+    Ipv6Addr::from(octets)
+}
+
+fn ip_addr(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes {
+        &[a, b, c, d] => Some(IpAddr::V4(Ipv4Addr::new(a, b, c, d))),
+        _ => None,
+    }
+}
+
+/// Whole addresses only; a partial one at the end is ignored
+fn many_ip_addrs(bytes: &[u8]) -> IpAddrList {
+    bytes.chunks_exact(4).filter_map(ip_addr).collect()
 }
 
-fn ip_addr_pairs(addrs: Vec<u32>) -> Vec<(IpAddr, IpAddr)> {
-    let (ips, masks): (Vec<_>, Vec<_>) = addrs.into_iter()
-                                              .map(|e| u32_to_ip(e))
-                                              .enumerate()
-                                              .partition(|&(i, _)| i % 2 == 0);
-    let ips: Vec<_> = ips.into_iter().map(|(_, v)| v).collect();
-    let masks: Vec<_> = masks.into_iter().map(|(_, v)| v).collect();
-    ips.into_iter()
-       .zip(masks.into_iter())
-       .collect()
+/// Whole pairs only; an address without its mask is ignored
+fn ip_addr_pairs(bytes: &[u8]) -> Vec<(IpAddr, IpAddr)> {
+    bytes.chunks_exact(8).filter_map(|p| Some((ip_addr(p.get(..4)?)?, ip_addr(p.get(4..)?)?))).collect()
 }
 
 fn num_u16s(bytes: &[u8]) -> IResult<&[u8], u8> {
-    match be_u8(bytes) {
-        IResult::Done(i, o) => IResult::Done(i, o / 2),
-        a => a,
-    }
+    be_u8(bytes).map(|(i, o)| (i, o / 2))
 }
 
 fn num_u32s(bytes: &[u8]) -> IResult<&[u8], u8> {
-    match be_u8(bytes) {
-        IResult::Done(i, o) => IResult::Done(i, o / 4),
-        a => a,
-    }
+    be_u8(bytes).map(|(i, o)| (i, o / 4))
 }
 
 macro_rules! ip_pairs(
@@ -122,7 +651,8 @@ macro_rules! ip_pairs(
         named!($name<&[u8], DhcpOption>,
             do_parse!(
                 tag!([$tag]) >>
-                addrs: length_count!(num_u32s, be_u32) >>
+                count: num_u32s >>
+                addrs: take!(count as usize * 4) >>
                 ({ $variant(ip_addr_pairs(addrs)) })
             )
         );
@@ -131,7 +661,9 @@ macro_rules! ip_pairs(
 
 /// A macro for the options that take the form
 ///
-///     [tag, length, ip_addr...]
+/// ```text
+/// [tag, length, ip_addr...]
+/// ```
 ///
 /// Since the only thing that really differs, is
 /// the tag and the Enum variant that is returned
@@ -140,7 +672,8 @@ macro_rules! many_ips(
         named!($name<&[u8], DhcpOption>,
             do_parse!(
                 tag!([$tag]) >>
-                addrs: length_count!(num_u32s, be_u32) >>
+                count: num_u32s >>
+                addrs: take!(count as usize * 4) >>
                 ({ $variant(many_ip_addrs(addrs)) })
             )
         );
@@ -149,7 +682,9 @@ macro_rules! many_ips(
 
 /// A macro for options that are of the form:
 ///
-///     [tag, length, somestring]
+/// ```text
+/// [tag, length, somestring]
+/// ```
 ///
 /// , since I haven't figured out a way to
 /// easily construct a parser to take the length
@@ -180,6 +715,19 @@ macro_rules! single_ip(
     )
 );
 
+macro_rules! single_u32(
+    ($name:ident, $tag:expr, $variant:expr) => (
+        named!($name<&[u8], DhcpOption>,
+            do_parse!(
+                tag!([$tag]) >>
+                _length: tag!([4u8]) >>
+                value: be_u32 >>
+                ({ $variant(value) })
+            )
+        );
+    )
+);
+
 macro_rules! bool(
     ($name:ident, $tag:expr, $variant:expr) => (
         named!($name<&[u8], DhcpOption>,
@@ -206,6 +754,20 @@ macro_rules! from_primitive(
     )
 );
 
+named!(pad<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([0u8]) >>
+        ({ Pad })
+    )
+);
+
+named!(end<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([255u8]) >>
+        ({ End })
+    )
+);
+
 single_ip!(subnet_mask, 1u8, SubnetMask);
 
 named!(time_offset<&[u8], DhcpOption>,
@@ -245,47 +807,10 @@ single_ip!(swap_server, 16u8, SwapServer);
 length_specific_string!(root_path, 17u8, RootPath);
 length_specific_string!(extensions_path, 18u8, ExtensionsPath);
 
-// COLLECT ALL OF THE ABOVE INTO ONE PARSER
-named!(vendor_extensions_rfc1497<&[u8], DhcpOption>, alt!(
-        do_parse!(
-            tag!([0u8]) >>
-            ({ Pad })
-        )
-        | do_parse!(
-            tag!([255u8]) >>
-            ({ End })
-        )
-        | subnet_mask
-        | time_offset
-        | router
-        | time_server
-        | name_server         // 5
-        | domain_name_server
-        | log_server
-        | cookie_server
-        | lpr_server
-        | impress_server      // 10
-        | resource_loc_server
-        | hostname
-        | boot_file_size
-        | merit_dump_file
-        | domain_name         // 15
-        | swap_server
-        | root_path
-        | extensions_path
-    )
-);
 
 bool!(ip_forwarding, 19u8, IPForwarding);
 bool!(non_source_local_routing, 20u8, NonLocalSourceRouting);
-// TODO
-/* named!(policy_filter<&[u8], DhcpOption>, */
-/*     do_parse!( */
-/*         tag!([21u8]) >> */
-/*         s: map!(sized_buffer, ip_addr_pairs) >> */
-/*         ({ PolicyFilter(s) }) */
-/*     ) */
-/* ); */
+ip_pairs!(policy_filter, 21u8, PolicyFilter);
 named!(max_datagram_reassembly_size<&[u8], DhcpOption>,
     do_parse!(
         tag!([22u8]) >>
@@ -318,17 +843,6 @@ named!(path_mtu_plateau_table<&[u8], DhcpOption>,
     )
 );
 
-// COLLECT
-named!(ip_layer_parameters_per_host<&[u8], DhcpOption>, alt!(
-          ip_forwarding
-        | non_source_local_routing      // 20
-        /* | policy_filter //TODO */
-        | max_datagram_reassembly_size
-        | default_ip_ttl
-        | path_mtu_aging_timeout
-        | path_mtu_plateau_table        // 25
-    )
-);
 
 named!(interface_mtu<&[u8], DhcpOption>,
     do_parse!(
@@ -346,18 +860,6 @@ bool!(perform_router_discovery, 31u8, PerformRouterDiscovery);
 single_ip!(router_solicitation_address, 32u8, RouterSolicitationAddress);
 ip_pairs!(static_route, 33u8, StaticRoute);
 
-// COLLECT
-named!(ip_layer_parameters_per_interface<&[u8], DhcpOption>, alt!(
-          interface_mtu
-        | all_subnets_are_local
-        | broadcast_address
-        | perform_mask_discovery
-        | mask_supplier                 // 30
-        | perform_router_discovery
-        | router_solicitation_address
-        | static_route
-    )
-);
 
 bool!(trailer_encapsulation, 34u8, TrailerEncapsulation);
 named!(arp_cache_timeout<&[u8], DhcpOption>,
@@ -370,13 +872,6 @@ named!(arp_cache_timeout<&[u8], DhcpOption>,
 );
 bool!(ethernet_encapsulation, 36u8, EthernetEncapsulation);
 
-// COLLECT
-named!(link_layer_parameters_per_interface<&[u8], DhcpOption>, alt!(
-          trailer_encapsulation
-        | arp_cache_timeout         // 35
-        | ethernet_encapsulation
-    )
-);
 
 named!(tcp_default_ttl<&[u8], DhcpOption>,
     do_parse!(
@@ -396,13 +891,6 @@ named!(tcp_keepalive_interval<&[u8], DhcpOption>,
 );
 bool!(tcp_keepalive_garbage, 39u8, TcpKeepaliveGarbage);
 
-// COLLECT
-named!(tcp_parameters<&[u8], DhcpOption>, alt!(
-          tcp_default_ttl
-        | tcp_keepalive_interval
-        | tcp_keepalive_garbage
-    )
-);
 
 length_specific_string!(nis_domain, 40u8, NisDomain);
 many_ips!(network_information_servers, 41u8, NetworkInformationServers);
@@ -428,20 +916,6 @@ length_specific_string!(net_bios_scope, 47u8, NetBiosScope);
 many_ips!(xfont_server, 48u8, XFontServer);
 many_ips!(xdisplay_manager, 49u8, XDisplayManager);
 
-// COLLECT
-named!(application_and_service_parameters<&[u8], DhcpOption>, alt!(
-          nis_domain                            // 40
-        | network_information_servers
-        | ntp_servers
-        | vendor_extensions
-        | net_bios_name_servers
-        | net_bios_datagram_distribution_server // 45
-        | net_bios_node_type
-        | net_bios_scope
-        | xfont_server
-        | xdisplay_manager
-    )
-);
 
 single_ip!(requested_ip_address, 50u8, RequestedIpAddress);
 named!(ip_address_lease_time<&[u8], DhcpOption>,
@@ -453,7 +927,14 @@ named!(ip_address_lease_time<&[u8], DhcpOption>,
     )
 );
 from_primitive!(option_overload, 52u8, OptionOverload);
-from_primitive!(message_type, 53u8, MessageType);
+named!(message_type<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([53u8]) >>
+        _length: tag!([1u8]) >>
+        value: be_u8 >>
+        ({ MessageType(options::DhcpMessageTypes::from(value)) })
+    )
+);
 single_ip!(server_identifier, 54u8, ServerIdentifier);
 named!(param_request_list<&[u8], DhcpOption>,
     do_parse!(
@@ -471,99 +952,444 @@ named!(max_message_size<&[u8], DhcpOption>,
         ({ MaxMessageSize(size_) })
     )
 );
+named!(renewal_time_value<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([58u8]) >>
+        _length: be_u8 >>
+        time: be_u32 >>
+        ({ RenewalTimeValue(time) })
+    )
+);
+named!(rebinding_time_value<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([59u8]) >>
+        _length: be_u8 >>
+        time: be_u32 >>
+        ({ RebindingTimeValue(time) })
+    )
+);
+named!(class_identifier<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([60u8]) >>
+        data: length_count!(be_u8, be_u8) >>
+        ({ ClassIdentifier(data) })
+    )
+);
+named!(client_identifier<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([61u8]) >>
+        id: map_opt!(sized_buffer, ClientId::from_bytes) >>
+        ({ ClientIdentifier(id) })
+    )
+);
+length_specific_string!(netware_ip_domain, 62u8, NetwareIpDomain);
+named!(netware_ip_information<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([63u8]) >>
+        suboptions: map_res!(sized_buffer, netware_ip_suboptions) >>
+        ({ NetwareIpInformation(suboptions) })
+    )
+);
+length_specific_string!(nis_plus_domain, 64u8, NisPlusDomain);
+many_ips!(nis_plus_servers, 65u8, NisPlusServers);
+length_specific_string!(tftp_server_name, 66u8, TftpServerName);
+length_specific_string!(bootfile_name, 67u8, BootfileName);
+// RFC 2132 allows this one to be empty, meaning there are no home agents
+many_ips!(mobile_ip_home_agent, 68u8, MobileIpHomeAgent);
+many_ips!(smtp_servers, 69u8, SmtpServers);
+many_ips!(pop3_servers, 70u8, Pop3Servers);
+many_ips!(nntp_servers, 71u8, NntpServers);
+many_ips!(www_servers, 72u8, WwwServers);
+many_ips!(finger_servers, 73u8, FingerServers);
+many_ips!(irc_servers, 74u8, IrcServers);
+many_ips!(street_talk_servers, 75u8, StreetTalkServers);
+many_ips!(stda_servers, 76u8, StdaServers);
 
-// COLLECT
-named!(dhcp_extensions<&[u8], DhcpOption>, alt!(
-          requested_ip_address  // 50
-        | ip_address_lease_time
-        | option_overload
-        | message_type
-        | server_identifier
-        | param_request_list    // 55
-        | message
-        | max_message_size
-/*         | renewal_time_value */
-/*         | rebinding_time_value */
-/*         | class_identifier      // 60 */
-/*         | client_identifier */
+
+named!(user_class<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([77u8]) >>
+        classes: map!(sized_buffer, UserClasses::from_bytes) >>
+        ({ UserClass(classes) })
     )
 );
 
-// Main parser
-named!(dhcp_option(&[u8]) -> DhcpOption, alt!(
-          vendor_extensions_rfc1497
-        | ip_layer_parameters_per_host
-        | ip_layer_parameters_per_interface
-        | link_layer_parameters_per_interface
-        | tcp_parameters
-        | application_and_service_parameters
-        | dhcp_extensions
-        | relay_agent_information_option_rfc3046
+named!(bcmcs_controller_domains<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([88u8]) >>
+        names: map_res!(sized_buffer, |d| decode_names(d, true, NameMode::Lenient)) >>
+        ({ BcmcsControllerDomains(names) })
     )
 );
+many_ips!(bcmcs_controller_addresses, 89u8, BcmcsControllerAddresses);
 
-#[cfg(test)] mod tests {
-    use options::DhcpOption;
-    use options::DhcpOption::{ Router };
-    use super::{ parse, router };
-    use nom::IResult;
-    use std::net::{IpAddr, Ipv4Addr};
+named!(civic_location<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([99u8]) >>
+        location: map_res!(sized_buffer, options::CivicLocation::from_bytes) >>
+        ({ CivicLocation(location) })
+    )
+);
 
-    #[test]
-    fn test_many_ip_addresses() {
-        let ips = vec![3u8,
-                       8,
-                       127, 0, 0, 1,
-                       192, 168, 1, 1,
-        ];
+length_specific_string!(pcode, 100u8, PCode);
+length_specific_string!(tcode, 101u8, TCode);
+many_ips!(netinfo_parent_server_address, 112u8, NetinfoParentServerAddress);
+length_specific_string!(netinfo_parent_server_tag, 113u8, NetinfoParentServerTag);
+named!(auto_configure<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([116u8]) >>
+        _length: tag!([1u8]) >>
+        value: be_u8 >>
+        ({ AutoConfigure(options::AutoConfigure::from(value)) })
+    )
+);
+named!(name_service_search<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([117u8]) >>
+        services: length_count!(num_u16s, map!(be_u16, NameService::from)) >>
+        ({ NameServiceSearch(services) })
+    )
+);
+named!(geoconf<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([123u8]) >>
+        location: map_res!(sized_buffer, GeoLocation::from_bytes) >>
+        ({ GeoConf(location) })
+    )
+);
 
-        match router(&ips) {
-            IResult::Done(i, o) => {
-                if i.len() > 0 {
-                    panic!("Remaining input was {:?}", i);
-                }
-                assert_eq!(o, Router(vec![IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
-                                          IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))]));
-            },
-            e => panic!("Result was {:?}", e),
-        }
-    }
+named!(domain_search<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([119u8]) >>
+        names: map_res!(sized_buffer, |d| decode_names(d, true, NameMode::Lenient)) >>
+        ({ DomainSearch(names) })
+    )
+);
 
-    #[test]
-    fn test_unknown_option() {
-        let option = vec![
-            0u8,
-            254u8, 4u8, 192u8, 168u8, 1u8, 1u8,
-            0u8,
-            255u8
-        ];
-        let expected: Vec<DhcpOption> = vec![
-            DhcpOption::Pad,
-            DhcpOption::Pad,
-            DhcpOption::End
-        ];
-        let actual = parse(&option).unwrap();
-        assert_eq!(expected, actual);
+fn sip_servers_value(bytes: &[u8], mode: NameMode) -> Result<options::SipServers> {
+    match bytes.split_first() {
+        Some((&0u8, names)) => Ok(options::SipServers::Names(decode_names(names, true, mode)?)),
+        Some((&1u8, addrs)) if addrs.len() % 4 == 0 => {
+            Ok(options::SipServers::Addresses(many_ip_addrs(addrs)))
+        },
+        _ => Err(Error::ParseError("Invalid SIP servers option".to_owned())),
     }
+}
 
-    #[test]
-    fn test_invalid_option_length_buffer_overrun() {
-        // Declare an options length that is longer
-        // than the remaning buffer
-        let option = vec![
-            0u8,
-            12u8, 10u8, 1u8,
-            255u8
-        ];
-        let expected: Vec<DhcpOption> = vec![
-            DhcpOption::Pad,
-        ];
-        let actual = parse(&option).unwrap();
-        assert_eq!(expected, actual);
+named!(sip_servers<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([120u8]) >>
+        servers: map_res!(sized_buffer, |d| sip_servers_value(d, NameMode::Lenient)) >>
+        ({ SipServers(servers) })
+    )
+);
+
+/// Option 137 holds a single uncompressed name
+fn single_name(bytes: &[u8], mode: NameMode) -> Result<String> {
+    match decode_name(bytes, 0, false, mode)? {
+        (name, end) if end == bytes.len() => Ok(name),
+        _ => Err(Error::ParseError("Trailing data after domain name".to_owned())),
     }
+}
 
-    #[test]
-    fn test_invalid_option_length_datatype_overflow() {
+many_ips!(pana_agents, 136u8, PanaAgents);
+named!(lost_server<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([137u8]) >>
+        name: map_res!(sized_buffer, |d| single_name(d, NameMode::Lenient)) >>
+        ({ LostServer(name) })
+    )
+);
+many_ips!(capwap_ac_addresses, 138u8, CapwapAcAddresses);
+named!(sip_ua_config_domains<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([141u8]) >>
+        names: map_res!(sized_buffer, |d| decode_names(d, true, NameMode::Lenient)) >>
+        ({ SipUaConfigDomains(names) })
+    )
+);
+many_ips!(tftp_server_addresses, 150u8, TftpServerAddresses);
+
+named!(status_code<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([151u8]) >>
+        length: verify!(be_u8, |l| l >= 1u8) >>
+        status: be_u8 >>
+        message: map_res!(take!(length - 1), str::from_utf8) >>
+        ({ StatusCode { status: LeasequeryStatus::from(status), message: message.to_owned() } })
+    )
+);
+single_u32!(base_time, 152u8, BaseTime);
+single_u32!(start_time_of_state, 153u8, StartTimeOfState);
+single_u32!(query_start_time, 154u8, QueryStartTime);
+single_u32!(query_end_time, 155u8, QueryEndTime);
+named!(dhcp_state<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([156u8]) >>
+        _length: tag!([1u8]) >>
+        state: be_u8 >>
+        ({ DhcpState(LeaseState::from(state)) })
+    )
+);
+named!(data_source<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([157u8]) >>
+        _length: tag!([1u8]) >>
+        flags: be_u8 >>
+        ({ DataSource { remote: flags & 0x01 != 0 } })
+    )
+);
+named!(port_params<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([159u8]) >>
+        _length: tag!([4u8]) >>
+        offset: be_u8 >>
+        psid_len: be_u8 >>
+        psid: be_u16 >>
+        ({ PortParams { offset, psid_len, psid } })
+    )
+);
+named!(pxelinux_magic<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([208u8]) >>
+        _length: tag!([4u8]) >>
+        magic: be_u32 >>
+        ({ PxelinuxMagic(magic.to_be_bytes()) })
+    )
+);
+length_specific_string!(pxelinux_config_file, 209u8, PxelinuxConfigFile);
+length_specific_string!(pxelinux_path_prefix, 210u8, PxelinuxPathPrefix);
+single_u32!(pxelinux_reboot_time, 211u8, PxelinuxRebootTime);
+named!(six_rd<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([212u8]) >>
+        length: verify!(be_u8, |l: u8| l >= 22u8 && (l - 18u8).is_multiple_of(4)) >>
+        ipv4_mask_len: be_u8 >>
+        prefix_len: be_u8 >>
+        prefix: take!(16) >>
+        relays: take!(length as usize - 18) >>
+        ({ SixRd { ipv4_mask_len, prefix_len, prefix: slice_to_ipv6(prefix), border_relays: many_ip_addrs(relays) } })
+    )
+);
+named!(subnet_allocation_option<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([220u8]) >>
+        value: map_res!(sized_buffer, subnet_allocation) >>
+        ({ SubnetAllocation { flags: value.0, suboptions: value.1 } })
+    )
+);
+named!(virtual_subnet_selection<&[u8], DhcpOption>,
+    do_parse!(
+        tag!([221u8]) >>
+        data: sized_buffer >>
+        ({ VirtualSubnetSelection(options::VirtualSubnetSelection::from_bytes(data)) })
+    )
+);
+length_specific_string!(proxy_auto_discovery, 252u8, ProxyAutoDiscovery);
+
+// Anything we don't have a parser for is kept as it is
+named!(unknown<&[u8], DhcpOption>,
+    do_parse!(
+        code: be_u8 >>
+        data: length_count!(be_u8, be_u8) >>
+        ({ Unknown { code, data } })
+    )
+);
+
+// Main parser: dispatch on the code byte rather than trying each
+// option's tag in turn
+fn dhcp_option(bytes: &[u8]) -> IResult<&[u8], DhcpOption> {
+    let code = match bytes.first() {
+        Some(code) => *code,
+        None => return Err(Err::Incomplete(Needed::Size(1))),
+    };
+    match code {
+        0u8 => pad(bytes),
+        1u8 => subnet_mask(bytes),
+        2u8 => time_offset(bytes),
+        3u8 => router(bytes),
+        4u8 => time_server(bytes),
+        5u8 => name_server(bytes),
+        6u8 => domain_name_server(bytes),
+        7u8 => log_server(bytes),
+        8u8 => cookie_server(bytes),
+        9u8 => lpr_server(bytes),
+        10u8 => impress_server(bytes),
+        11u8 => resource_loc_server(bytes),
+        12u8 => hostname(bytes),
+        13u8 => boot_file_size(bytes),
+        14u8 => merit_dump_file(bytes),
+        15u8 => domain_name(bytes),
+        16u8 => swap_server(bytes),
+        17u8 => root_path(bytes),
+        18u8 => extensions_path(bytes),
+        19u8 => ip_forwarding(bytes),
+        20u8 => non_source_local_routing(bytes),
+        21u8 => policy_filter(bytes),
+        22u8 => max_datagram_reassembly_size(bytes),
+        23u8 => default_ip_ttl(bytes),
+        24u8 => path_mtu_aging_timeout(bytes),
+        25u8 => path_mtu_plateau_table(bytes),
+        26u8 => interface_mtu(bytes),
+        27u8 => all_subnets_are_local(bytes),
+        28u8 => broadcast_address(bytes),
+        29u8 => perform_mask_discovery(bytes),
+        30u8 => mask_supplier(bytes),
+        31u8 => perform_router_discovery(bytes),
+        32u8 => router_solicitation_address(bytes),
+        33u8 => static_route(bytes),
+        34u8 => trailer_encapsulation(bytes),
+        35u8 => arp_cache_timeout(bytes),
+        36u8 => ethernet_encapsulation(bytes),
+        37u8 => tcp_default_ttl(bytes),
+        38u8 => tcp_keepalive_interval(bytes),
+        39u8 => tcp_keepalive_garbage(bytes),
+        40u8 => nis_domain(bytes),
+        41u8 => network_information_servers(bytes),
+        42u8 => ntp_servers(bytes),
+        43u8 => vendor_extensions(bytes),
+        44u8 => net_bios_name_servers(bytes),
+        45u8 => net_bios_datagram_distribution_server(bytes),
+        46u8 => net_bios_node_type(bytes),
+        47u8 => net_bios_scope(bytes),
+        48u8 => xfont_server(bytes),
+        49u8 => xdisplay_manager(bytes),
+        50u8 => requested_ip_address(bytes),
+        51u8 => ip_address_lease_time(bytes),
+        52u8 => option_overload(bytes),
+        53u8 => message_type(bytes),
+        54u8 => server_identifier(bytes),
+        55u8 => param_request_list(bytes),
+        56u8 => message(bytes),
+        57u8 => max_message_size(bytes),
+        58u8 => renewal_time_value(bytes),
+        59u8 => rebinding_time_value(bytes),
+        60u8 => class_identifier(bytes),
+        61u8 => client_identifier(bytes),
+        62u8 => netware_ip_domain(bytes),
+        63u8 => netware_ip_information(bytes),
+        64u8 => nis_plus_domain(bytes),
+        65u8 => nis_plus_servers(bytes),
+        66u8 => tftp_server_name(bytes),
+        67u8 => bootfile_name(bytes),
+        68u8 => mobile_ip_home_agent(bytes),
+        69u8 => smtp_servers(bytes),
+        70u8 => pop3_servers(bytes),
+        71u8 => nntp_servers(bytes),
+        72u8 => www_servers(bytes),
+        73u8 => finger_servers(bytes),
+        74u8 => irc_servers(bytes),
+        75u8 => street_talk_servers(bytes),
+        76u8 => stda_servers(bytes),
+        77u8 => user_class(bytes),
+        82u8 => relay_agent_information_option_rfc3046(bytes),
+        88u8 => bcmcs_controller_domains(bytes),
+        89u8 => bcmcs_controller_addresses(bytes),
+        99u8 => civic_location(bytes),
+        100u8 => pcode(bytes),
+        101u8 => tcode(bytes),
+        112u8 => netinfo_parent_server_address(bytes),
+        113u8 => netinfo_parent_server_tag(bytes),
+        116u8 => auto_configure(bytes),
+        117u8 => name_service_search(bytes),
+        123u8 => geoconf(bytes),
+        119u8 => domain_search(bytes),
+        120u8 => sip_servers(bytes),
+        136u8 => pana_agents(bytes),
+        137u8 => lost_server(bytes),
+        138u8 => capwap_ac_addresses(bytes),
+        141u8 => sip_ua_config_domains(bytes),
+        150u8 => tftp_server_addresses(bytes),
+        151u8 => status_code(bytes),
+        152u8 => base_time(bytes),
+        153u8 => start_time_of_state(bytes),
+        154u8 => query_start_time(bytes),
+        155u8 => query_end_time(bytes),
+        156u8 => dhcp_state(bytes),
+        157u8 => data_source(bytes),
+        159u8 => port_params(bytes),
+        208u8 => pxelinux_magic(bytes),
+        209u8 => pxelinux_config_file(bytes),
+        210u8 => pxelinux_path_prefix(bytes),
+        211u8 => pxelinux_reboot_time(bytes),
+        212u8 => six_rd(bytes),
+        220u8 => subnet_allocation_option(bytes),
+        221u8 => virtual_subnet_selection(bytes),
+        252u8 => proxy_auto_discovery(bytes),
+        255u8 => end(bytes),
+        _ => unknown(bytes),
+    }
+}
+
+#[cfg(test)] mod tests {
+    use options::{self, DhcpOption, SipServers, NameService, LeasequeryStatus, LeaseState, UserClasses};
+    use options::{CivicAddressElement, CivicWhat, CaType};
+    use options::DhcpOption::{ Router };
+    use super::{ parse, parse_with_quirks, router, OptionsIter, PadHandling };
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    /// Parse samples of one option separated by Pad, so they aren't
+    /// joined as RFC 3396 fragments
+    fn parse_samples(option: &[u8]) -> Vec<DhcpOption> {
+        parse(option).unwrap().into_iter().filter(|o| *o != DhcpOption::Pad).collect()
+    }
+
+    #[test]
+    fn test_many_ip_addresses() {
+        let ips = vec![3u8,
+                       8,
+                       127, 0, 0, 1,
+                       192, 168, 1, 1,
+        ];
+
+        match router(&ips) {
+            Ok((i, o)) => {
+                if !i.is_empty() {
+                    panic!("Remaining input was {:?}", i);
+                }
+                assert_eq!(o, Router(ip_list![IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+                                          IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))]));
+            },
+            e => panic!("Result was {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_unknown_option() {
+        let option = vec![
+            0u8,
+            254u8, 4u8, 192u8, 168u8, 1u8, 1u8,
+            0u8,
+            255u8
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::Pad,
+            DhcpOption::Unknown { code: 254u8, data: vec![192u8, 168u8, 1u8, 1u8] },
+            DhcpOption::Pad,
+            DhcpOption::End
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_invalid_option_length_buffer_overrun() {
+        // Declare an options length that is longer
+        // than the remaning buffer
+        let option = vec![
+            0u8,
+            12u8, 10u8, 1u8,
+            255u8
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::Pad,
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_invalid_option_length_datatype_overflow() {
         // A option length at or near the bounds of the u8 datatype
         // could cause an overflow of any u8's that we're using for
         // length calculations, so we test how we handle that here
@@ -628,6 +1454,26 @@ named!(dhcp_option(&[u8]) -> DhcpOption, alt!(
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_option_021_policy_filter() {
+        let option = vec![
+            21u8,
+            16u8,
+            10u8, 0u8, 0u8, 0u8,
+            255u8, 0u8, 0u8, 0u8,
+            192u8, 168u8, 1u8, 0u8,
+            255u8, 255u8, 255u8, 0u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::PolicyFilter(vec![
+                (IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)), IpAddr::V4(Ipv4Addr::new(255, 0, 0, 0))),
+                (IpAddr::V4(Ipv4Addr::new(192, 168, 1, 0)), IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+            ])
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn test_option_050_requested_ip_address() {
         let option = vec![
@@ -665,17 +1511,18 @@ named!(dhcp_option(&[u8]) -> DhcpOption, alt!(
     #[test]
     fn test_option_053_dhcp_message_type() {
         use options::DhcpMessageTypes;
-        // Parse all known options
-        let options = vec![
-            vec![ 53u8, 1u8, 1u8 ], // Discover
-            vec![ 53u8, 1u8, 2u8 ], // Offer
-            vec![ 53u8, 1u8, 3u8 ], // Request
-            vec![ 53u8, 1u8, 4u8 ], // Decline
-            vec![ 53u8, 1u8, 5u8 ], // Ack
-            vec![ 53u8, 1u8, 6u8 ], // Nak
-            vec![ 53u8, 1u8, 7u8 ], // Release
-        ];
-        let message_types = vec![
+        // Parse all known message types, and one we don't know
+        let options = [
+            [ 53u8, 1u8, 1u8 ], // Discover
+            [ 53u8, 1u8, 2u8 ], // Offer
+            [ 53u8, 1u8, 3u8 ], // Request
+            [ 53u8, 1u8, 4u8 ], // Decline
+            [ 53u8, 1u8, 5u8 ], // Ack
+            [ 53u8, 1u8, 6u8 ], // Nak
+            [ 53u8, 1u8, 7u8 ], // Release
+            [ 53u8, 1u8, 8u8 ],
+        ];
+        let message_types = [
             DhcpMessageTypes::Discover,
             DhcpMessageTypes::Offer,
             DhcpMessageTypes::Request,
@@ -683,13 +1530,821 @@ named!(dhcp_option(&[u8]) -> DhcpOption, alt!(
             DhcpMessageTypes::Ack,
             DhcpMessageTypes::Nak,
             DhcpMessageTypes::Release,
+            DhcpMessageTypes::Other(8),
         ];
         for (index, expected) in message_types.iter().enumerate() {
-            if let &DhcpOption::MessageType(ref actual) = parse(&options[index]).unwrap().first().unwrap() {
+            if let DhcpOption::MessageType(actual) = parse(&options[index]).unwrap().first().unwrap() {
                 assert_eq!(expected, actual);
             } else {
                 panic!("Failed to parse MessageType: {:?}", expected);
             }
         }
     }
+
+    #[test]
+    fn test_option_058_059_renewal_and_rebinding_time() {
+        let option = vec![
+            58u8, 4u8, 0u8, 0u8, 2u8, 88u8,
+            59u8, 4u8, 0u8, 0u8, 4u8, 26u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::RenewalTimeValue(600),
+            DhcpOption::RebindingTimeValue(1050),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_060_class_identifier() {
+        let option = vec![
+            60u8,
+            8u8,
+            77u8, 83u8, 70u8, 84u8, 32u8, 53u8, 46u8, 48u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::ClassIdentifier(b"MSFT 5.0".to_vec())
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_061_client_identifier() {
+        use options::ClientId;
+        use hwaddr::{HardwareAddr, MacAddress};
+        let option = vec![
+            61u8, 7u8, 1u8, 0u8, 11u8, 130u8, 1u8, 252u8, 66u8, 0u8,
+            61u8, 9u8, 255u8, 0u8, 0u8, 0u8, 1u8, 0u8, 3u8, 0u8, 1u8, 0u8,
+            61u8, 4u8, 0u8, 104u8, 111u8, 115u8, 0u8,
+            61u8, 0u8,
+        ];
+        let mut options = OptionsIter::new(&option).pads(PadHandling::Skip);
+        assert_eq!(options.next().unwrap().unwrap(), DhcpOption::ClientIdentifier(
+            ClientId::Hardware(HardwareAddr::from_mac(MacAddress([0u8, 11u8, 130u8, 1u8, 252u8, 66u8])))
+        ));
+        assert_eq!(options.next().unwrap().unwrap(), DhcpOption::ClientIdentifier(
+            ClientId::Duid { iaid: 1, duid: vec![0u8, 3u8, 0u8, 1u8] }
+        ));
+        assert_eq!(options.next().unwrap().unwrap(), DhcpOption::ClientIdentifier(
+            ClientId::Other { id_type: 0u8, data: b"hos".to_vec() }
+        ));
+        assert!(options.next().unwrap().is_err());
+        assert!(options.next().is_none());
+    }
+
+    #[test]
+    fn test_option_062_063_netware_ip() {
+        use options::NetwareIpSubOption;
+        let option = vec![
+            62u8, 4u8, 99u8, 111u8, 114u8, 112u8,
+            63u8, 5u8, 2u8, 0u8, 8u8, 1u8, 3u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::NetwareIpDomain("corp".to_string()),
+            DhcpOption::NetwareIpInformation(vec![
+                NetwareIpSubOption::ExistsInOptionsArea,
+                NetwareIpSubOption::AutoRetries(3),
+            ]),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_064_065_nis_plus() {
+        let option = vec![
+            64u8, 4u8, 99u8, 111u8, 114u8, 112u8,
+            65u8, 8u8, 10u8, 0u8, 0u8, 1u8, 10u8, 0u8, 0u8, 2u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::NisPlusDomain("corp".to_string()),
+            DhcpOption::NisPlusServers(ip_list![
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+            ]),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_066_067_tftp_server_and_bootfile_name() {
+        let option = vec![
+            66u8, 8u8, 49u8, 48u8, 46u8, 48u8, 46u8, 48u8, 46u8, 49u8,
+            67u8, 10u8, 112u8, 120u8, 101u8, 108u8, 105u8, 110u8, 117u8, 120u8, 46u8, 48u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::TftpServerName("10.0.0.1".to_string()),
+            DhcpOption::BootfileName("pxelinux.0".to_string()),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_068_mobile_ip_home_agent() {
+        let option = vec![
+            68u8, 4u8, 10u8, 0u8, 0u8, 1u8, 0u8,
+            68u8, 0u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::MobileIpHomeAgent(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]),
+            DhcpOption::MobileIpHomeAgent(ip_list![]),
+        ];
+        let actual = parse_samples(&option);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_069_to_076_application_servers() {
+        let option = vec![
+            69u8, 4u8, 10u8, 0u8, 0u8, 69u8,
+            70u8, 4u8, 10u8, 0u8, 0u8, 70u8,
+            71u8, 4u8, 10u8, 0u8, 0u8, 71u8,
+            72u8, 4u8, 10u8, 0u8, 0u8, 72u8,
+            73u8, 4u8, 10u8, 0u8, 0u8, 73u8,
+            74u8, 4u8, 10u8, 0u8, 0u8, 74u8,
+            75u8, 4u8, 10u8, 0u8, 0u8, 75u8,
+            76u8, 4u8, 10u8, 0u8, 0u8, 76u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::SmtpServers(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 69))]),
+            DhcpOption::Pop3Servers(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 70))]),
+            DhcpOption::NntpServers(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 71))]),
+            DhcpOption::WwwServers(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 72))]),
+            DhcpOption::FingerServers(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 73))]),
+            DhcpOption::IrcServers(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 74))]),
+            DhcpOption::StreetTalkServers(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 75))]),
+            DhcpOption::StdaServers(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 76))]),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_077_user_class() {
+        // RFC 3004 form: two length-prefixed classes
+        let option = vec![
+            77u8,
+            7u8,
+            2u8, 65u8, 66u8,
+            3u8, 67u8, 68u8, 69u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::UserClass(UserClasses::Rfc3004(vec![vec![65u8, 66u8], vec![67u8, 68u8, 69u8]]))
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+
+        // Flat form: a single bare class string
+        let option = vec![
+            77u8,
+            4u8,
+            84u8, 101u8, 115u8, 116u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::UserClass(UserClasses::Flat(vec![84u8, 101u8, 115u8, 116u8]))
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_088_089_bcmcs_controllers() {
+        let option = vec![
+            88u8, 13u8,
+            3u8, 99u8, 111u8, 109u8, 0u8,
+            1u8, 97u8, 0xc0u8, 0u8,
+            1u8, 98u8, 0xc0u8, 0u8,
+            89u8, 4u8, 10u8, 0u8, 0u8, 1u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::BcmcsControllerDomains(vec!["com".to_string(), "a.com".to_string(), "b.com".to_string()]),
+            DhcpOption::BcmcsControllerAddresses(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_099_civic_location() {
+        let option = vec![
+            99u8, 17u8, 0u8, b'U', b'S',
+            1u8, 2u8, b'C', b'A',
+            3u8, 8u8, b'S', b'a', b'n', b' ', b'J', b'o', b's', b'e',
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::CivicLocation(options::CivicLocation {
+                what: CivicWhat::DhcpServer,
+                country_code: "US".to_string(),
+                elements: vec![
+                    CivicAddressElement { ca_type: CaType::A1, value: "CA".to_string() },
+                    CivicAddressElement { ca_type: CaType::A3, value: "San Jose".to_string() },
+                ],
+            }),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+
+        assert_eq!(parse(&[99u8, 2u8, 0u8, b'U']).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_option_100_101_timezone() {
+        let mut option = vec![100u8, 22u8];
+        option.extend_from_slice(b"EST5EDT,M3.2.0,M11.1.0");
+        option.extend_from_slice(&[101u8, 16u8]);
+        option.extend_from_slice(b"America/New_York");
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::PCode("EST5EDT,M3.2.0,M11.1.0".to_string()),
+            DhcpOption::TCode("America/New_York".to_string()),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_112_113_netinfo() {
+        let option = vec![
+            112u8, 4u8, 17u8, 254u8, 0u8, 1u8,
+            113u8, 7u8, b'n', b'e', b't', b'w', b'o', b'r', b'k',
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::NetinfoParentServerAddress(ip_list![IpAddr::V4(Ipv4Addr::new(17, 254, 0, 1))]),
+            DhcpOption::NetinfoParentServerTag("network".to_string()),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_116_auto_configure() {
+        let option = vec![116u8, 1u8, 1u8, 116u8, 1u8, 0u8, 116u8, 1u8, 7u8];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::AutoConfigure(options::AutoConfigure::AutoConfigure),
+            DhcpOption::AutoConfigure(options::AutoConfigure::DoNotAutoConfigure),
+            DhcpOption::AutoConfigure(options::AutoConfigure::Other(7)),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_117_name_service_search() {
+        let option = vec![117u8, 8u8, 0u8, 65u8, 0u8, 6u8, 0u8, 44u8, 1u8, 0u8];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::NameServiceSearch(vec![
+                NameService::NisPlus,
+                NameService::DomainNameServer,
+                NameService::NetBios,
+                NameService::Other(256),
+            ]),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_119_domain_search() {
+        let option = vec![
+            119u8,
+            13u8,
+            3u8, 99u8, 111u8, 109u8, 0u8,
+            1u8, 97u8, 0xc0u8, 0u8,
+            1u8, 98u8, 0xc0u8, 5u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::DomainSearch(vec!["com".to_string(), "a.com".to_string(), "b.a.com".to_string()])
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_120_sip_servers() {
+        let option = vec![
+            120u8, 5u8, 0u8, 1u8, 97u8, 0u8, 0u8, 0u8,
+            120u8, 5u8, 1u8, 10u8, 0u8, 0u8, 1u8, 0u8,
+            120u8, 4u8, 1u8, 10u8, 0u8, 0u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::SipServers(SipServers::Names(vec!["a".to_string(), "".to_string()])),
+            DhcpOption::SipServers(SipServers::Addresses(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))])),
+        ];
+        let actual = parse_samples(&option);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_120_sip_servers_compressed() {
+        // Pointers count from the first name, after the encoding byte
+        let option = vec![
+            120u8, 25u8, 0u8,
+            3u8, 115u8, 105u8, 112u8, 7u8, 101u8, 120u8, 97u8, 109u8, 112u8, 108u8, 101u8, 3u8, 99u8, 111u8, 109u8, 0u8,
+            4u8, 115u8, 105u8, 112u8, 50u8, 0xc0u8, 4u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::SipServers(SipServers::Names(vec!["sip.example.com".to_string(), "sip2.example.com".to_string()])),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_123_geoconf() {
+        let option = vec![
+            123u8, 16u8,
+            0x0bu8, 0xbcu8, 0x49u8, 0x37u8, 0x4cu8,
+            0x09u8, 0x2eu8, 0x6eu8, 0x14u8, 0x7bu8,
+            0x1au8, 0x80u8, 0x00u8, 0x0du8, 0x33u8,
+            0x41u8,
+            123u8, 2u8, 0u8, 0u8,
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(actual.len(), 1);
+        match actual[0] {
+            DhcpOption::GeoConf(ref location) => {
+                assert_eq!(location.latitude, -1136052404);
+                assert_eq!(location.longitude, 5073933435);
+                assert_eq!(location.altitude, 3379);
+            },
+            ref other => panic!("Expected GeoConf, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_option_136_to_138_access_controllers() {
+        let option = vec![
+            136u8, 4u8, 10u8, 0u8, 0u8, 1u8,
+            137u8, 5u8, 3u8, 99u8, 111u8, 109u8, 0u8, 0u8,
+            137u8, 6u8, 3u8, 99u8, 111u8, 109u8, 0u8, 0u8,
+            138u8, 8u8, 10u8, 0u8, 0u8, 2u8, 10u8, 0u8, 0u8, 3u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::PanaAgents(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]),
+            DhcpOption::LostServer("com".to_string()),
+            DhcpOption::CapwapAcAddresses(ip_list![
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3)),
+            ]),
+        ];
+        let actual = parse_samples(&option);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_141_sip_ua_config_domains() {
+        let option = vec![
+            141u8, 21u8,
+            7u8, 101u8, 120u8, 97u8, 109u8, 112u8, 108u8, 101u8, 3u8, 99u8, 111u8, 109u8, 0u8,
+            5u8, 118u8, 111u8, 105u8, 99u8, 101u8, 0xc0u8, 0u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::SipUaConfigDomains(vec!["example.com".to_string(), "voice.example.com".to_string()]),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_150_tftp_server_addresses() {
+        let option = vec![150u8, 8u8, 10u8, 1u8, 1u8, 5u8, 10u8, 1u8, 1u8, 6u8];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::TftpServerAddresses(ip_list![
+                IpAddr::V4(Ipv4Addr::new(10, 1, 1, 5)),
+                IpAddr::V4(Ipv4Addr::new(10, 1, 1, 6)),
+            ]),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_151_to_157_bulk_leasequery() {
+        let option = vec![
+            151u8, 5u8, 4u8, 110u8, 111u8, 112u8, 101u8, 0u8,
+            151u8, 1u8, 0u8, 0u8,
+            151u8, 0u8,
+            152u8, 4u8, 0x5fu8, 0x5eu8, 0x10u8, 0x00u8,
+            153u8, 4u8, 0u8, 0u8, 0x0eu8, 0x10u8,
+            154u8, 4u8, 0x5fu8, 0x5du8, 0x00u8, 0x00u8,
+            155u8, 4u8, 0x5fu8, 0x5eu8, 0x00u8, 0x00u8,
+            156u8, 1u8, 2u8,
+            157u8, 1u8, 1u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::StatusCode { status: LeasequeryStatus::NotAllowed, message: "nope".to_string() },
+            DhcpOption::StatusCode { status: LeasequeryStatus::Success, message: "".to_string() },
+            DhcpOption::BaseTime(0x5f5e1000),
+            DhcpOption::StartTimeOfState(3600),
+            DhcpOption::QueryStartTime(0x5f5d0000),
+            DhcpOption::QueryEndTime(0x5f5e0000),
+            DhcpOption::DhcpState(LeaseState::Active),
+            DhcpOption::DataSource { remote: true },
+        ];
+        let actual = parse_samples(&option);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_159_port_params() {
+        let option = vec![159u8, 4u8, 6u8, 8u8, 0x34u8, 0x00u8, 159u8, 3u8, 6u8, 8u8, 0x34u8];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::PortParams { offset: 6, psid_len: 8, psid: 0x3400 },
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_208_to_211_pxelinux() {
+        let mut option = vec![208u8, 4u8, 0xf1u8, 0x00u8, 0x74u8, 0x7eu8, 209u8, 7u8];
+        option.extend_from_slice(b"default");
+        option.extend_from_slice(&[210u8, 15u8]);
+        option.extend_from_slice(b"/tftpboot/pxe1/");
+        option.extend_from_slice(&[211u8, 4u8, 0u8, 0u8, 1u8, 44u8]);
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::PxelinuxMagic([0xf1, 0x00, 0x74, 0x7e]),
+            DhcpOption::PxelinuxConfigFile("default".to_string()),
+            DhcpOption::PxelinuxPathPrefix("/tftpboot/pxe1/".to_string()),
+            DhcpOption::PxelinuxRebootTime(300),
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_212_six_rd() {
+        let option = vec![
+            212u8, 22u8, 8u8, 32u8,
+            0x20u8, 0x01u8, 0x0du8, 0xb8u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8,
+            192u8, 0u8, 2u8, 1u8, 0u8,
+            212u8, 20u8, 8u8, 32u8,
+            0x20u8, 0x01u8, 0x0du8, 0xb8u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8,
+            192u8, 0u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::SixRd {
+                ipv4_mask_len: 8,
+                prefix_len: 32,
+                prefix: Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0),
+                border_relays: ip_list![IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))],
+            },
+        ];
+        let actual = parse_samples(&option);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_220_subnet_allocation() {
+        use options::{SubnetAllocationSubOption, SubnetPrefix};
+        let option = vec![220u8, 9u8, 0x01u8, 1u8, 6u8, 0u8, 29u8, 192u8, 0u8, 2u8, 8u8];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::SubnetAllocation {
+                flags: 0x01,
+                suboptions: vec![SubnetAllocationSubOption::SubnetInformation {
+                    flags: 0,
+                    prefixes: vec![SubnetPrefix { prefix_len: 29, prefix: Ipv4Addr::new(192, 0, 2, 8) }],
+                }],
+            },
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_221_virtual_subnet_selection() {
+        use options::VirtualSubnetSelection;
+        let option = vec![221u8, 5u8, 0u8, b'b', b'l', b'u', b'e', 0u8, 221u8, 1u8, 255u8];
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::VirtualSubnetSelection(VirtualSubnetSelection::VrfName("blue".to_string())),
+            DhcpOption::VirtualSubnetSelection(VirtualSubnetSelection::Global),
+        ];
+        let actual = parse_samples(&option);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_option_252_proxy_auto_discovery() {
+        let mut option = vec![252u8, 32u8];
+        option.extend_from_slice(b"http://wpad.example.com/wpad.dat");
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::ProxyAutoDiscovery("http://wpad.example.com/wpad.dat".to_string())
+        ];
+        let actual = parse(&option).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_parse_with_quirks() {
+        use quirks::Quirks;
+        let option = vec![
+            12u8, 5u8, 84u8, 101u8, 115u8, 116u8, 0u8,  // NUL-terminated hostname
+            57u8, 2u8, 220u8, 5u8,                      // little-endian 1500
+            51u8, 6u8, 0u8, 0u8, 4u8, 176u8, 0u8, 0u8,  // oversized lease time
+            15u8, 3u8, 108u8, 0xe0u8, 110u8,            // Latin-1 domain name
+            53u8, 1u8, 1u8,
+        ];
+        let quirks = Quirks {
+            nul_terminated: vec![12u8],
+            swapped_byte_order: vec![57u8],
+            lossy_utf8: vec![15u8],
+            lossy_utf8_all: false,
+            trust_length_field: true,
+        };
+        let expected: Vec<DhcpOption> = vec![
+            DhcpOption::HostName("Test".to_string()),
+            DhcpOption::MaxMessageSize(1500),
+            DhcpOption::IpAddressLeaseTime(1200),
+            DhcpOption::DomainName("l\u{fffd}n".to_string()),
+            DhcpOption::MessageType(::options::DhcpMessageTypes::Discover),
+        ];
+        let actual = parse_with_quirks(&option, &quirks).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_parse_lossy_utf8_all() {
+        use quirks::Quirks;
+        let quirks = Quirks { lossy_utf8_all: true, ..Quirks::default() };
+        let mut option = vec![12u8, 255u8];
+        option.extend_from_slice(&[0xffu8; 255]);
+        option.extend_from_slice(&[53u8, 1u8, 1u8]);
+        // The replacement characters take the name past 255 bytes, and
+        // none of them are cut off
+        assert_eq!(parse_with_quirks(&option, &quirks).unwrap(), vec![
+            DhcpOption::HostName("\u{fffd}".repeat(255)),
+            DhcpOption::MessageType(::options::DhcpMessageTypes::Discover),
+        ]);
+        // Without the quirk the name doesn't decode and is skipped
+        assert_eq!(parse(&option).unwrap(), vec![DhcpOption::MessageType(::options::DhcpMessageTypes::Discover)]);
+    }
+
+    #[test]
+    fn test_parse_skips_declared_length() {
+        use quirks::Quirks;
+        // A subnet mask with a fifth byte that happens to be an option code
+        let option = [1u8, 5u8, 255u8, 255u8, 255u8, 0u8, 53u8, 12u8, 1u8, 1u8];
+        assert_eq!(parse(&option).unwrap(), vec![
+            DhcpOption::Unknown { code: 1u8, data: vec![255u8, 255u8, 255u8, 0u8, 53u8] },
+            DhcpOption::HostName("\u{1}".to_string()),
+        ]);
+        let quirks = Quirks { trust_length_field: true, ..Quirks::default() };
+        assert_eq!(parse_with_quirks(&option, &quirks).unwrap(), vec![
+            DhcpOption::SubnetMask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+            DhcpOption::HostName("\u{1}".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_options_iter() {
+        let option = vec![
+            53u8, 1u8, 1u8,
+            254u8, 1u8, 0u8,
+            53u8, 2u8, 1u8, 0u8,    // wrong length
+            12u8, 10u8, 1u8,    // runs past the end
+        ];
+        let mut iter = OptionsIter::new(&option);
+        match iter.next() {
+            Some(Ok(DhcpOption::MessageType(_))) => {},
+            e => panic!("Result was {:?}", e),
+        }
+        match iter.next() {
+            Some(Ok(DhcpOption::Unknown { code: 254u8, .. })) => {},
+            e => panic!("Result was {:?}", e),
+        }
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn test_short_address_lists_are_inline() {
+        match parse(&[6u8, 8u8, 10u8, 0u8, 0u8, 1u8, 10u8, 0u8, 0u8, 2u8]).unwrap().first() {
+            Some(DhcpOption::DomainNameServer(addrs)) => assert_eq!((addrs.len(), addrs.spilled()), (2, false)),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_options_iter_stays_within_each_option() {
+        // A subnet mask with no value mustn't take the router's bytes
+        let option = vec![
+            1u8, 0u8,
+            3u8, 4u8, 10u8, 0u8, 0u8, 1u8,
+        ];
+        let mut iter = OptionsIter::new(&option);
+        assert!(iter.next().unwrap().is_err());
+        assert_eq!(iter.next().unwrap().unwrap(), DhcpOption::Router(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_parse_joins_fragments() {
+        // Split through an address, as RFC 3396 allows
+        let option = vec![
+            3u8, 2u8, 10u8, 0u8,
+            3u8, 6u8, 0u8, 1u8, 10u8, 0u8, 0u8, 2u8,
+            12u8, 2u8, b'h', b'o',
+            12u8, 2u8, b's', b't',
+            53u8, 1u8, 1u8,
+            53u8, 1u8, 1u8,
+        ];
+        let expected: Vec<DhcpOption> = vec![
+            Router(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))]),
+            DhcpOption::HostName("host".to_string()),
+            // Repeats of fixed-length options are copies
+            DhcpOption::MessageType(options::DhcpMessageTypes::Discover),
+            DhcpOption::MessageType(options::DhcpMessageTypes::Discover),
+        ];
+        assert_eq!(parse(&option).unwrap(), expected);
+
+        let mut long = Vec::new();
+        for _ in 0..2 {
+            long.extend_from_slice(&[15u8, 200u8]);
+            long.extend_from_slice(&[b'a'; 200]);
+        }
+        assert_eq!(parse(&long).unwrap(), vec![DhcpOption::DomainName("a".repeat(400))]);
+    }
+
+    #[test]
+    fn test_parse_with_diagnostics() {
+        use super::{parse_with_diagnostics, ParseWarning, WarningKind};
+        let option = vec![
+            53u8, 1u8, 1u8,
+            53u8, 2u8, 1u8, 0u8,    // wrong length
+            254u8, 1u8, 0u8,
+            12u8, 10u8, 1u8,    // runs past the end
+        ];
+        let (options, warnings) = parse_with_diagnostics(&option);
+        assert_eq!(options.len(), 2);
+        assert_eq!(warnings, vec![
+            ParseWarning { offset: 3, code: 53u8, length: Some(2u8), kind: WarningKind::Malformed },
+            ParseWarning { offset: 10, code: 12u8, length: Some(10u8), kind: WarningKind::Truncated },
+        ]);
+        assert_eq!(warnings[0].to_string(), "Could not parse option 53 at offset 3; skipped it");
+        assert_eq!(warnings[1].to_string(), "Option 12 runs past the end of the buffer at offset 10; stopped there");
+    }
+
+    #[test]
+    fn test_parse_spanned() {
+        use super::{parse_spanned, Spanned};
+        let option = vec![
+            0u8,
+            53u8, 1u8, 1u8,
+            53u8, 2u8, 1u8, 0u8,    // wrong length
+            12u8, 1u8, b'a',
+            255u8,
+        ];
+        let options = parse_spanned(&option).unwrap();
+        assert_eq!(options, vec![
+            Spanned { value: DhcpOption::Pad, offset: 0, len: 1 },
+            Spanned { value: DhcpOption::MessageType(options::DhcpMessageTypes::Discover), offset: 1, len: 3 },
+            Spanned { value: DhcpOption::HostName("a".to_string()), offset: 8, len: 3 },
+            Spanned { value: DhcpOption::End, offset: 11, len: 1 },
+        ]);
+        assert_eq!(&option[options[2].range()], &[12u8, 1u8, b'a']);
+    }
+
+    #[test]
+    fn test_parse_into() {
+        use super::parse_into;
+        let mut out = vec![DhcpOption::Pad; 8];
+        let capacity = out.capacity();
+        parse_into(&[53u8, 1u8, 1u8, 255u8], &mut out).unwrap();
+        assert_eq!(out, vec![DhcpOption::MessageType(options::DhcpMessageTypes::Discover), DhcpOption::End]);
+        assert_eq!(out.capacity(), capacity);
+        parse_into(&[], &mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_parse_single_option() {
+        use super::parse_single_option;
+        let bytes = [0u8, 53u8, 1u8, 5u8, 1u8, 2u8, 255u8, 255u8, 254u8, 1u8, 7u8, 255u8, 0u8];
+        let (pad, rest) = parse_single_option(&bytes).unwrap();
+        assert_eq!(pad, DhcpOption::Pad);
+        let (message_type, rest) = parse_single_option(rest).unwrap();
+        assert_eq!(message_type, DhcpOption::MessageType(options::DhcpMessageTypes::Ack));
+        // A subnet mask two bytes short
+        assert!(parse_single_option(rest).is_err());
+        let (unknown, rest) = parse_single_option(&rest[4..]).unwrap();
+        assert_eq!(unknown, DhcpOption::Unknown { code: 254u8, data: vec![7u8] });
+        let (end, rest) = parse_single_option(rest).unwrap();
+        assert_eq!(end, DhcpOption::End);
+        assert_eq!(rest, &[0u8]);
+        assert!(parse_single_option(&[]).is_err());
+        assert!(parse_single_option(&[12u8, 4u8, b'h']).is_err());
+    }
+
+    #[test]
+    fn test_parse_after_cookie() {
+        use super::{find_magic_cookie, parse_after_cookie};
+        let vendor = [0u8, 0u8, 99u8, 130u8, 83u8, 99u8, 53u8, 1u8, 3u8, 255u8];
+        assert_eq!(find_magic_cookie(&vendor), Some(2));
+        let (offset, parsed) = parse_after_cookie(&vendor).unwrap();
+        assert_eq!(offset, 2);
+        assert_eq!(parsed, vec![DhcpOption::MessageType(options::DhcpMessageTypes::Request), DhcpOption::End]);
+        assert_eq!(find_magic_cookie(&[99u8, 130u8, 83u8]), None);
+        assert!(parse_after_cookie(&[53u8, 1u8, 3u8, 255u8]).is_err());
+    }
+
+    #[test]
+    fn test_strict_lengths() {
+        use super::{parse_with_config, ParseConfig};
+        let option = vec![
+            53u8, 2u8, 5u8, 0u8,
+            1u8, 5u8, 255u8, 255u8, 255u8, 0u8, 0u8,
+            51u8, 4u8, 0u8, 0u8, 14u8, 16u8,
+            3u8, 4u8, 10u8, 0u8, 0u8, 1u8,
+        ];
+        // Without strict, the oversized subnet mask is kept undecoded
+        assert_eq!(parse(&option).unwrap(), vec![
+            DhcpOption::Unknown { code: 1u8, data: vec![255u8, 255u8, 255u8, 0u8, 0u8] },
+            DhcpOption::IpAddressLeaseTime(3600),
+            DhcpOption::Router(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]),
+        ]);
+
+        let strict = ParseConfig { strict: true, ..ParseConfig::default() };
+        assert_eq!(parse_with_config(&option, &strict).unwrap(), vec![
+            DhcpOption::IpAddressLeaseTime(3600),
+            DhcpOption::Router(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]),
+        ]);
+        let mut iter = OptionsIter::new(&option).strict(true);
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().unwrap().is_err());
+        assert_eq!(iter.count(), 2);
+    }
+
+    #[test]
+    fn test_strict_names() {
+        use super::{parse_with_config, ParseConfig};
+        let option = vec![
+            // Both cut off before the root label
+            119u8, 4u8, 3u8, b'c', b'o', b'm',
+            137u8, 4u8, 3u8, b'c', b'o', b'm',
+            141u8, 5u8, 3u8, b'c', b'o', b'm', 0u8,
+        ];
+        assert_eq!(parse(&option).unwrap(), vec![
+            DhcpOption::DomainSearch(vec!["com".to_string()]),
+            DhcpOption::LostServer("com".to_string()),
+            DhcpOption::SipUaConfigDomains(vec!["com".to_string()]),
+        ]);
+
+        let strict = ParseConfig { strict: true, ..ParseConfig::default() };
+        assert_eq!(parse_with_config(&option, &strict).unwrap(), vec![
+            DhcpOption::SipUaConfigDomains(vec!["com".to_string()]),
+        ]);
+    }
+
+    #[test]
+    fn test_limits() {
+        use super::{Limits, ParseConfig, parse_with_config};
+        use Error;
+        let option = vec![
+            0u8,
+            51u8, 4u8, 0u8, 0u8, 14u8, 16u8,
+            82u8, 4u8, 1u8, 2u8, 0u8, 1u8,
+            3u8, 4u8, 10u8, 0u8, 0u8, 1u8,
+            255u8,
+        ];
+        let with_limits = |limits| parse_with_config(&option, &ParseConfig { limits, ..ParseConfig::default() });
+        let exceeded = |limits| matches!(with_limits(limits), Err(Error::CapacityExceeded));
+        assert_eq!(with_limits(Limits::default()).unwrap().len(), 5);
+        assert_eq!(with_limits(Limits { max_options: 3, ..Limits::default() }).unwrap().len(), 5);
+        assert!(exceeded(Limits { max_options: 2, ..Limits::default() }));
+        assert!(exceeded(Limits { max_depth: 1, ..Limits::default() }));
+        assert!(exceeded(Limits { max_decoded_bytes: 11, ..Limits::default() }));
+
+        let limits = Limits { max_options: 1, ..Limits::default() };
+        let mut iter = OptionsIter::new(&option).limits(limits);
+        assert_eq!(iter.next().unwrap().unwrap(), DhcpOption::Pad);
+        assert_eq!(iter.next().unwrap().unwrap(), DhcpOption::IpAddressLeaseTime(3600));
+        match iter.next() {
+            Some(Err(Error::CapacityExceeded)) => {},
+            e => panic!("Result was {:?}", e),
+        }
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_pad_handling() {
+        use super::{parse_with_config, ParseConfig, PadHandling};
+        let option = vec![0u8, 0u8, 0u8, 53u8, 1u8, 1u8, 0u8, 0u8, 255u8];
+        let parse_pads = |pads| parse_with_config(&option, &ParseConfig { pads, ..ParseConfig::default() }).unwrap();
+
+        assert_eq!(parse_pads(PadHandling::Keep), parse(&option).unwrap());
+        assert_eq!(parse_pads(PadHandling::Collapse), vec![
+            DhcpOption::Pad,
+            DhcpOption::MessageType(::options::DhcpMessageTypes::Discover),
+            DhcpOption::Pad,
+            DhcpOption::End,
+        ]);
+        assert_eq!(parse_pads(PadHandling::Skip), vec![
+            DhcpOption::MessageType(::options::DhcpMessageTypes::Discover),
+            DhcpOption::End,
+        ]);
+    }
 }
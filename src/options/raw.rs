@@ -0,0 +1,297 @@
+// Everything else walks options with these, so they must not panic on
+// any input; see `parse_untrusted`.
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+use options::fixed_length;
+#[cfg(feature = "heapless")]
+use { Result, Error };
+#[cfg(feature = "heapless")]
+use heapless;
+#[cfg(feature = "bumpalo")]
+use bumpalo::Bump;
+#[cfg(feature = "bumpalo")]
+use bumpalo::collections::Vec as BumpVec;
+
+/// An option that has only been split out of the options area, not decoded.
+///
+/// `data` borrows the value bytes straight from the input buffer, so no
+/// allocation is needed to produce one. `Pad` and `End` are represented
+/// by their codes (0 and 255) with empty `data`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RawOption<'a> {
+    pub code: u8,
+    pub data: &'a [u8],
+}
+
+/// Split the next option off the front of `bytes`.
+///
+/// Returns `None` when there is nothing left or when the next option's
+/// length field runs past the end of the buffer, which is the same point
+/// at which `options::parse` gives up.
+pub fn next_raw_option<'a>(bytes: &'a [u8]) -> Option<(RawOption<'a>, &'a [u8])> {
+    let (&code, rest) = bytes.split_first()?;
+    if code == 0u8 || code == 255u8 {
+        return Some((RawOption { code, data: &[] }, rest));
+    }
+    let (&len, rest) = rest.split_first()?;
+    let data = rest.get(..len as usize)?;
+    let rest = rest.get(len as usize..)?;
+    Some((RawOption { code, data }, rest))
+}
+
+/// Iterator over the `(code, data)` pairs in an options area, with no
+/// decoding and no allocation. Stops after `End`, or where an option runs
+/// past the end of the buffer.
+#[derive(Debug, Clone)]
+pub struct RawOptionsIter<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> RawOptionsIter<'a> {
+    pub fn new(bytes: &'a [u8]) -> RawOptionsIter<'a> {
+        RawOptionsIter { remaining: bytes }
+    }
+}
+
+impl<'a> Iterator for RawOptionsIter<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let (opt, rest) = next_raw_option(self.remaining)?;
+        self.remaining = if opt.code == 255u8 { &[] } else { rest };
+        Some((opt.code, opt.data))
+    }
+}
+
+/// How much of the options area one option takes up
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OptionSize {
+    pub code: u8,
+    /// Length of the value, summed over all fragments
+    pub data_len: usize,
+    /// Bytes used on the wire, including every code and length byte
+    pub wire_size: usize,
+    /// Number of back-to-back options with the code. Values longer than
+    /// 255 bytes are split across several (RFC 3396).
+    pub fragments: usize,
+}
+
+/// The size of each option in `bytes`, in order.
+///
+/// Options repeated back to back are fragments of one value and are
+/// counted together, the way `parse` joins them; the same code further
+/// on is another option, as is a repeat of a code with a fixed length,
+/// e.g. a second Server Identifier. `Pad` isn't counted. Stops after `End`, so
+/// padding after it isn't counted either.
+pub fn option_sizes(bytes: &[u8]) -> Vec<OptionSize> {
+    let mut sizes: Vec<OptionSize> = Vec::new();
+    let mut previous = None;
+    let mut remaining = bytes;
+    while let Some((opt, rest)) = next_raw_option(remaining) {
+        let wire_size = remaining.len() - rest.len();
+        match sizes.last_mut() {
+            _ if opt.code == 0u8 => {},
+            Some(size) if previous == Some(opt.code) && fixed_length(opt.code).is_none() => {
+                size.data_len += opt.data.len();
+                size.wire_size += wire_size;
+                size.fragments += 1;
+            },
+            _ => sizes.push(OptionSize {
+                code: opt.code,
+                data_len: opt.data.len(),
+                wire_size,
+                fragments: 1,
+            }),
+        }
+        if opt.code == 255u8 {
+            break;
+        }
+        previous = Some(opt.code);
+        remaining = rest;
+    }
+    sizes
+}
+
+/// Where the `End` option is in `bytes`, or `None` if the options run
+/// out first, which usually means the message was cut off. `parse`
+/// accepts either.
+pub fn end_offset(bytes: &[u8]) -> Option<usize> {
+    let mut remaining = bytes;
+    while let Some((opt, rest)) = next_raw_option(remaining) {
+        if opt.code == 255u8 {
+            return Some(bytes.len() - remaining.len());
+        }
+        remaining = rest;
+    }
+    None
+}
+
+/// Whatever follows the `End` option in `bytes` other than Pad, from the
+/// first such byte to the last. Empty if there is no End or only Pad
+/// follows it; anything else there is corruption or someone hiding data.
+pub fn trailing_data(bytes: &[u8]) -> &[u8] {
+    let after_end = match end_offset(bytes).and_then(|end| bytes.get(end + 1..)) {
+        Some(after_end) => after_end,
+        None => return &[],
+    };
+    let start = after_end.iter().position(|&b| b != 0u8).unwrap_or(after_end.len());
+    let end = after_end.iter().rposition(|&b| b != 0u8).map_or(start, |last| last + 1);
+    after_end.get(start..end).unwrap_or(&[])
+}
+
+/// Counterpart to `options::parse` that doesn't allocate, for callers
+/// with a fixed memory budget per message. This is not a `no_std` mode:
+/// the crate as a whole needs `std` and an allocator, so it can't be
+/// built for targets without them.
+///
+/// Clears `out` and fills it with the undecoded options found in `bytes`,
+/// stopping after `End`. Option values are left as byte slices; decoding
+/// them into `DhcpOption`s would require allocating. Fails with
+/// `Error::CapacityExceeded` if there are more than `N` options.
+#[cfg(feature = "heapless")]
+pub fn parse_into_heapless<'a, const N: usize>(bytes: &'a [u8],
+                                               out: &mut heapless::Vec<RawOption<'a>, N>)
+                                               -> Result<()> {
+    out.clear();
+    let mut remaining = bytes;
+    while let Some((opt, rest)) = next_raw_option(remaining) {
+        if out.push(opt).is_err() {
+            return Err(Error::CapacityExceeded);
+        }
+        if opt.code == 255u8 {
+            break;
+        }
+        remaining = rest;
+    }
+    Ok(())
+}
+
+/// Split `bytes` into options, stopping after `End`, with the list and a
+/// copy of each value allocated in `bump`. They outlive `bytes`, so the
+/// receive buffer can be reused straight away, and are all freed at once
+/// when the arena is reset.
+///
+/// This only splits: nothing is decoded, and there is no arena-backed
+/// `DhcpOption`. Decoding one of these options allocates its strings and
+/// vectors from the global allocator as usual.
+#[cfg(feature = "bumpalo")]
+pub fn split_in<'b>(bytes: &[u8], bump: &'b Bump) -> BumpVec<'b, RawOption<'b>> {
+    let mut out = BumpVec::new_in(bump);
+    let mut remaining = bytes;
+    while let Some((opt, rest)) = next_raw_option(remaining) {
+        out.push(RawOption { code: opt.code, data: bump.alloc_slice_copy(opt.data) });
+        if opt.code == 255u8 {
+            break;
+        }
+        remaining = rest;
+    }
+    out
+}
+
+#[cfg(test)] mod tests {
+    use super::{next_raw_option, option_sizes, end_offset, trailing_data, OptionSize, RawOption, RawOptionsIter};
+
+    #[test]
+    fn test_next_raw_option() {
+        let bytes = [0u8, 53u8, 1u8, 1u8, 255u8];
+        let (pad, rest) = next_raw_option(&bytes).unwrap();
+        assert_eq!(pad, RawOption { code: 0u8, data: &[] });
+        let (msg_type, rest) = next_raw_option(rest).unwrap();
+        assert_eq!(msg_type, RawOption { code: 53u8, data: &[1u8] });
+        let (end, rest) = next_raw_option(rest).unwrap();
+        assert_eq!(end, RawOption { code: 255u8, data: &[] });
+        assert_eq!(next_raw_option(rest), None);
+    }
+
+    #[test]
+    fn test_next_raw_option_buffer_overrun() {
+        let bytes = [12u8, 10u8, 1u8];
+        assert_eq!(next_raw_option(&bytes), None);
+    }
+
+    #[test]
+    fn test_raw_options_iter() {
+        let bytes = [0u8, 53u8, 1u8, 1u8, 12u8, 2u8, b'p', b'c', 255u8, 6u8, 4u8];
+        let options: Vec<(u8, &[u8])> = RawOptionsIter::new(&bytes).collect();
+        assert_eq!(options, vec![
+            (0u8, &[][..]),
+            (53u8, &[1u8][..]),
+            (12u8, &b"pc"[..]),
+            (255u8, &[][..]),
+        ]);
+        assert_eq!(RawOptionsIter::new(&[12u8, 10u8, 1u8]).next(), None);
+    }
+
+    #[test]
+    fn test_option_sizes() {
+        let mut bytes = vec![53u8, 1u8, 1u8, 0u8, 43u8, 255u8];
+        bytes.extend_from_slice(&[7u8; 255]);
+        bytes.extend_from_slice(&[43u8, 2u8, 7u8, 7u8, 0u8, 43u8, 1u8, 7u8, 255u8, 0u8, 0u8]);
+        assert_eq!(option_sizes(&bytes), vec![
+            OptionSize { code: 53u8, data_len: 1, wire_size: 3, fragments: 1 },
+            OptionSize { code: 43u8, data_len: 257, wire_size: 261, fragments: 2 },
+            // Not back to back with the others, so a separate option
+            OptionSize { code: 43u8, data_len: 1, wire_size: 3, fragments: 1 },
+            OptionSize { code: 255u8, data_len: 0, wire_size: 1, fragments: 1 },
+        ]);
+        // Repeats of a fixed-length option are copies, not fragments
+        let server = OptionSize { code: 54u8, data_len: 4, wire_size: 6, fragments: 1 };
+        assert_eq!(option_sizes(&[54u8, 4u8, 10u8, 0u8, 0u8, 1u8, 54u8, 4u8, 10u8, 0u8, 0u8, 2u8]), vec![server, server]);
+    }
+
+    #[test]
+    fn test_end_offset() {
+        assert_eq!(end_offset(&[53u8, 1u8, 1u8, 0u8, 255u8, 0u8]), Some(4));
+        assert_eq!(end_offset(&[255u8]), Some(0));
+        assert_eq!(end_offset(&[53u8, 1u8, 1u8, 0u8]), None);
+        assert_eq!(end_offset(&[53u8, 1u8, 1u8, 12u8, 10u8, 255u8]), None);
+    }
+
+    #[test]
+    fn test_trailing_data() {
+        assert_eq!(trailing_data(&[53u8, 1u8, 1u8, 255u8, 0u8, 0u8]), &[] as &[u8]);
+        assert_eq!(trailing_data(&[53u8, 1u8, 1u8, 255u8, 0u8, b'h', 0u8, b'i', 0u8, 0u8]), &[b'h', 0u8, b'i']);
+        assert_eq!(trailing_data(&[53u8, 1u8, 1u8, 0u8, 7u8]), &[] as &[u8]);
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn test_parse_into_heapless() {
+        use heapless;
+        use super::parse_into_heapless;
+        use Error;
+
+        let bytes = [53u8, 1u8, 1u8, 0u8, 255u8, 3u8, 4u8];
+        let mut out: heapless::Vec<RawOption, 4> = heapless::Vec::new();
+        parse_into_heapless(&bytes, &mut out).unwrap();
+        assert_eq!(&out[..], &[
+            RawOption { code: 53u8, data: &[1u8] },
+            RawOption { code: 0u8, data: &[] },
+            RawOption { code: 255u8, data: &[] },
+        ]);
+
+        let mut small: heapless::Vec<RawOption, 2> = heapless::Vec::new();
+        match parse_into_heapless(&bytes, &mut small) {
+            Err(Error::CapacityExceeded) => {},
+            e => panic!("Result was {:?}", e),
+        }
+    }
+
+    #[cfg(feature = "bumpalo")]
+    #[test]
+    fn test_split_in() {
+        use bumpalo::Bump;
+        use super::split_in;
+
+        let bump = Bump::new();
+        let options = {
+            let bytes = vec![53u8, 1u8, 1u8, 12u8, 2u8, b'p', b'c', 255u8, 7u8];
+            split_in(&bytes, &bump)
+        };
+        assert_eq!(&options[..], &[
+            RawOption { code: 53u8, data: &[1u8] },
+            RawOption { code: 12u8, data: b"pc" },
+            RawOption { code: 255u8, data: &[] },
+        ]);
+    }
+}
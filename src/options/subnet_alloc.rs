@@ -0,0 +1,146 @@
+//! Option 220, subnet allocation (RFC 6656)
+//!
+//! A relay asks for, and the server hands out, whole subnets. The option
+//! is a flags byte followed by suboptions.
+
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+use { Result, Error };
+use std::net::Ipv4Addr;
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct SubnetPrefix {
+    pub prefix_len: u8,
+    pub prefix: Ipv4Addr,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum SubnetAllocationSubOption {
+    /// Suboption 1, the subnets requested or allocated
+    SubnetInformation { flags: u8, prefixes: Vec<SubnetPrefix> },
+    /// Suboption 2, a name for the subnet, e.g. for a pool
+    SubnetName(String),
+    /// A suboption we don't know, or one we couldn't decode
+    Unknown { code: u8, data: Vec<u8> },
+}
+use self::SubnetAllocationSubOption::*;
+
+impl SubnetAllocationSubOption {
+    fn from_code_and_data(code: u8, data: &[u8]) -> SubnetAllocationSubOption {
+        match (code, data.split_first()) {
+            (1u8, Some((&flags, prefixes))) if prefixes.len() % 5 == 0 => SubnetInformation {
+                flags,
+                prefixes: prefixes.chunks_exact(5).filter_map(|p| match p {
+                    &[prefix_len, a, b, c, d] => Some(SubnetPrefix { prefix_len, prefix: Ipv4Addr::new(a, b, c, d) }),
+                    _ => None,
+                }).collect(),
+            },
+            (2u8, _) => match ::std::str::from_utf8(data) {
+                Ok(name) => SubnetName(name.to_string()),
+                Err(_) => Unknown { code, data: data.to_vec() },
+            },
+            _ => Unknown { code, data: data.to_vec() },
+        }
+    }
+
+    fn code_and_data(&self) -> (u8, Vec<u8>) {
+        match *self {
+            SubnetInformation { flags, ref prefixes } => {
+                let mut data = vec![flags];
+                for prefix in prefixes {
+                    data.push(prefix.prefix_len);
+                    data.extend_from_slice(&prefix.prefix.octets());
+                }
+                (1u8, data)
+            },
+            SubnetName(ref name) => (2u8, name.as_bytes().to_vec()),
+            Unknown { code, ref data } => (code, data.clone()),
+        }
+    }
+
+    /// Append the wire form of this suboption to `buf`. Values longer
+    /// than 255 bytes are truncated.
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        let (code, data) = self.code_and_data();
+        let len = ::std::cmp::min(data.len(), 255);
+        buf.push(code);
+        buf.push(len as u8);
+        buf.extend(data.iter().take(len));
+    }
+}
+
+/// Split the value of option 220 into its flags and suboptions
+pub fn subnet_allocation(bytes: &[u8]) -> Result<(u8, Vec<SubnetAllocationSubOption>)> {
+    let (&flags, mut data) = match bytes.split_first() {
+        Some(split) => split,
+        None => return Err(Error::ParseError("Empty subnet allocation option".to_string())),
+    };
+    #[cfg(feature = "tracing")]
+    let _span = ::tracing::trace_span!("subnet_allocation", flags).entered();
+    let mut suboptions = Vec::new();
+    while let Some((&code, rest)) = data.split_first() {
+        let (value, rest) = match rest.split_first() {
+            Some((&len, rest)) if rest.len() >= len as usize => rest.split_at(len as usize),
+            _ => return Err(Error::ParseError(format!("Truncated subnet allocation suboption {}", code))),
+        };
+        let suboption = SubnetAllocationSubOption::from_code_and_data(code, value);
+        #[cfg(feature = "tracing")]
+        ::tracing::trace!(code, "parsed {:?}", suboption);
+        suboptions.push(suboption);
+        data = rest;
+    }
+    Ok((flags, suboptions))
+}
+
+#[cfg(test)] mod tests {
+    use super::{subnet_allocation, SubnetPrefix};
+    use super::SubnetAllocationSubOption::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_subnet_allocation() {
+        let data = [
+            0x02u8,
+            1u8, 11u8, 0u8, 24u8, 10u8, 1u8, 2u8, 0u8, 28u8, 10u8, 1u8, 3u8, 16u8,
+            2u8, 4u8, b'p', b'o', b'o', b'l',
+            1u8, 3u8, 0u8, 24u8, 10u8,
+        ];
+        let (flags, suboptions) = subnet_allocation(&data).unwrap();
+        assert_eq!(flags, 0x02);
+        assert_eq!(suboptions, vec![
+            SubnetInformation {
+                flags: 0,
+                prefixes: vec![
+                    SubnetPrefix { prefix_len: 24, prefix: Ipv4Addr::new(10, 1, 2, 0) },
+                    SubnetPrefix { prefix_len: 28, prefix: Ipv4Addr::new(10, 1, 3, 16) },
+                ],
+            },
+            SubnetName("pool".to_string()),
+            Unknown { code: 1u8, data: vec![0u8, 24u8, 10u8] },
+        ]);
+        let mut encoded = vec![flags];
+        for sub in &suboptions {
+            sub.encode(&mut encoded);
+        }
+        assert_eq!(encoded, data.to_vec());
+    }
+
+    #[test]
+    fn test_truncated_subnet_allocation() {
+        assert!(subnet_allocation(&[]).is_err());
+        assert!(subnet_allocation(&[0u8, 2u8, 4u8, b'p']).is_err());
+        assert_eq!(subnet_allocation(&[1u8]).unwrap(), (1u8, vec![]));
+    }
+}
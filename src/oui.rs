@@ -0,0 +1,139 @@
+//! IEEE OUI (manufacturer prefix) lookup
+//!
+//! The built-in table behind the `oui` feature only covers a handful of
+//! prefixes commonly seen on networks (virtualization platforms, network
+//! vendors). For full coverage load the IEEE registry with
+//! `OuiTable::from_ieee_txt`, or implement `OuiProvider` over your own
+//! data source.
+
+use std::collections::HashMap;
+
+pub trait OuiProvider {
+    fn lookup(&self, oui: [u8; 3]) -> Option<&str>;
+}
+
+/// A manufacturer table held in memory
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct OuiTable {
+    entries: HashMap<[u8; 3], String>,
+}
+
+impl OuiTable {
+    pub fn new() -> OuiTable {
+        OuiTable { entries: HashMap::new() }
+    }
+
+    pub fn insert(&mut self, oui: [u8; 3], manufacturer: String) {
+        self.entries.insert(oui, manufacturer);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Load the `(hex)` lines of the IEEE `oui.txt` registry, e.g.
+    /// (with the tabs written as `\t`)
+    ///
+    /// ```text
+    /// 00-00-0C   (hex)\t\tCisco Systems, Inc
+    /// ```
+    ///
+    /// Lines in any other format are ignored.
+    pub fn from_ieee_txt(text: &str) -> OuiTable {
+        let mut table = OuiTable::new();
+        for line in text.lines() {
+            let mut parts = line.splitn(2, "(hex)");
+            let prefix = parts.next().unwrap_or("").trim();
+            let name = match parts.next() {
+                Some(n) => n.trim(),
+                None => continue,
+            };
+            if let Some(oui) = parse_oui(prefix) {
+                table.insert(oui, name.to_string());
+            }
+        }
+        table
+    }
+}
+
+impl OuiProvider for OuiTable {
+    fn lookup(&self, oui: [u8; 3]) -> Option<&str> {
+        self.entries.get(&oui).map(|s| &s[..])
+    }
+}
+
+fn parse_oui(s: &str) -> Option<[u8; 3]> {
+    let mut oui = [0u8; 3];
+    let mut count = 0;
+    for part in s.split(['-', ':']) {
+        if count == 3 || part.len() != 2 {
+            return None;
+        }
+        oui[count] = match u8::from_str_radix(part, 16) {
+            Ok(b) => b,
+            Err(_) => return None,
+        };
+        count += 1;
+    }
+    if count == 3 { Some(oui) } else { None }
+}
+
+/// Sorted by prefix so lookups can binary search
+#[cfg(feature = "oui")]
+static BUILTIN: &[([u8; 3], &str)] = &[
+    ([0x00, 0x00, 0x0C], "Cisco Systems, Inc"),
+    ([0x00, 0x00, 0x5E], "ICANN, IANA Department"),
+    ([0x00, 0x03, 0x93], "Apple, Inc."),
+    ([0x00, 0x0C, 0x29], "VMware, Inc."),
+    ([0x00, 0x15, 0x5D], "Microsoft Corporation"),
+    ([0x00, 0x16, 0x3E], "Xensource, Inc."),
+    ([0x00, 0x50, 0x56], "VMware, Inc."),
+    ([0x08, 0x00, 0x27], "PCS Systemtechnik GmbH"),
+    ([0xB8, 0x27, 0xEB], "Raspberry Pi Foundation"),
+    ([0xDC, 0xA6, 0x32], "Raspberry Pi Trading Ltd"),
+];
+
+/// The table compiled into the crate
+#[cfg(feature = "oui")]
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltinOui;
+
+#[cfg(feature = "oui")]
+impl OuiProvider for BuiltinOui {
+    fn lookup(&self, oui: [u8; 3]) -> Option<&str> {
+        BUILTIN.binary_search_by(|&(prefix, _)| prefix.cmp(&oui))
+               .ok()
+               .map(|i| BUILTIN[i].1)
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{OuiTable, OuiProvider};
+
+    #[test]
+    fn test_from_ieee_txt() {
+        let text = "OUI/MA-L                                                    Organization\n\
+                    00-00-0C   (hex)\t\tCisco Systems, Inc\n\
+                    00000C     (base 16)\t\tCisco Systems, Inc\n\
+                    08-00-27   (hex)\t\tPCS Systemtechnik GmbH\n";
+        let table = OuiTable::from_ieee_txt(text);
+        assert_eq!(table.len(), 2);
+        assert!(!table.is_empty());
+        assert!(OuiTable::new().is_empty());
+        assert_eq!(table.lookup([0x00, 0x00, 0x0C]), Some("Cisco Systems, Inc"));
+        assert_eq!(table.lookup([0x08, 0x00, 0x27]), Some("PCS Systemtechnik GmbH"));
+        assert_eq!(table.lookup([0x01, 0x02, 0x03]), None);
+    }
+
+    #[cfg(feature = "oui")]
+    #[test]
+    fn test_builtin() {
+        use super::BuiltinOui;
+        assert_eq!(BuiltinOui.lookup([0x00, 0x50, 0x56]), Some("VMware, Inc."));
+        assert_eq!(BuiltinOui.lookup([0x00, 0x50, 0x57]), None);
+    }
+}
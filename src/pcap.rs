@@ -0,0 +1,530 @@
+//! Reading DHCP messages out of packet captures
+//!
+//! Both the classic pcap format and pcapng are understood. Frames are
+//! unwrapped down to UDP over IPv4 and anything that isn't to or from
+//! port 67 or 68 is skipped. Supported link types are Ethernet (with
+//! 802.1Q/802.1ad tags), raw IPv4, BSD loopback, and Linux cooked
+//! captures (SLL and SLL2). IP fragments are skipped rather than
+//! reassembled.
+//!
+//! ```ignore
+//! let capture = std::fs::read("dhcp.pcapng")?;
+//! for packet in pcap::packets(&capture)? {
+//!     println!("{:?}", packet?.message);
+//! }
+//! ```
+
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+use std::convert::TryInto;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use {DhcpMessage, Result, Error};
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b23c4d;
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b3c4d;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 1;
+const PCAPNG_SIMPLE_PACKET: u32 = 3;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88a8;
+
+/// A DHCP message found in a capture
+#[derive(Debug, PartialEq)]
+pub struct DhcpPacket {
+    pub timestamp: SystemTime,
+    pub source: SocketAddrV4,
+    pub destination: SocketAddrV4,
+    pub message: DhcpMessage,
+}
+
+fn capture_error(msg: &str) -> Error {
+    Error::ParseError(format!("Invalid capture: {}", msg))
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Endian {
+    big: bool,
+}
+
+const LITTLE: Endian = Endian { big: false };
+const BIG: Endian = Endian { big: true };
+
+impl Endian {
+    fn u16(&self, buf: &[u8], offset: usize) -> Option<u16> {
+        buf.get(offset..offset + 2).and_then(|b| b.try_into().ok()).map(|b| {
+            if self.big { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) }
+        })
+    }
+
+    fn u32(&self, buf: &[u8], offset: usize) -> Option<u32> {
+        buf.get(offset..offset + 4).and_then(|b| b.try_into().ok()).map(|b| {
+            if self.big { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) }
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Interface {
+    linktype: u32,
+    /// Timestamp units per second
+    resolution: u64,
+}
+
+enum Format {
+    Pcap { endian: Endian, interface: Interface },
+    Pcapng { endian: Endian, interfaces: Vec<Interface> },
+}
+
+/// A UDP datagram to or from the DHCP ports, not yet parsed
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Datagram<'a> {
+    pub timestamp: SystemTime,
+    pub source: SocketAddrV4,
+    pub destination: SocketAddrV4,
+    pub payload: &'a [u8],
+}
+
+/// Iterator over the DHCP datagrams in a capture. A capture that is cut
+/// off or malformed yields one error and then ends.
+pub struct Datagrams<'a> {
+    format: Format,
+    remaining: &'a [u8],
+}
+
+/// Iterator over the DHCP messages in a capture.
+///
+/// Messages on the DHCP ports that fail to parse are yielded as errors
+/// and skipped. A capture that is cut off or malformed yields one error
+/// and then ends.
+pub struct DhcpPackets<'a> {
+    datagrams: Datagrams<'a>,
+}
+
+/// Start reading the capture in `bytes`, which can be in pcap or pcapng
+/// format
+pub fn packets<'a>(bytes: &'a [u8]) -> Result<DhcpPackets<'a>> {
+    datagrams(bytes).map(|datagrams| DhcpPackets { datagrams })
+}
+
+/// Like `packets`, but leaves the payloads unparsed
+pub fn datagrams<'a>(bytes: &'a [u8]) -> Result<Datagrams<'a>> {
+    let magic = match LITTLE.u32(bytes, 0) {
+        Some(magic) => magic,
+        None => return Err(capture_error("too short for a file header")),
+    };
+    if magic == PCAPNG_SECTION_HEADER {
+        return Ok(Datagrams {
+            format: Format::Pcapng { endian: LITTLE, interfaces: Vec::new() },
+            remaining: bytes,
+        });
+    }
+    let (endian, resolution) = match (magic, magic.swap_bytes()) {
+        (PCAP_MAGIC_MICROS, _) => (LITTLE, 1_000_000),
+        (PCAP_MAGIC_NANOS, _) => (LITTLE, 1_000_000_000),
+        (_, PCAP_MAGIC_MICROS) => (BIG, 1_000_000),
+        (_, PCAP_MAGIC_NANOS) => (BIG, 1_000_000_000),
+        _ => return Err(capture_error("not a pcap or pcapng file")),
+    };
+    let linktype = match endian.u32(bytes, 20) {
+        Some(linktype) => linktype,
+        None => return Err(capture_error("too short for a file header")),
+    };
+    Ok(Datagrams {
+        format: Format::Pcap { endian, interface: Interface { linktype, resolution } },
+        remaining: bytes.get(24..).unwrap_or(&[]),
+    })
+}
+
+/// A captured frame, before unwrapping
+struct Frame<'a> {
+    interface: Interface,
+    ticks: u64,
+    data: &'a [u8],
+}
+
+impl<'a> Datagrams<'a> {
+    /// The next frame in the capture, or `None` at the end of it
+    fn next_frame(&mut self) -> Result<Option<Frame<'a>>> {
+        loop {
+            if self.remaining.is_empty() {
+                return Ok(None);
+            }
+            match self.format {
+                Format::Pcap { endian, interface } => {
+                    let header = (endian.u32(self.remaining, 0), endian.u32(self.remaining, 4),
+                                  endian.u32(self.remaining, 8));
+                    let (secs, fraction, len) = match header {
+                        (Some(secs), Some(fraction), Some(len)) => (secs, fraction, len as usize),
+                        _ => return Err(capture_error("record header is cut off")),
+                    };
+                    let (data, rest) = match (self.remaining.get(16..16 + len), self.remaining.get(16 + len..)) {
+                        (Some(data), Some(rest)) => (data, rest),
+                        _ => return Err(capture_error("record is cut off")),
+                    };
+                    self.remaining = rest;
+                    let ticks = secs as u64 * interface.resolution + fraction as u64;
+                    return Ok(Some(Frame { interface, ticks, data }));
+                },
+                Format::Pcapng { ref mut endian, ref mut interfaces } => {
+                    let block_type = endian.u32(self.remaining, 0);
+                    if block_type == Some(PCAPNG_SECTION_HEADER) {
+                        // Each section sets its own byte order
+                        *endian = match endian.u32(self.remaining, 8) {
+                            Some(PCAPNG_BYTE_ORDER_MAGIC) => *endian,
+                            Some(m) if m.swap_bytes() == PCAPNG_BYTE_ORDER_MAGIC => Endian { big: !endian.big },
+                            _ => return Err(capture_error("bad section header")),
+                        };
+                        interfaces.clear();
+                    }
+                    let len = match endian.u32(self.remaining, 4) {
+                        Some(len) if len >= 12 && len % 4 == 0 => len as usize,
+                        _ => return Err(capture_error("bad block length")),
+                    };
+                    let (body, rest) = match (self.remaining.get(8..len - 4), self.remaining.get(len..)) {
+                        (Some(body), Some(rest)) => (body, rest),
+                        _ => return Err(capture_error("block is cut off")),
+                    };
+                    self.remaining = rest;
+                    match block_type {
+                        Some(PCAPNG_INTERFACE_DESCRIPTION) => {
+                            let linktype = match endian.u16(body, 0) {
+                                Some(linktype) => linktype as u32,
+                                None => return Err(capture_error("interface description is cut off")),
+                            };
+                            let resolution = if_tsresol(*endian, body.get(8..).unwrap_or(&[]));
+                            interfaces.push(Interface { linktype, resolution });
+                        },
+                        Some(PCAPNG_ENHANCED_PACKET) => {
+                            let fields = (endian.u32(body, 0), endian.u32(body, 4),
+                                          endian.u32(body, 8), endian.u32(body, 12));
+                            let (id, high, low, caplen) = match fields {
+                                (Some(id), Some(high), Some(low), Some(caplen)) => (id, high, low, caplen as usize),
+                                _ => return Err(capture_error("packet block is cut off")),
+                            };
+                            let interface = match interfaces.get(id as usize) {
+                                Some(&interface) => interface,
+                                None => return Err(capture_error("packet on an undescribed interface")),
+                            };
+                            let data = match body.get(20..20 + caplen) {
+                                Some(data) => data,
+                                None => return Err(capture_error("packet block is cut off")),
+                            };
+                            let ticks = ((high as u64) << 32) | (low as u64);
+                            return Ok(Some(Frame { interface, ticks, data }));
+                        },
+                        Some(PCAPNG_SIMPLE_PACKET) => {
+                            // No timestamp, and always on the first interface
+                            let interface = match interfaces.first() {
+                                Some(&interface) => interface,
+                                None => return Err(capture_error("packet on an undescribed interface")),
+                            };
+                            let orig_len = endian.u32(body, 0).unwrap_or(0) as usize;
+                            let data = body.get(4..).unwrap_or(&[]);
+                            let data = data.get(..orig_len).unwrap_or(data);
+                            return Ok(Some(Frame { interface, ticks: 0, data }));
+                        },
+                        _ => {},
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Timestamp units per second from an interface description's options,
+/// which default to microseconds
+fn if_tsresol(endian: Endian, mut options: &[u8]) -> u64 {
+    while let (Some(code), Some(len)) = (endian.u16(options, 0), endian.u16(options, 2)) {
+        let len = len as usize;
+        if code == 0 || options.len() < 4 + len {
+            break;
+        }
+        if let (9, 1, Some(&value)) = (code, len, options.get(4)) {
+            let exponent = (value & 0x7f) as u32;
+            let base: u64 = if value & 0x80 == 0 { 10 } else { 2 };
+            return base.checked_pow(exponent).unwrap_or(1_000_000);
+        }
+        options = options.get(4 + len.div_ceil(4) * 4..).unwrap_or(&[]);
+    }
+    1_000_000
+}
+
+fn timestamp(ticks: u64, resolution: u64) -> Result<SystemTime> {
+    let secs = ticks / resolution;
+    let nanos = (ticks % resolution) as u128 * 1_000_000_000 / resolution as u128;
+    UNIX_EPOCH.checked_add(Duration::new(secs, nanos as u32))
+              .ok_or_else(|| capture_error("timestamp is out of range"))
+}
+
+/// The IPv4 packet inside a frame, if it holds one
+fn ipv4_packet(linktype: u32, frame: &[u8]) -> Option<&[u8]> {
+        match linktype {
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            let mut ethertype = BIG.u16(frame, offset)?;
+            while ethertype == ETHERTYPE_VLAN || ethertype == ETHERTYPE_QINQ {
+                offset += 4;
+                ethertype = BIG.u16(frame, offset)?;
+            }
+            if ethertype == ETHERTYPE_IPV4 { frame.get(offset + 2..) } else { None }
+        },
+        LINKTYPE_RAW | LINKTYPE_IPV4 => Some(frame),
+        LINKTYPE_NULL => {
+            // The address family is in the byte order of the machine that
+            // made the capture
+            let family = LITTLE.u32(frame, 0)?;
+            if family == 2 || family.swap_bytes() == 2 { frame.get(4..) } else { None }
+        },
+        LINKTYPE_LINUX_SLL if BIG.u16(frame, 14)? == ETHERTYPE_IPV4 => frame.get(16..),
+        LINKTYPE_LINUX_SLL2 if BIG.u16(frame, 0)? == ETHERTYPE_IPV4 => frame.get(20..),
+        _ => None,
+    }
+}
+
+/// Source, destination and payload of a UDP datagram to or from the
+/// DHCP ports
+fn dhcp_datagram(ip: &[u8]) -> Option<(SocketAddrV4, SocketAddrV4, &[u8])> {
+        let version_ihl = *ip.first()?;
+    if version_ihl >> 4 != 4 || ip.get(9)? != &17u8 {
+        return None;
+    }
+    // Skip fragments: anything with more to come or a non-zero offset
+    if BIG.u16(ip, 6)? & 0x3fff != 0 {
+        return None;
+    }
+    let header_len = ((version_ihl & 0x0f) as usize) * 4;
+    if header_len < 20 || header_len > ip.len() {
+        return None;
+    }
+    let total_len = ::std::cmp::min(BIG.u16(ip, 2)? as usize, ip.len());
+    let udp = ip.get(header_len..total_len)?;
+    let (src_port, dst_port) = (BIG.u16(udp, 0)?, BIG.u16(udp, 2)?);
+    let is_dhcp = |port| port == 67 || port == 68;
+    if !is_dhcp(src_port) && !is_dhcp(dst_port) {
+        return None;
+    }
+    let udp_len = ::std::cmp::min(BIG.u16(udp, 4)? as usize, udp.len());
+    let payload = udp.get(8..udp_len)?;
+    let src = SocketAddrV4::new(Ipv4Addr::from(BIG.u32(ip, 12)?), src_port);
+    let dst = SocketAddrV4::new(Ipv4Addr::from(BIG.u32(ip, 16)?), dst_port);
+    Some((src, dst, payload))
+}
+
+impl<'a> Iterator for Datagrams<'a> {
+    type Item = Result<Datagram<'a>>;
+
+    fn next(&mut self) -> Option<Result<Datagram<'a>>> {
+        loop {
+            let frame = match self.next_frame() {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return None,
+                Err(e) => {
+                    self.remaining = &[];
+                    return Some(Err(e));
+                },
+            };
+            let datagram = ipv4_packet(frame.interface.linktype, frame.data).and_then(dhcp_datagram);
+            if let Some((source, destination, payload)) = datagram {
+                let timestamp = match timestamp(frame.ticks, frame.interface.resolution) {
+                    Ok(timestamp) => timestamp,
+                    Err(e) => {
+                        self.remaining = &[];
+                        return Some(Err(e));
+                    },
+                };
+                return Some(Ok(Datagram {
+                    timestamp,
+                    source,
+                    destination,
+                    payload,
+                }));
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for DhcpPackets<'a> {
+    type Item = Result<DhcpPacket>;
+
+    fn next(&mut self) -> Option<Result<DhcpPacket>> {
+        let datagram = match self.datagrams.next()? {
+            Ok(datagram) => datagram,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(DhcpMessage::parse(datagram.payload).map(|message| DhcpPacket {
+            timestamp: datagram.timestamp,
+            source: datagram.source,
+            destination: datagram.destination,
+            message,
+        }))
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::packets;
+    use {DhcpMessage, Error};
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, DhcpMessageTypes};
+    use std::net::{Ipv4Addr, SocketAddrV4};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn discover() -> DhcpMessage {
+        DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(0, 0, 0, 0),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options: vec![DhcpOption::MessageType(DhcpMessageTypes::Discover), DhcpOption::End],
+        }
+    }
+
+    /// An Ethernet frame with a VLAN tag around a UDP datagram
+    fn frame(src_port: u16, dst_port: u16, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0xffu8; 6];
+        frame.extend_from_slice(&[0, 11, 130, 1, 252, 66]);
+        frame.extend_from_slice(&[0x81, 0x00, 0x00, 0x0a, 0x08, 0x00]);
+        let total_len = (20 + 8 + payload.len()) as u16;
+        frame.extend_from_slice(&[0x45, 0]);
+        frame.extend_from_slice(&total_len.to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0, 0, 64, 17, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255]);
+        frame.extend_from_slice(&src_port.to_be_bytes());
+        frame.extend_from_slice(&dst_port.to_be_bytes());
+        frame.extend_from_slice(&(total_len - 20).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(payload);
+        // Ethernet padding, which isn't part of the datagram
+        frame.extend_from_slice(&[0, 0, 0, 0]);
+        frame
+    }
+
+    #[test]
+    fn test_pcap() {
+        let mut capture = vec![0xa1u8, 0xb2, 0xc3, 0xd4, 0, 2, 0, 4];
+        capture.extend_from_slice(&[0u8; 8]);
+        capture.extend_from_slice(&[0, 0, 0xff, 0xff, 0, 0, 0, 1]);
+        for &(port, secs) in &[(53u16, 1u32), (67u16, 2u32)] {
+            let frame = frame(port + 1, port, &discover().encode());
+            capture.extend_from_slice(&secs.to_be_bytes());
+            capture.extend_from_slice(&500u32.to_be_bytes());
+            capture.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+            capture.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+            capture.extend(frame);
+        }
+
+        let found: Vec<_> = packets(&capture).unwrap().map(|p| p.unwrap()).collect();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].timestamp, UNIX_EPOCH + Duration::new(2, 500_000));
+        assert_eq!(found[0].source, SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 68));
+        assert_eq!(found[0].destination, SocketAddrV4::new(Ipv4Addr::new(255, 255, 255, 255), 67));
+        assert_eq!(found[0].message, discover());
+    }
+
+    fn block(block_type: u32, body: &[u8]) -> Vec<u8> {
+        let len = (12 + body.len()).div_ceil(4) * 4;
+        let mut block = Vec::new();
+        block.extend_from_slice(&block_type.to_le_bytes());
+        block.extend_from_slice(&(len as u32).to_le_bytes());
+        block.extend_from_slice(body);
+        block.resize(len - 4, 0);
+        block.extend_from_slice(&(len as u32).to_le_bytes());
+        block
+    }
+
+    #[test]
+    fn test_pcapng() {
+        let mut capture = block(0x0a0d0d0a, &[0x4d, 0x3c, 0x2b, 0x1a, 1, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        // Ethernet, with if_tsresol set to milliseconds
+        capture.extend(block(1, &[1, 0, 0, 0, 0, 0, 0, 0, 9, 0, 1, 0, 3, 0, 0, 0, 0, 0, 0, 0]));
+        let frame = frame(68, 67, &discover().encode());
+        let mut epb = Vec::new();
+        epb.extend_from_slice(&0u32.to_le_bytes());
+        epb.extend_from_slice(&0u32.to_le_bytes());
+        epb.extend_from_slice(&1500u32.to_le_bytes());
+        epb.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        epb.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        epb.extend(frame);
+        capture.extend(block(6, &epb));
+        // A block type this reader doesn't know about is skipped
+        capture.extend(block(0x0bad, &[1, 2, 3, 4]));
+
+        let found: Vec<_> = packets(&capture).unwrap().map(|p| p.unwrap()).collect();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].timestamp, UNIX_EPOCH + Duration::from_millis(1500));
+        assert_eq!(found[0].message, discover());
+    }
+
+    #[test]
+    fn test_truncated_capture() {
+        let mut capture = vec![0xd4u8, 0xc3, 0xb2, 0xa1, 2, 0, 4, 0];
+        capture.extend_from_slice(&[0u8; 8]);
+        capture.extend_from_slice(&[0xff, 0xff, 0, 0, 1, 0, 0, 0]);
+        capture.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 100, 0, 0, 0, 100, 0, 0, 0, 1, 2, 3]);
+        let mut iter = packets(&capture).unwrap();
+        match iter.next() {
+            Some(Err(Error::ParseError(_))) => {},
+            e => panic!("Result was {:?}", e),
+        }
+        assert!(iter.next().is_none());
+        assert!(packets(&[1u8, 2, 3, 4]).is_err());
+    }
+
+    #[test]
+    fn test_short_ip_header() {
+        // Raw IPv4 with an IHL of 0 and a total length of 67, which lines
+        // up with the DHCP server port if the header is taken as empty
+        let packet = [0x40u8, 0, 0, 67, 0, 16, 0, 0, 64, 17, 0, 0, 0, 0, 0, 0];
+        let mut capture = vec![0xa1u8, 0xb2, 0xc3, 0xd4, 0, 2, 0, 4];
+        capture.extend_from_slice(&[0u8; 8]);
+        capture.extend_from_slice(&[0, 0, 0xff, 0xff, 0, 0, 0, 101]);
+        capture.extend_from_slice(&[0u8; 8]);
+        capture.extend_from_slice(&(packet.len() as u32).to_be_bytes());
+        capture.extend_from_slice(&(packet.len() as u32).to_be_bytes());
+        capture.extend_from_slice(&packet);
+        assert!(packets(&capture).unwrap().next().is_none());
+    }
+
+    #[test]
+    fn test_timestamp_out_of_range() {
+        let mut capture = block(0x0a0d0d0a, &[0x4d, 0x3c, 0x2b, 0x1a, 1, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        // if_tsresol of whole seconds
+        capture.extend(block(1, &[1, 0, 0, 0, 0, 0, 0, 0, 9, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]));
+        let frame = frame(68, 67, &discover().encode());
+        let mut epb = Vec::new();
+        epb.extend_from_slice(&0u32.to_le_bytes());
+        epb.extend_from_slice(&u32::MAX.to_le_bytes());
+        epb.extend_from_slice(&u32::MAX.to_le_bytes());
+        epb.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        epb.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        epb.extend(frame);
+        capture.extend(block(6, &epb));
+        let mut iter = packets(&capture).unwrap();
+        match iter.next() {
+            Some(Err(Error::ParseError(_))) => {},
+            e => panic!("Result was {:?}", e),
+        }
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,119 @@
+//! Parsing messages straight out of `pnet` packet views
+//!
+//! Each layer is checked before the next is unwrapped: the frame must
+//! hold IPv4 (under any number of VLAN tags), the packet must be an
+//! unfragmented UDP datagram, and the datagram must be to or from port
+//! 67 or 68. Padding after the UDP length, e.g. Ethernet's, is ignored.
+
+use pnet_packet::Packet;
+use pnet_packet::ethernet::{EtherType, EtherTypes, EthernetPacket};
+use pnet_packet::ip::IpNextHeaderProtocols;
+use pnet_packet::ipv4::{Ipv4Flags, Ipv4Packet};
+use pnet_packet::udp::UdpPacket;
+use pnet_packet::vlan::VlanPacket;
+use {DhcpMessage, Result, Error};
+
+/// 802.1ad, which pnet calls `PBridge`
+const ETHERTYPE_QINQ: EtherType = EtherType(0x88a8);
+
+fn packet_error(msg: &str) -> Error {
+    Error::ParseError(format!("Not a DHCP packet: {}", msg))
+}
+
+/// Parse the message in a UDP datagram to or from the DHCP ports
+pub fn parse_udp(udp: &UdpPacket) -> Result<DhcpMessage> {
+    let is_dhcp = |port| port == 67 || port == 68;
+    if !is_dhcp(udp.get_source()) && !is_dhcp(udp.get_destination()) {
+        return Err(packet_error("not to or from port 67 or 68"));
+    }
+    let payload = udp.payload();
+    let len = ::std::cmp::min((udp.get_length() as usize).saturating_sub(8), payload.len());
+    DhcpMessage::parse(&payload[..len])
+}
+
+/// Parse the message in an IPv4 packet carrying a DHCP datagram
+pub fn parse_ipv4(ip: &Ipv4Packet) -> Result<DhcpMessage> {
+    if ip.get_next_level_protocol() != IpNextHeaderProtocols::Udp {
+        return Err(packet_error("not UDP"));
+    }
+    if ip.get_flags() & Ipv4Flags::MoreFragments != 0 || ip.get_fragment_offset() != 0 {
+        return Err(packet_error("IP fragment"));
+    }
+    let udp = UdpPacket::new(ip.payload()).ok_or_else(|| packet_error("truncated UDP header"))?;
+    parse_udp(&udp)
+}
+
+/// Parse the message in an Ethernet frame carrying a DHCP datagram
+pub fn parse_ethernet(frame: &EthernetPacket) -> Result<DhcpMessage> {
+    let mut ethertype = frame.get_ethertype();
+    let mut payload = frame.payload();
+    while ethertype == EtherTypes::Vlan || ethertype == ETHERTYPE_QINQ {
+        let vlan = VlanPacket::new(payload).ok_or_else(|| packet_error("truncated VLAN tag"))?;
+        ethertype = vlan.get_ethertype();
+        payload = &payload[VlanPacket::minimum_packet_size()..];
+    }
+    if ethertype != EtherTypes::Ipv4 {
+        return Err(packet_error("not IPv4"));
+    }
+    let ip = Ipv4Packet::new(payload).ok_or_else(|| packet_error("truncated IPv4 header"))?;
+    parse_ipv4(&ip)
+}
+
+#[cfg(test)] mod tests {
+    use super::parse_ethernet;
+    use pnet_packet::ethernet::EthernetPacket;
+    use DhcpMessage;
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, DhcpMessageTypes};
+    use std::net::Ipv4Addr;
+
+    fn discover() -> DhcpMessage {
+        DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(0, 0, 0, 0),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options: vec![DhcpOption::MessageType(DhcpMessageTypes::Discover), DhcpOption::End],
+        }
+    }
+
+    /// An Ethernet frame with a VLAN tag around a UDP datagram
+    fn frame(protocol: u8, src_port: u16, dst_port: u16, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0xffu8; 6];
+        frame.extend_from_slice(&[0, 11, 130, 1, 252, 66]);
+        frame.extend_from_slice(&[0x81, 0x00, 0x00, 0x0a, 0x08, 0x00]);
+        let total_len = (20 + 8 + payload.len()) as u16;
+        frame.extend_from_slice(&[0x45, 0]);
+        frame.extend_from_slice(&total_len.to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0, 0, 64, protocol, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255]);
+        frame.extend_from_slice(&src_port.to_be_bytes());
+        frame.extend_from_slice(&dst_port.to_be_bytes());
+        frame.extend_from_slice(&(total_len - 20).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(payload);
+        frame.extend_from_slice(&[0, 0, 0, 0]);
+        frame
+    }
+
+    #[test]
+    fn test_parse_ethernet() {
+        let bytes = frame(17, 68, 67, &discover().encode());
+        assert_eq!(parse_ethernet(&EthernetPacket::new(&bytes).unwrap()).unwrap(), discover());
+
+        let bytes = frame(17, 5353, 53, &discover().encode());
+        assert!(parse_ethernet(&EthernetPacket::new(&bytes).unwrap()).is_err());
+        let bytes = frame(6, 68, 67, &discover().encode());
+        assert!(parse_ethernet(&EthernetPacket::new(&bytes).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! Messages that keep the bytes their options were parsed from
+//!
+//! `DhcpMessage::encode` writes options the way this crate would, so Pad
+//! runs, oddly split options and bytes after End don't survive a parse
+//! and encode. A `PreservedMessage` keeps each option's original bytes
+//! next to its decoded value, and anything that didn't parse as an
+//! option as bytes alone, so it encodes back to exactly what it was
+//! parsed from. Options can still be replaced or added with
+//! `PreservedOption::new`, which encodes the new value.
+
+use {DhcpMessage, Result, OPTIONS_OFFSET, parse_message};
+use options::{self, DhcpOption};
+
+/// An option and the bytes it was parsed from, or bytes that aren't an
+/// option, e.g. a malformed one or padding after End
+#[derive(Debug, PartialEq, Clone)]
+pub struct PreservedOption {
+    pub value: Option<DhcpOption>,
+    pub bytes: Vec<u8>,
+}
+
+impl PreservedOption {
+    /// `option` with the bytes it encodes to
+    pub fn new(option: DhcpOption) -> PreservedOption {
+        let mut bytes = Vec::new();
+        option.encode(&mut bytes);
+        PreservedOption { value: Some(option), bytes }
+    }
+}
+
+/// Split `bytes` into options and the bytes between them, so that the
+/// pieces' bytes put back together are `bytes`
+pub fn parse_options(bytes: &[u8]) -> Result<Vec<PreservedOption>> {
+    let mut preserved = Vec::new();
+    let mut covered = 0;
+    for spanned in options::parse_spanned(bytes)? {
+        if spanned.offset > covered {
+            preserved.push(PreservedOption { value: None, bytes: bytes[covered..spanned.offset].to_vec() });
+        }
+        covered = spanned.offset + spanned.len;
+        preserved.push(PreservedOption { bytes: bytes[spanned.range()].to_vec(), value: Some(spanned.value) });
+    }
+    if covered < bytes.len() {
+        preserved.push(PreservedOption { value: None, bytes: bytes[covered..].to_vec() });
+    }
+    Ok(preserved)
+}
+
+/// The bytes of `options`, one after the other
+pub fn encode_options(options: &[PreservedOption]) -> Vec<u8> {
+    options.iter().flat_map(|o| o.bytes.iter().cloned()).collect()
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PreservedMessage {
+    /// The header fields. Its `options` are left empty; see `decoded`.
+    pub header: DhcpMessage,
+    pub options: Vec<PreservedOption>,
+}
+
+impl PreservedMessage {
+    pub fn parse(bytes: &[u8]) -> Result<PreservedMessage> {
+        let mut header = DhcpMessage::from(parse_message(bytes)?);
+        header.options = Vec::new();
+        let options = parse_options(&bytes[OPTIONS_OFFSET..])?;
+        Ok(PreservedMessage { header, options })
+    }
+
+    /// The original bytes, with any changes to the header or options
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = self.header.encode();
+        buf.truncate(OPTIONS_OFFSET);
+        buf.extend(encode_options(&self.options));
+        buf
+    }
+
+    /// The message with the decoded options, as `DhcpMessage::parse`
+    /// would give
+    pub fn decoded(&self) -> DhcpMessage {
+        let mut msg = self.header.clone();
+        msg.options = self.options.iter().filter_map(|o| o.value.clone()).collect();
+        msg
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{PreservedMessage, PreservedOption, parse_options};
+    use std::net::Ipv4Addr;
+    use DhcpMessage;
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, DhcpMessageTypes};
+
+    #[test]
+    fn test_parse_options() {
+        let bytes = [
+            0u8, 0u8,
+            53u8, 1u8, 1u8,
+            53u8, 2u8, 1u8, 0u8,    // wrong length
+            12u8, 1u8, b'a',
+            12u8, 1u8, b'b',
+            255u8,
+            0u8, 0u8, 0u8,          // padding after End
+        ];
+        let options = parse_options(&bytes).unwrap();
+        assert_eq!(options.len(), 7);
+        assert_eq!(options[3], PreservedOption { value: None, bytes: vec![53u8, 2u8, 1u8, 0u8] });
+        // The two fragments of option 12 are one value, but keep their split
+        assert_eq!(options[4], PreservedOption {
+            value: Some(DhcpOption::HostName("ab".to_string())),
+            bytes: vec![12u8, 1u8, b'a', 12u8, 1u8, b'b'],
+        });
+        assert_eq!(options[6], PreservedOption { value: None, bytes: vec![0u8, 0u8, 0u8] });
+        assert_eq!(super::encode_options(&options), bytes.to_vec());
+    }
+
+    #[test]
+    fn test_preserved_message() {
+        let msg = DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(0, 0, 0, 0),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options: vec![DhcpOption::MessageType(DhcpMessageTypes::Discover), DhcpOption::Pad, DhcpOption::End],
+        };
+        let mut bytes = msg.encode();
+        bytes.extend_from_slice(&[0u8; 10]);
+
+        let mut preserved = PreservedMessage::parse(&bytes).unwrap();
+        assert_eq!(preserved.encode(), bytes);
+        assert_eq!(preserved.decoded(), DhcpMessage::parse(&bytes).unwrap());
+
+        preserved.header.xid = 1;
+        preserved.options[1] = PreservedOption::new(DhcpOption::HostName("a".to_string()));
+        let edited = DhcpMessage::parse(&preserved.encode()).unwrap();
+        assert_eq!(edited.xid, 1);
+        assert_eq!(edited.options[1], DhcpOption::HostName("a".to_string()));
+        assert_eq!(preserved.encode().len(), bytes.len() + 2);
+    }
+}
@@ -0,0 +1,207 @@
+//! Per-device interop workarounds
+//!
+//! Some clients and relays get the wire format subtly wrong. Rather than
+//! loosening the parser for everyone, the workarounds are described by a
+//! `Quirks` value, and `QuirkProfile`s tie a set of quirks to the devices
+//! that need them.
+
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+use std::borrow::Cow;
+use options::put_option;
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Quirks {
+    /// Option codes whose value has trailing NUL bytes that should be
+    /// dropped, e.g. hostnames sent as C strings
+    pub nul_terminated: Vec<u8>,
+    /// Option codes whose integer value is sent little-endian. The value
+    /// bytes are reversed before decoding, so this only makes sense for
+    /// options holding a single integer.
+    pub swapped_byte_order: Vec<u8>,
+    /// Option codes holding text that may not be valid UTF-8. Invalid
+    /// sequences are replaced with U+FFFD, where otherwise the option
+    /// would fail to parse and be dropped.
+    pub lossy_utf8: Vec<u8>,
+    /// Decode every option that holds text as if its code were in
+    /// `lossy_utf8`
+    pub lossy_utf8_all: bool,
+    /// Decode options that are longer than their parser reads, dropping
+    /// the extra bytes. Without this such an option is kept undecoded, as
+    /// `Unknown`. Meant for devices that send oversized fixed-length
+    /// options.
+    pub trust_length_field: bool,
+}
+
+/// Options whose whole value is text
+fn holds_text(code: u8) -> bool {
+    matches!(code, 12u8 | 14u8 | 15u8 | 17u8 | 18u8 | 40u8 | 47u8 | 56u8 | 62u8 | 64u8 | 66u8 | 67u8
+        | 100u8 | 101u8 | 113u8 | 209u8 | 210u8 | 252u8)
+}
+
+impl Quirks {
+    fn rewrites(&self, code: u8) -> bool {
+        self.nul_terminated.contains(&code) || self.swapped_byte_order.contains(&code)
+            || self.is_lossy_utf8(code)
+    }
+
+    /// True if invalid UTF-8 in option `code` is replaced rather than
+    /// failing the option. The replacements can take the value past 255
+    /// bytes.
+    pub fn is_lossy_utf8(&self, code: u8) -> bool {
+        self.lossy_utf8.contains(&code) || (self.lossy_utf8_all && holds_text(code))
+    }
+
+    /// Apply any value rewrites for `code` to `data`
+    pub fn rewrite<'a>(&self, code: u8, data: &'a [u8]) -> Cow<'a, [u8]> {
+        if !self.rewrites(code) {
+            return Cow::Borrowed(data);
+        }
+        let mut data = data;
+        if self.nul_terminated.contains(&code) {
+            while let Some((&0u8, rest)) = data.split_last() {
+                data = rest;
+            }
+        }
+        if self.swapped_byte_order.contains(&code) {
+            let mut swapped = data.to_vec();
+            swapped.reverse();
+            return Cow::Owned(swapped);
+        }
+        if self.is_lossy_utf8(code) && ::std::str::from_utf8(data).is_err() {
+            return Cow::Owned(String::from_utf8_lossy(data).into_owned().into_bytes());
+        }
+        Cow::Borrowed(data)
+    }
+
+    /// Rewrite a whole `[code, length, value...]` option, returning `None`
+    /// if these quirks leave it alone. A value that grows past 255 bytes
+    /// is split into RFC 3396 fragments.
+    pub fn rewrite_option(&self, option: &[u8]) -> Option<Vec<u8>> {
+        let (code, value) = match option {
+            &[code, len, ref rest @ ..] if self.rewrites(code) => (code, rest.get(..len as usize)?),
+            _ => return None,
+        };
+        let data = self.rewrite(code, value);
+        let mut rewritten = Vec::with_capacity(2 + data.len());
+        put_option(&mut rewritten, code, &data);
+        Some(rewritten)
+    }
+}
+
+/// A set of quirks and the devices they apply to. A profile matches if
+/// every selector it sets matches.
+#[derive(Debug, PartialEq, Clone)]
+pub struct QuirkProfile {
+    pub name: String,
+    /// Matched against the start of option 60
+    pub vendor_class_prefix: Option<Vec<u8>>,
+    /// Matched against the first three bytes of chaddr
+    pub oui: Option<[u8; 3]>,
+    pub quirks: Quirks,
+}
+
+impl QuirkProfile {
+    pub fn matches(&self, vendor_class: Option<&[u8]>, chaddr: &[u8]) -> bool {
+        if self.vendor_class_prefix.is_none() && self.oui.is_none() {
+            return false;
+        }
+        if let Some(ref prefix) = self.vendor_class_prefix {
+            match vendor_class {
+                Some(class) if class.starts_with(prefix) => {},
+                _ => return false,
+            }
+        }
+        if let Some(ref oui) = self.oui {
+            if chaddr.get(..3) != Some(&oui[..]) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct QuirkRegistry {
+    profiles: Vec<QuirkProfile>,
+}
+
+impl QuirkRegistry {
+    pub fn new() -> QuirkRegistry {
+        QuirkRegistry { profiles: Vec::new() }
+    }
+
+    pub fn add(&mut self, profile: QuirkProfile) {
+        self.profiles.push(profile);
+    }
+
+    /// The first registered profile matching the device
+    pub fn select(&self, vendor_class: Option<&[u8]>, chaddr: &[u8]) -> Option<&QuirkProfile> {
+        self.profiles.iter().find(|p| p.matches(vendor_class, chaddr))
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{Quirks, QuirkProfile, QuirkRegistry};
+
+    #[test]
+    fn test_rewrite_nul_terminated() {
+        let quirks = Quirks { nul_terminated: vec![12u8], ..Quirks::default() };
+        assert_eq!(quirks.rewrite_option(&[12u8, 5u8, 84u8, 101u8, 115u8, 116u8, 0u8]),
+                   Some(vec![12u8, 4u8, 84u8, 101u8, 115u8, 116u8]));
+        assert_eq!(quirks.rewrite_option(&[15u8, 2u8, 84u8, 0u8]), None);
+    }
+
+    #[test]
+    fn test_rewrite_swapped_byte_order() {
+        let quirks = Quirks { swapped_byte_order: vec![57u8], ..Quirks::default() };
+        assert_eq!(quirks.rewrite_option(&[57u8, 2u8, 220u8, 5u8]),
+                   Some(vec![57u8, 2u8, 5u8, 220u8]));
+    }
+
+    #[test]
+    fn test_rewrite_lossy_utf8() {
+        let quirks = Quirks { lossy_utf8: vec![12u8], ..Quirks::default() };
+        assert_eq!(quirks.rewrite_option(&[12u8, 3u8, 84u8, 0xffu8, 116u8]),
+                   Some(vec![12u8, 5u8, 84u8, 0xefu8, 0xbfu8, 0xbdu8, 116u8]));
+        assert_eq!(quirks.rewrite(12u8, &[84u8, 116u8]).into_owned(), vec![84u8, 116u8]);
+        assert_eq!(quirks.rewrite(15u8, &[0xffu8]).into_owned(), vec![0xffu8]);
+
+        // Every replacement character is kept, in fragments
+        assert_eq!(quirks.rewrite(12u8, &[0xffu8; 255]).len(), 765);
+        let rewritten = quirks.rewrite_option(&[&[12u8, 255u8][..], &[0xffu8; 255]].concat()).unwrap();
+        assert_eq!(rewritten.len(), 765 + 3 * 2);
+        assert_eq!(&rewritten[..2], &[12u8, 255u8]);
+        assert_eq!(&rewritten[257..259], &[12u8, 255u8]);
+    }
+
+    #[test]
+    fn test_rewrite_lossy_utf8_all() {
+        let quirks = Quirks { lossy_utf8_all: true, ..Quirks::default() };
+        assert_eq!(quirks.rewrite_option(&[15u8, 2u8, 84u8, 0xffu8]),
+                   Some(vec![15u8, 4u8, 84u8, 0xefu8, 0xbfu8, 0xbdu8]));
+        // Options that don't hold text are left alone
+        assert_eq!(quirks.rewrite_option(&[1u8, 4u8, 255u8, 255u8, 255u8, 0u8]), None);
+    }
+
+    #[test]
+    fn test_registry_select() {
+        let mut registry = QuirkRegistry::new();
+        registry.add(QuirkProfile {
+            name: "by vendor class".to_string(),
+            vendor_class_prefix: Some(b"acme-".to_vec()),
+            oui: None,
+            quirks: Quirks::default(),
+        });
+        registry.add(QuirkProfile {
+            name: "by oui".to_string(),
+            vendor_class_prefix: None,
+            oui: Some([0u8, 1u8, 2u8]),
+            quirks: Quirks::default(),
+        });
+        let chaddr = [0u8, 1u8, 2u8, 3u8, 4u8, 5u8];
+        assert_eq!(registry.select(Some(b"acme-phone"), &chaddr).unwrap().name, "by vendor class");
+        assert_eq!(registry.select(Some(b"other"), &chaddr).unwrap().name, "by oui");
+        assert_eq!(registry.select(None, &[9u8, 9u8, 9u8]), None);
+    }
+}
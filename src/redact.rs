@@ -0,0 +1,344 @@
+//! Removing what identifies a subscriber from a message, for keeping
+//! captures long term
+//!
+//! `redact` replaces the client hardware address in `chaddr`, the client
+//! identifier (option 61), the host name (option 12), the domain name in
+//! the Client FQDN option (81), and the Agent Remote ID and Subscriber ID
+//! suboptions of option 82, including any of these overloaded into
+//! `sname` or `file` by option 52. Addresses, the relay's circuit ID and
+//! every other option are left as they are, so captures still show which
+//! server, relay and subnet each exchange went through. Other options
+//! this crate doesn't decode are left alone too.
+
+use DhcpMessage;
+use hwaddr::HardwareAddr;
+use options::{self, DhcpOption, ClientId, OptionOverloadType, RelayAgentInformationSubOption};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Redaction {
+    /// Zero every byte of binary values and replace names with
+    /// "redacted", so nothing of the original is left
+    Mask,
+    /// Replace each value with a keyed SipHash-2-4 of it: the same length
+    /// for binary values, 16 hex digits for names. The same client gets
+    /// the same pseudonym in every capture redacted with the same key,
+    /// and without the key the originals can't be found by hashing every
+    /// possible MAC. Pseudonymous MACs are locally administered unicast
+    /// addresses, so they aren't taken for a real vendor's.
+    Hash { key: [u8; 16] },
+}
+
+fn sip_round(v: &mut [u64; 4]) {
+    v[0] = v[0].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(13) ^ v[0];
+    v[0] = v[0].rotate_left(32);
+    v[2] = v[2].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(16) ^ v[2];
+    v[0] = v[0].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(21) ^ v[0];
+    v[2] = v[2].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(17) ^ v[2];
+    v[2] = v[2].rotate_left(32);
+}
+
+fn le_u64(bytes: &[u8]) -> u64 {
+    bytes.iter().rev().fold(0u64, |n, &b| n << 8 | b as u64)
+}
+
+/// SipHash-2-4, written out here because its output mustn't change with
+/// the Rust version the way `std`'s hashers may
+fn siphash(key: &[u8; 16], data: &[u8]) -> u64 {
+    let k0 = le_u64(&key[..8]);
+    let k1 = le_u64(&key[8..]);
+    let mut v = [k0 ^ 0x736f6d6570736575, k1 ^ 0x646f72616e646f6d, k0 ^ 0x6c7967656e657261, k1 ^ 0x7465646279746573];
+    let mut compress = |m: u64, rounds: usize| {
+        v[3] ^= m;
+        for _ in 0..rounds {
+            sip_round(&mut v);
+        }
+        v[0] ^= m;
+    };
+    let chunks = data.chunks_exact(8);
+    let tail = chunks.remainder();
+    for chunk in chunks {
+        compress(le_u64(chunk), 2);
+    }
+    let mut last = [0u8; 8];
+    last[..tail.len()].copy_from_slice(tail);
+    last[7] = data.len() as u8;
+    compress(le_u64(&last), 2);
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        sip_round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+/// `len` bytes derived from `value`, hashing it with a block counter
+/// for as many blocks as it takes
+fn pseudonym(key: &[u8; 16], value: &[u8], len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(len + 8);
+    let mut block = 0u32;
+    while out.len() < len {
+        let mut input = value.to_vec();
+        input.extend_from_slice(&block.to_be_bytes());
+        out.extend_from_slice(&siphash(key, &input).to_le_bytes());
+        block += 1;
+    }
+    out.truncate(len);
+    out
+}
+
+fn redact_bytes(bytes: &[u8], redaction: Redaction) -> Vec<u8> {
+    match redaction {
+        Redaction::Mask => vec![0u8; bytes.len()],
+        Redaction::Hash { ref key } => pseudonym(key, bytes, bytes.len()),
+    }
+}
+
+fn redact_hardware(addr: &HardwareAddr, redaction: Redaction) -> Vec<u8> {
+    let mut bytes = redact_bytes(&addr.addr, redaction);
+    if let (Redaction::Hash { .. }, Some(_)) = (redaction, addr.mac()) {
+        bytes[0] = (bytes[0] | 0x02) & !0x01;
+    }
+    bytes
+}
+
+fn redact_name(name: &str, redaction: Redaction) -> String {
+    match redaction {
+        Redaction::Mask => "redacted".to_string(),
+        Redaction::Hash { ref key } => format!("{:016x}", siphash(key, name.as_bytes())),
+    }
+}
+
+/// Replace the fields listed in the module docs in `msg`. Bytes of
+/// `chaddr` past the hardware address are zeroed, since clients
+/// sometimes leave identifying data there.
+pub fn redact(msg: &mut DhcpMessage, redaction: Redaction) {
+    let hardware = redact_hardware(&msg.hardware_addr(), redaction);
+    let chaddr_len = msg.chaddr.len();
+    msg.chaddr = hardware;
+    msg.chaddr.resize(chaddr_len, 0u8);
+
+    redact_options(&mut msg.options, redaction);
+    let overload = msg.options.iter().filter_map(|o| match *o {
+        DhcpOption::OptionOverload(t) => Some(t),
+        _ => None,
+    }).next();
+    match overload {
+        Some(OptionOverloadType::File) => redact_field(&mut msg.file, redaction),
+        Some(OptionOverloadType::Sname) => redact_field(&mut msg.sname, redaction),
+        Some(OptionOverloadType::FileAndSname) => {
+            redact_field(&mut msg.file, redaction);
+            redact_field(&mut msg.sname, redaction);
+        },
+        None => {},
+    }
+}
+
+/// Redact the options overloaded into `sname` or `file`. If they don't
+/// parse, or no longer fit once redacted, the field is zeroed instead.
+fn redact_field(field: &mut Vec<u8>, redaction: Redaction) {
+    let len = field.len();
+    let mut encoded = match options::parse(field) {
+        Ok(mut overloaded) => {
+            redact_options(&mut overloaded, redaction);
+            options::encode(&overloaded)
+        },
+        Err(_) => Vec::new(),
+    };
+    if encoded.len() > len {
+        encoded.clear();
+    }
+    encoded.resize(len, 0u8);
+    *field = encoded;
+}
+
+fn redact_options(options: &mut [DhcpOption], redaction: Redaction) {
+    for option in options.iter_mut() {
+        match *option {
+            DhcpOption::HostName(ref mut name) => *name = redact_name(name, redaction),
+            DhcpOption::ClientIdentifier(ref mut id) => {
+                *id = match *id {
+                    ClientId::Hardware(ref addr) => {
+                        ClientId::Hardware(HardwareAddr::new(addr.htype, redact_hardware(addr, redaction)))
+                    },
+                    ClientId::Duid { iaid, ref duid } => ClientId::Duid { iaid, duid: redact_bytes(duid, redaction) },
+                    ClientId::Other { id_type, ref data } => ClientId::Other { id_type, data: redact_bytes(data, redaction) },
+                };
+            },
+            // Client FQDN: flags and the two RCODE bytes, then the name
+            DhcpOption::Unknown { code: 81u8, ref mut data } if data.len() > 3 => {
+                let name = redact_bytes(&data[3..], redaction);
+                data.truncate(3);
+                data.extend(name);
+            },
+            DhcpOption::RelayAgentInformation(ref mut subs) => {
+                for sub in subs.iter_mut() {
+                    match *sub {
+                        RelayAgentInformationSubOption::AgentRemoteID(ref mut id) => *id = redact_bytes(id, redaction),
+                        RelayAgentInformationSubOption::SubscriberID(ref mut id) => *id = redact_name(id, redaction),
+                        _ => {},
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{redact, siphash, Redaction};
+    use std::net::{IpAddr, Ipv4Addr};
+    use DhcpMessage;
+    use op::Op;
+    use htype::Htype;
+    use hwaddr::HardwareAddr;
+    use options::{self, DhcpOption, DhcpMessageTypes, ClientId, OptionOverloadType, RelayAgentInformationSubOption};
+
+    #[test]
+    fn test_siphash() {
+        // From the reference implementation's test vectors
+        let mut key = [0u8; 16];
+        for (i, k) in key.iter_mut().enumerate() {
+            *k = i as u8;
+        }
+        assert_eq!(siphash(&key, &[]), 0x726fdb47dd0e0e31);
+        assert_eq!(siphash(&key, &[0u8]), 0x74f839c593dc67fd);
+        let fifteen: Vec<u8> = (0..15u8).collect();
+        assert_eq!(siphash(&key, &fifteen), 0xa129ca6149be45e5);
+    }
+
+    fn message() -> DhcpMessage {
+        let mac = vec![0u8, 11u8, 130u8, 1u8, 252u8, 66u8];
+        let mut chaddr = mac.clone();
+        chaddr.resize(16, 0u8);
+        DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 1,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(0, 0, 0, 0),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(10, 0, 0, 1),
+            chaddr,
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options: vec![
+                DhcpOption::MessageType(DhcpMessageTypes::Request),
+                DhcpOption::ClientIdentifier(ClientId::Hardware(HardwareAddr::new(Htype::Ethernet_10mb, mac))),
+                DhcpOption::HostName("alices-phone".to_string()),
+                DhcpOption::RequestedIpAddress(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5))),
+                DhcpOption::RelayAgentInformation(vec![
+                    RelayAgentInformationSubOption::AgentCircuitID(b"Gi1/0/24".to_vec()),
+                    RelayAgentInformationSubOption::AgentRemoteID(b"subscriber-17".to_vec()),
+                    RelayAgentInformationSubOption::SubscriberID("17".to_string()),
+                ]),
+                DhcpOption::End,
+            ],
+        }
+    }
+
+    #[test]
+    fn test_redact_mask() {
+        let original = message();
+        let mut msg = original.clone();
+        redact(&mut msg, Redaction::Mask);
+        assert_eq!(msg.chaddr, vec![0u8; 16]);
+        assert_eq!(msg.options[1], DhcpOption::ClientIdentifier(ClientId::Hardware(HardwareAddr::new(Htype::Ethernet_10mb, vec![0u8; 6]))));
+        assert_eq!(msg.options[2], DhcpOption::HostName("redacted".to_string()));
+        assert_eq!(msg.options[3], original.options[3]);
+        assert_eq!(msg.options[4], DhcpOption::RelayAgentInformation(vec![
+            RelayAgentInformationSubOption::AgentCircuitID(b"Gi1/0/24".to_vec()),
+            RelayAgentInformationSubOption::AgentRemoteID(vec![0u8; 13]),
+            RelayAgentInformationSubOption::SubscriberID("redacted".to_string()),
+        ]));
+        assert_eq!((msg.giaddr, msg.xid), (original.giaddr, original.xid));
+    }
+
+    #[test]
+    fn test_redact_hash() {
+        let mut a = message();
+        let mut b = message();
+        let mut c = message();
+        redact(&mut a, Redaction::Hash { key: [1u8; 16] });
+        redact(&mut b, Redaction::Hash { key: [1u8; 16] });
+        redact(&mut c, Redaction::Hash { key: [2u8; 16] });
+        assert_eq!(a, b);
+        assert!(a.chaddr != c.chaddr);
+
+        let mac = a.hardware_addr().mac().unwrap();
+        assert!(mac.0 != [0u8, 11u8, 130u8, 1u8, 252u8, 66u8]);
+        assert_eq!(mac.0[0] & 0x03, 0x02);
+        assert_eq!(&a.chaddr[6..], &[0u8; 10]);
+        // The client identifier's MAC gets the same pseudonym as chaddr's
+        assert_eq!(a.options[1], DhcpOption::ClientIdentifier(ClientId::Hardware(HardwareAddr::new(Htype::Ethernet_10mb, mac.0.to_vec()))));
+        match a.options[2] {
+            DhcpOption::HostName(ref name) => assert_eq!(name.len(), 16),
+            ref other => panic!("{:?}", other),
+        }
+        assert_eq!(DhcpMessage::parse(&a.encode()).unwrap(), a);
+    }
+
+    #[test]
+    fn test_redact_client_fqdn() {
+        let mut fqdn = vec![0x01u8, 0u8, 0u8];
+        fqdn.extend_from_slice(b"\x0calices-phone\x07example\x00");
+        let mut msg = message();
+        msg.options.insert(1, DhcpOption::Unknown { code: 81u8, data: fqdn.clone() });
+        let mut masked = msg.clone();
+        redact(&mut masked, Redaction::Mask);
+        let mut expected = vec![0x01u8, 0u8, 0u8];
+        expected.resize(fqdn.len(), 0u8);
+        assert_eq!(masked.options[1], DhcpOption::Unknown { code: 81u8, data: expected });
+
+        redact(&mut msg, Redaction::Hash { key: [1u8; 16] });
+        match msg.options[1] {
+            DhcpOption::Unknown { code: 81u8, ref data } => {
+                assert_eq!(data.len(), fqdn.len());
+                assert_eq!(&data[..3], &fqdn[..3]);
+                assert!(data[3..] != fqdn[3..]);
+            },
+            ref other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_redact_overloaded() {
+        let mut msg = message();
+        msg.options.insert(1, DhcpOption::OptionOverload(OptionOverloadType::File));
+        msg.file = options::encode(&[DhcpOption::HostName("alices-phone".to_string()), DhcpOption::End]);
+        msg.file.resize(128, 0u8);
+        // Not overloaded, so left alone even though it looks like options
+        msg.sname = msg.file[..64].to_vec();
+        let sname = msg.sname.clone();
+
+        let mut masked = msg.clone();
+        redact(&mut masked, Redaction::Mask);
+        assert_eq!(masked.file.len(), 128);
+        assert_eq!(options::parse(&masked.file).unwrap(), vec![DhcpOption::HostName("redacted".to_string()), DhcpOption::End]);
+        assert_eq!(masked.sname, sname);
+
+        redact(&mut msg, Redaction::Hash { key: [1u8; 16] });
+        match options::parse(&msg.file).unwrap()[0] {
+            DhcpOption::HostName(ref name) => assert_eq!(name.len(), 16),
+            ref other => panic!("{:?}", other),
+        }
+
+        // Too long to fit once hashed, so the field is zeroed
+        let mut msg = message();
+        msg.options.insert(1, DhcpOption::OptionOverload(OptionOverloadType::Sname));
+        let names: Vec<DhcpOption> = "abcd".chars().flat_map(|c| vec![
+            DhcpOption::HostName(c.to_string()),
+            DhcpOption::MessageType(DhcpMessageTypes::Request),
+        ]).collect();
+        msg.sname = options::encode(&names);
+        msg.sname.resize(64, 0u8);
+        redact(&mut msg, Redaction::Hash { key: [1u8; 16] });
+        assert_eq!(msg.sname, vec![0u8; 64]);
+    }
+}
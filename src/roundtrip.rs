@@ -0,0 +1,86 @@
+//! Encode/parse round-trip checks
+//!
+//! These are meant to be called from fuzz targets (see `fuzz/`), with
+//! input from raw bytes or from `arbitrary`-generated values.
+//!
+//! Some values can't be represented on the wire, e.g. IPv6 addresses in
+//! DHCPv4 address options. So when starting from values, the check
+//! encodes and parses once to normalize them, then requires a second
+//! encode/parse to give the same result.
+
+use {DhcpMessage, Result, Error};
+use options::{self, DhcpOption};
+use options::raw::RawOptionsIter;
+
+/// Options parsed from `bytes` must survive being encoded and parsed
+/// again. Back-to-back repeats of a code, e.g. a router list followed by
+/// an empty one, come back as one option since the parser joins them as
+/// RFC 3396 fragments, so those only have to carry the same bytes.
+pub fn check_parse_round_trip(bytes: &[u8]) -> Result<()> {
+    let parsed = options::parse(bytes)?;
+    let reparsed = options::parse(&options::encode(&parsed))?;
+    if parsed != reparsed && joined_values(&parsed) != joined_values(&reparsed) {
+        return Err(Error::ParseError(format!("parsed {:?} but re-parsed {:?}", parsed, reparsed)));
+    }
+    Ok(())
+}
+
+/// The `(code, value)` pairs `options` encode to, with back-to-back
+/// repeats of a code joined
+fn joined_values(options: &[DhcpOption]) -> Vec<(u8, Vec<u8>)> {
+    let mut values: Vec<(u8, Vec<u8>)> = Vec::new();
+    for (code, data) in RawOptionsIter::new(&options::encode(options)) {
+        match values.last_mut() {
+            Some(&mut (last, ref mut joined)) if last == code && code != 0u8 && code != 255u8 => {
+                joined.extend_from_slice(data);
+            },
+            _ => values.push((code, data.to_vec())),
+        }
+    }
+    values
+}
+
+/// Encoding and parsing `options` must be stable after the first pass
+pub fn check_encode_round_trip(options: &[DhcpOption]) -> Result<()> {
+    let normalized = options::parse(&options::encode(options))?;
+    check_parse_round_trip(&options::encode(&normalized))
+}
+
+/// Encoding and parsing `message` must be stable after the first pass
+pub fn check_message_round_trip(message: &DhcpMessage) -> Result<()> {
+    let normalized = DhcpMessage::parse(&message.encode())?;
+    let reparsed = DhcpMessage::parse(&normalized.encode())?;
+    if normalized != reparsed {
+        return Err(Error::ParseError(format!("parsed {:?} but re-parsed {:?}", normalized, reparsed)));
+    }
+    Ok(())
+}
+
+#[cfg(test)] mod tests {
+    use super::{check_parse_round_trip, check_encode_round_trip};
+    use options::DhcpOption;
+    use std::net::{IpAddr, Ipv6Addr};
+
+    #[test]
+    fn test_check_parse_round_trip() {
+        let bytes = [53u8, 1u8, 1u8, 77u8, 4u8, 84u8, 101u8, 115u8, 116u8, 254u8, 1u8, 0u8, 255u8];
+        check_parse_round_trip(&bytes).unwrap();
+    }
+
+    #[test]
+    fn test_check_parse_round_trip_empty_repeat() {
+        // A router list followed by an empty one, which comes back joined
+        check_parse_round_trip(&[3u8, 5u8, 1u8, 2u8, 3u8, 4u8, 3u8, 0u8]).unwrap();
+        check_parse_round_trip(&[3u8, 4u8, 1u8, 2u8, 3u8, 4u8, 3u8, 0u8]).unwrap();
+    }
+
+    #[test]
+    fn test_check_encode_round_trip_normalizes() {
+        let options = vec![
+            DhcpOption::Router(ip_list![IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1))]),
+            DhcpOption::HostName("x".repeat(300)),
+            DhcpOption::End,
+        ];
+        check_encode_round_trip(&options).unwrap();
+    }
+}
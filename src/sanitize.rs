@@ -0,0 +1,129 @@
+//! Log-safe copies of fields the sender controls
+//!
+//! Host names, domain names, vendor classes and the option 82 circuit and
+//! remote IDs are whatever the client or relay put there, and end up in
+//! syslog lines and web UIs. These give them back as printable ASCII:
+//! `\` becomes `\\`, tab, CR and LF become `\t`, `\r` and `\n`, and any
+//! other byte outside space to `~`, including all non-ASCII, becomes
+//! `\xNN`. The escaping can be undone, so two different values never
+//! log the same. Markup is left alone; quoting for HTML is the UI's job.
+
+use DhcpMessage;
+use options::{DhcpOption, RelayAgentInformationSubOption};
+
+/// `bytes` escaped as described in the module docs
+pub fn escape(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len());
+    for &b in bytes {
+        match b {
+            b'\\' => out.push_str("\\\\"),
+            b'\t' => out.push_str("\\t"),
+            b'\r' => out.push_str("\\r"),
+            b'\n' => out.push_str("\\n"),
+            0x20..=0x7e => out.push(b as char),
+            _ => out.push_str(&format!("\\x{:02x}", b)),
+        }
+    }
+    out
+}
+
+/// Option 12
+pub fn host_name(msg: &DhcpMessage) -> Option<String> {
+    msg.options.iter().filter_map(|o| match o {
+        DhcpOption::HostName(name) => Some(escape(name.as_bytes())),
+        _ => None,
+    }).next()
+}
+
+/// Option 15
+pub fn domain_name(msg: &DhcpMessage) -> Option<String> {
+    msg.options.iter().filter_map(|o| match o {
+        DhcpOption::DomainName(name) => Some(escape(name.as_bytes())),
+        _ => None,
+    }).next()
+}
+
+/// Option 60
+pub fn vendor_class(msg: &DhcpMessage) -> Option<String> {
+    msg.options.iter().filter_map(|o| match o {
+        DhcpOption::ClassIdentifier(class) => Some(escape(class)),
+        _ => None,
+    }).next()
+}
+
+fn relay_suboption<F>(msg: &DhcpMessage, f: F) -> Option<String>
+    where F: Fn(&RelayAgentInformationSubOption) -> Option<&Vec<u8>> {
+    msg.options.iter().filter_map(|o| match o {
+        DhcpOption::RelayAgentInformation(subs) => subs.iter().filter_map(&f).next(),
+        _ => None,
+    }).next().map(|data| escape(data))
+}
+
+/// The Agent Circuit ID suboption of option 82, as sent rather than
+/// decoded
+pub fn circuit_id(msg: &DhcpMessage) -> Option<String> {
+    relay_suboption(msg, |s| match s {
+        RelayAgentInformationSubOption::AgentCircuitID(d) => Some(d),
+        _ => None,
+    })
+}
+
+/// The Agent Remote ID suboption of option 82, as sent rather than
+/// decoded
+pub fn remote_id(msg: &DhcpMessage) -> Option<String> {
+    relay_suboption(msg, |s| match s {
+        RelayAgentInformationSubOption::AgentRemoteID(d) => Some(d),
+        _ => None,
+    })
+}
+
+#[cfg(test)] mod tests {
+    use super::{escape, host_name, vendor_class, circuit_id, remote_id, domain_name};
+    use std::net::Ipv4Addr;
+    use DhcpMessage;
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, RelayAgentInformationSubOption};
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape(b"laptop-01"), "laptop-01");
+        assert_eq!(escape(b"a\nFAKE LOG LINE\r\x1b[2J\\"), "a\\nFAKE LOG LINE\\r\\x1b[2J\\\\");
+        assert_eq!(escape("caf\u{e9}".as_bytes()), "caf\\xc3\\xa9");
+        assert_eq!(escape(b"\x00\x7f\xff\t"), "\\x00\\x7f\\xff\\t");
+    }
+
+    #[test]
+    fn test_message_fields() {
+        let msg = DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 1,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(0, 0, 0, 0),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(10, 0, 0, 1),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options: vec![
+                DhcpOption::HostName("<script>\n".to_string()),
+                DhcpOption::ClassIdentifier(b"MSFT 5.0\x00".to_vec()),
+                DhcpOption::RelayAgentInformation(vec![
+                    RelayAgentInformationSubOption::AgentCircuitID(vec![0u8, 4u8, 0u8, 100u8, 1u8, 24u8]),
+                    RelayAgentInformationSubOption::AgentRemoteID(b"user\x07".to_vec()),
+                ]),
+                DhcpOption::End,
+            ],
+        };
+        assert_eq!(host_name(&msg), Some("<script>\\n".to_string()));
+        assert_eq!(domain_name(&msg), None);
+        assert_eq!(vendor_class(&msg), Some("MSFT 5.0\\x00".to_string()));
+        assert_eq!(circuit_id(&msg), Some("\\x00\\x04\\x00d\\x01\\x18".to_string()));
+        assert_eq!(remote_id(&msg), Some("user\\x07".to_string()));
+    }
+}
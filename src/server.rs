@@ -0,0 +1,247 @@
+//! Building a server's replies
+//!
+//! Given the client's DISCOVER or REQUEST and what the server decided to
+//! hand out, `offer`, `ack` and `nak` fill in the rest as RFC 2131
+//! section 4.3 asks: the header fields copied from the request, the
+//! server identifier, the lease timers, and the client identifier (RFC
+//! 6842) and relay agent information (RFC 3046) echoed back.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddrV4};
+use DhcpMessage;
+use op::Op;
+use options::{DhcpOption, DhcpMessageTypes};
+
+const BROADCAST_FLAG: u16 = 0x8000;
+const INFINITE_LEASE: u32 = 0xffffffff;
+
+/// The address and configuration the server is giving a client
+#[derive(Debug, PartialEq)]
+pub struct LeaseDecision {
+    pub address: Ipv4Addr,
+    /// In seconds; 0xffffffff is infinite
+    pub lease_time: u32,
+    /// T1, half the lease time if not set. Infinite leases have no T1,
+    /// so this is ignored for them.
+    pub renewal_time: Option<u32>,
+    /// T2, seven eighths of the lease time if not set. Ignored for
+    /// infinite leases, as `renewal_time` is.
+    pub rebinding_time: Option<u32>,
+    /// Everything else, e.g. subnet mask, router and DNS servers
+    pub options: Vec<DhcpOption>,
+}
+
+impl LeaseDecision {
+    pub fn new(address: Ipv4Addr, lease_time: u32) -> LeaseDecision {
+        LeaseDecision { address, lease_time, renewal_time: None, rebinding_time: None, options: Vec::new() }
+    }
+}
+
+/// An OFFER of `lease` in reply to a DISCOVER
+pub fn offer(discover: &DhcpMessage, server: Ipv4Addr, lease: LeaseDecision) -> DhcpMessage {
+    with_lease(discover, DhcpMessageTypes::Offer, server, lease)
+}
+
+/// An ACK of `lease` in reply to a REQUEST. `ciaddr` is kept, since
+/// a renewing client has it filled in.
+pub fn ack(request: &DhcpMessage, server: Ipv4Addr, lease: LeaseDecision) -> DhcpMessage {
+    let mut reply = with_lease(request, DhcpMessageTypes::Ack, server, lease);
+    reply.ciaddr = request.ciaddr;
+    reply
+}
+
+/// A NAK in reply to a REQUEST, optionally saying why. Through a relay
+/// the broadcast bit is set, so the relay broadcasts it to the client.
+pub fn nak(request: &DhcpMessage, server: Ipv4Addr, message: Option<&str>) -> DhcpMessage {
+    let mut reply = reply(request);
+    if request.giaddr != Ipv4Addr::new(0, 0, 0, 0) {
+        reply.flags |= BROADCAST_FLAG;
+    }
+    reply.options.push(DhcpOption::MessageType(DhcpMessageTypes::Nak));
+    reply.options.push(DhcpOption::ServerIdentifier(IpAddr::V4(server)));
+    if let Some(message) = message {
+        reply.options.push(DhcpOption::Message(message.to_string()));
+    }
+    echo(request, &mut reply);
+    reply
+}
+
+/// Where to send `reply` to `request`, per RFC 2131 section 4.1: back
+/// to the relay if there is one, otherwise to `ciaddr` if the client has
+/// one (except for a NAK), otherwise broadcast if the client asked for
+/// it or this is a NAK, and otherwise to `yiaddr`. Unicast to `yiaddr`
+/// only works if the server can add the client's ARP entry itself.
+pub fn destination(request: &DhcpMessage, reply: &DhcpMessage) -> SocketAddrV4 {
+    let unspecified = Ipv4Addr::new(0, 0, 0, 0);
+    let is_nak = reply.options.contains(&DhcpOption::MessageType(DhcpMessageTypes::Nak));
+    if request.giaddr != unspecified {
+        SocketAddrV4::new(request.giaddr, 67)
+    } else if !is_nak && request.ciaddr != unspecified {
+        SocketAddrV4::new(request.ciaddr, 68)
+    } else if is_nak || request.flags & BROADCAST_FLAG != 0 {
+        SocketAddrV4::new(Ipv4Addr::new(255, 255, 255, 255), 68)
+    } else {
+        SocketAddrV4::new(reply.yiaddr, 68)
+    }
+}
+
+/// The header of a reply to `request`, with no options yet
+fn reply(request: &DhcpMessage) -> DhcpMessage {
+    let unspecified = Ipv4Addr::new(0, 0, 0, 0);
+    DhcpMessage {
+        op: Op::BootReply,
+        htype: request.htype,
+        hlen: request.hlen,
+        hops: 0,
+        xid: request.xid,
+        secs: 0,
+        flags: request.flags,
+        ciaddr: unspecified,
+        yiaddr: unspecified,
+        siaddr: unspecified,
+        giaddr: request.giaddr,
+        chaddr: request.chaddr.clone(),
+        sname: vec![0u8; 64],
+        file: vec![0u8; 128],
+        options: Vec::new(),
+    }
+}
+
+fn with_lease(request: &DhcpMessage, message_type: DhcpMessageTypes, server: Ipv4Addr, lease: LeaseDecision) -> DhcpMessage {
+    let mut reply = reply(request);
+    reply.yiaddr = lease.address;
+    reply.options.push(DhcpOption::MessageType(message_type));
+    reply.options.push(DhcpOption::ServerIdentifier(IpAddr::V4(server)));
+    reply.options.push(DhcpOption::IpAddressLeaseTime(lease.lease_time));
+    if lease.lease_time != INFINITE_LEASE {
+        let t1 = lease.renewal_time.unwrap_or(lease.lease_time / 2);
+        let t2 = lease.rebinding_time.unwrap_or((lease.lease_time as u64 * 7 / 8) as u32);
+        reply.options.push(DhcpOption::RenewalTimeValue(t1));
+        reply.options.push(DhcpOption::RebindingTimeValue(t2));
+    }
+    reply.options.extend(lease.options.into_iter().filter(|o| !matches!(o, DhcpOption::Pad | DhcpOption::End)));
+    echo(request, &mut reply);
+    reply
+}
+
+/// Copy the client identifier and relay agent information from the
+/// request, then end the options
+fn echo(request: &DhcpMessage, reply: &mut DhcpMessage) {
+    for option in &request.options {
+        match option {
+            &DhcpOption::ClientIdentifier(_) | &DhcpOption::RelayAgentInformation(_) => {
+                reply.options.push(option.clone());
+            },
+            _ => {},
+        }
+    }
+    reply.options.push(DhcpOption::End);
+}
+
+#[cfg(test)] mod tests {
+    use super::{offer, ack, nak, destination, LeaseDecision};
+    use DhcpMessage;
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, DhcpMessageTypes, RelayAgentInformationSubOption};
+    use validate::validate;
+    use std::net::{IpAddr, Ipv4Addr, SocketAddrV4};
+
+    fn request(message_type: DhcpMessageTypes, options: Vec<DhcpOption>) -> DhcpMessage {
+        let mut all = vec![DhcpOption::MessageType(message_type)];
+        all.extend(options);
+        all.push(DhcpOption::End);
+        DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 1,
+            xid: 0x3903f326,
+            secs: 3,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(0, 0, 0, 0),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0u8; 64],
+            file: vec![0u8; 128],
+            options: all,
+        }
+    }
+
+    fn server() -> Ipv4Addr {
+        Ipv4Addr::new(10, 0, 0, 1)
+    }
+
+    #[test]
+    fn test_offer() {
+        let mut discover = request(DhcpMessageTypes::Discover, vec![
+            DhcpOption::ParamRequestList(vec![1, 3]),
+            DhcpOption::RelayAgentInformation(vec![RelayAgentInformationSubOption::AgentCircuitID(vec![1, 2])]),
+        ]);
+        discover.giaddr = Ipv4Addr::new(10, 0, 0, 254);
+        let mut lease = LeaseDecision::new(Ipv4Addr::new(10, 0, 0, 5), 3600);
+        lease.options.push(DhcpOption::Router(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 254))]));
+
+        let offer = offer(&discover, server(), lease);
+        assert_eq!(validate(&offer), vec![]);
+        assert_eq!(offer.xid, discover.xid);
+        assert_eq!(offer.chaddr, discover.chaddr);
+        assert_eq!(offer.giaddr, discover.giaddr);
+        assert_eq!(offer.hops, 0);
+        assert_eq!(offer.yiaddr, Ipv4Addr::new(10, 0, 0, 5));
+        assert_eq!(offer.options, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Offer),
+            DhcpOption::ServerIdentifier(IpAddr::V4(server())),
+            DhcpOption::IpAddressLeaseTime(3600),
+            DhcpOption::RenewalTimeValue(1800),
+            DhcpOption::RebindingTimeValue(3150),
+            DhcpOption::Router(ip_list![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 254))]),
+            DhcpOption::RelayAgentInformation(vec![RelayAgentInformationSubOption::AgentCircuitID(vec![1, 2])]),
+            DhcpOption::End,
+        ]);
+        assert_eq!(destination(&discover, &offer), SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 254), 67));
+    }
+
+    #[test]
+    fn test_ack_renewal() {
+        let mut renew = request(DhcpMessageTypes::Request, vec![]);
+        renew.ciaddr = Ipv4Addr::new(10, 0, 0, 5);
+        // Broadcast was asked for, but the client has an address to unicast to
+        renew.flags = 0x8000;
+        let mut lease = LeaseDecision::new(Ipv4Addr::new(10, 0, 0, 5), 0xffffffff);
+        lease.renewal_time = Some(100);
+
+        let ack = ack(&renew, server(), lease);
+        assert_eq!(validate(&ack), vec![]);
+        assert_eq!(ack.ciaddr, renew.ciaddr);
+        // An infinite lease has no T1, so the renewal time given is dropped
+        assert!(!ack.options.contains(&DhcpOption::RenewalTimeValue(100)));
+        assert_eq!(ack.options, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Ack),
+            DhcpOption::ServerIdentifier(IpAddr::V4(server())),
+            DhcpOption::IpAddressLeaseTime(0xffffffff),
+            DhcpOption::End,
+        ]);
+        assert_eq!(destination(&renew, &ack), SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 5), 68));
+    }
+
+    #[test]
+    fn test_nak() {
+        let mut request = request(DhcpMessageTypes::Request, vec![
+            DhcpOption::RequestedIpAddress(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 5))),
+        ]);
+        request.ciaddr = Ipv4Addr::new(192, 168, 1, 5);
+        let nak = nak(&request, server(), Some("wrong network"));
+        assert_eq!(validate(&nak), vec![]);
+        assert_eq!(nak.ciaddr, Ipv4Addr::new(0, 0, 0, 0));
+        assert_eq!(nak.yiaddr, Ipv4Addr::new(0, 0, 0, 0));
+        assert_eq!(nak.flags, 0);
+        assert_eq!(destination(&request, &nak), SocketAddrV4::new(Ipv4Addr::new(255, 255, 255, 255), 68));
+
+        request.giaddr = Ipv4Addr::new(192, 168, 1, 254);
+        let nak = super::nak(&request, server(), None);
+        assert_eq!(nak.flags, 0x8000);
+        assert_eq!(destination(&request, &nak), SocketAddrV4::new(Ipv4Addr::new(192, 168, 1, 254), 67));
+    }
+}
@@ -0,0 +1,152 @@
+//! Counting what's on the wire
+//!
+//! Feed parsed messages into a `Stats` and read its fields for a
+//! dashboard or metrics export: how many messages carried each option,
+//! how many of each message type there were, and which vendor classes
+//! sent them. Counters from several threads or capture files can be
+//! combined with `merge`.
+
+use std::collections::BTreeMap;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+use {DhcpMessage, RawMessage};
+use options::DhcpOption;
+use sanitize::escape;
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Stats {
+    pub messages: u64,
+    /// Messages carrying each option code, counted once per message
+    /// however often the code appears in it. Pad and End aren't counted.
+    pub options: BTreeMap<u8, u64>,
+    /// Messages of each type, by the value of option 53
+    pub message_types: BTreeMap<u8, u64>,
+    /// Messages without option 53, i.e. BOOTP
+    pub without_message_type: u64,
+    /// Messages with each vendor class (option 60), escaped as in
+    /// `sanitize::escape` since it's whatever the client sent
+    pub vendor_classes: BTreeMap<String, u64>,
+}
+
+impl Stats {
+    pub fn new() -> Stats {
+        Stats::default()
+    }
+
+    pub fn add(&mut self, message: &DhcpMessage) {
+        self.add_options(&message.options);
+    }
+
+    pub fn add_raw(&mut self, message: &RawMessage) {
+        self.add_options(&message.options);
+    }
+
+    fn add_options(&mut self, options: &[DhcpOption]) {
+        self.messages += 1;
+        let mut codes: Vec<u8> = options.iter().map(|o| o.code()).filter(|&c| c != 0u8 && c != 255u8).collect();
+        codes.sort();
+        codes.dedup();
+        for code in codes {
+            *self.options.entry(code).or_insert(0) += 1;
+        }
+
+        let message_type = options.iter().filter_map(|o| match o {
+            &DhcpOption::MessageType(t) => Some(u8::from(t)),
+            _ => None,
+        }).next();
+        match message_type {
+            Some(t) => *self.message_types.entry(t).or_insert(0) += 1,
+            None => self.without_message_type += 1,
+        }
+
+        let vendor_class = options.iter().filter_map(|o| match o {
+            DhcpOption::ClassIdentifier(class) => Some(escape(class)),
+            _ => None,
+        }).next();
+        if let Some(class) = vendor_class {
+            *self.vendor_classes.entry(class).or_insert(0) += 1;
+        }
+    }
+
+    /// Add the counts in `other` to these
+    pub fn merge(&mut self, other: &Stats) {
+        self.messages += other.messages;
+        self.without_message_type += other.without_message_type;
+        for (&code, &n) in &other.options {
+            *self.options.entry(code).or_insert(0) += n;
+        }
+        for (&t, &n) in &other.message_types {
+            *self.message_types.entry(t).or_insert(0) += n;
+        }
+        for (class, &n) in &other.vendor_classes {
+            *self.vendor_classes.entry(class.clone()).or_insert(0) += n;
+        }
+    }
+
+    /// The `n` most common vendor classes, most common first, with ties
+    /// in name order
+    pub fn top_vendor_classes(&self, n: usize) -> Vec<(&str, u64)> {
+        let mut classes: Vec<(&str, u64)> = self.vendor_classes.iter().map(|(c, &count)| (&c[..], count)).collect();
+        classes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        classes.truncate(n);
+        classes
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::Stats;
+    use std::net::Ipv4Addr;
+    use DhcpMessage;
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, DhcpMessageTypes};
+
+    fn message(options: Vec<DhcpOption>) -> DhcpMessage {
+        DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(0, 0, 0, 0),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options,
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let discover = |class: &[u8]| message(vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Discover),
+            DhcpOption::ClassIdentifier(class.to_vec()),
+            DhcpOption::HostName("a".to_string()),
+            DhcpOption::HostName("b".to_string()),
+            DhcpOption::Pad,
+            DhcpOption::End,
+        ]);
+        let mut stats = Stats::new();
+        stats.add(&discover(b"MSFT 5.0"));
+        stats.add(&discover(b"android-dhcp-13"));
+        stats.add(&discover(b"MSFT 5.0"));
+        stats.add(&message(vec![DhcpOption::End]));
+
+        assert_eq!(stats.messages, 4);
+        assert_eq!(stats.options.iter().map(|(&c, &n)| (c, n)).collect::<Vec<_>>(), vec![(12u8, 3), (53u8, 3), (60u8, 3)]);
+        assert_eq!(stats.message_types.get(&1u8), Some(&3));
+        assert_eq!(stats.without_message_type, 1);
+        assert_eq!(stats.top_vendor_classes(1), vec![("MSFT 5.0", 2)]);
+
+        let mut total = stats.clone();
+        total.merge(&stats);
+        assert_eq!(total.messages, 8);
+        assert_eq!(total.top_vendor_classes(5), vec![("MSFT 5.0", 4), ("android-dhcp-13", 2)]);
+    }
+}
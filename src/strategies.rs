@@ -0,0 +1,164 @@
+//! `proptest` strategies for options and messages
+//!
+//! Values come from the `arbitrary` impls and are then encoded and parsed
+//! once, so they are what this crate would parse off the wire, e.g.
+//! without IPv6 addresses in DHCPv4 address options. The near-valid
+//! strategies take the wire form of a valid value and break it in one
+//! place: truncating it, flipping bits in a byte, or changing an option's
+//! length field.
+
+use arbitrary::{Arbitrary, Unstructured};
+use proptest::prelude::*;
+use proptest::collection::vec;
+use {DhcpMessage, OPTIONS_OFFSET};
+use options::{self, DhcpOption};
+use options::raw::next_raw_option;
+
+fn from_arbitrary<T: for<'a> Arbitrary<'a> + ::std::fmt::Debug>() -> impl Strategy<Value = T> {
+    vec(any::<u8>(), 0..256).prop_filter_map("arbitrary rejected the input", |bytes| {
+        T::arbitrary(&mut Unstructured::new(&bytes)).ok()
+    })
+}
+
+/// A single option other than Pad and End
+pub fn option() -> BoxedStrategy<DhcpOption> {
+    from_arbitrary::<DhcpOption>().prop_filter_map("does not survive encoding", |option| {
+        let mut bytes = Vec::new();
+        option.encode(&mut bytes);
+        match options::parse(&bytes) {
+            Ok(ref parsed) if parsed.len() == 1 && parsed[0] != DhcpOption::Pad && parsed[0] != DhcpOption::End => {
+                Some(parsed[0].clone())
+            },
+            _ => None,
+        }
+    }).boxed()
+}
+
+/// Up to `max` options followed by End
+pub fn options(max: usize) -> BoxedStrategy<Vec<DhcpOption>> {
+    vec(option(), 0..max + 1).prop_map(|mut options| {
+        options.push(DhcpOption::End);
+        options
+    }).boxed()
+}
+
+/// A message with up to 8 options
+pub fn message() -> BoxedStrategy<DhcpMessage> {
+    (from_arbitrary::<DhcpMessage>(), options(8)).prop_filter_map("does not survive encoding", |(mut msg, options)| {
+        msg.options = options;
+        DhcpMessage::parse(&msg.encode()).ok()
+    }).boxed()
+}
+
+/// One way of breaking a valid encoding
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Corruption {
+    /// Cut the bytes short, at the given index modulo their length
+    Truncate(usize),
+    /// XOR the byte at the given index (modulo the length) with a
+    /// non-zero mask
+    FlipBits(usize, u8),
+    /// Add to the length field of an option, chosen by index modulo the
+    /// number of options that have one
+    Length(usize, i8),
+}
+
+pub fn corruption() -> BoxedStrategy<Corruption> {
+    prop_oneof![
+        any::<usize>().prop_map(Corruption::Truncate),
+        (any::<usize>(), 1u8..).prop_map(|(i, mask)| Corruption::FlipBits(i, mask)),
+        (any::<usize>(), any::<i8>().prop_filter("no change", |&d| d != 0)).prop_map(|(i, d)| Corruption::Length(i, d)),
+    ].boxed()
+}
+
+/// Apply `corruption` to `bytes`, whose options start at `options_start`.
+/// A `Length` corruption where there are no options flips bits in the
+/// last byte instead.
+pub fn corrupt(bytes: &mut Vec<u8>, options_start: usize, corruption: Corruption) {
+    if bytes.is_empty() {
+        return;
+    }
+    match corruption {
+        Corruption::Truncate(i) => {
+            let len = i % bytes.len();
+            bytes.truncate(len);
+        },
+        Corruption::FlipBits(i, mask) => {
+            let len = bytes.len();
+            bytes[i % len] ^= mask;
+        },
+        Corruption::Length(i, delta) => {
+            let mut length_fields = Vec::new();
+            let mut offset = options_start;
+            while let Some((raw, _)) = next_raw_option(&bytes[offset..]) {
+                if raw.code == 255u8 {
+                    break;
+                }
+                if raw.code != 0u8 {
+                    length_fields.push(offset + 1);
+                }
+                offset += if raw.code == 0u8 { 1 } else { 2 + raw.data.len() };
+            }
+            match length_fields.get(i % length_fields.len().max(1)) {
+                Some(&field) => bytes[field] = bytes[field].wrapping_add(delta as u8),
+                None => {
+                    let last = bytes.len() - 1;
+                    bytes[last] ^= 0xff;
+                },
+            }
+        },
+    }
+}
+
+/// The wire form of an `option()` with one `corruption()` applied
+pub fn near_valid_option_bytes() -> BoxedStrategy<Vec<u8>> {
+    (option(), corruption()).prop_map(|(option, corruption)| {
+        let mut bytes = Vec::new();
+        option.encode(&mut bytes);
+        corrupt(&mut bytes, 0, corruption);
+        bytes
+    }).boxed()
+}
+
+/// The wire form of a `message()` with one `corruption()` applied
+pub fn near_valid_message_bytes() -> BoxedStrategy<Vec<u8>> {
+    (message(), corruption()).prop_map(|(msg, corruption)| {
+        let mut bytes = msg.encode();
+        corrupt(&mut bytes, OPTIONS_OFFSET, corruption);
+        bytes
+    }).boxed()
+}
+
+#[cfg(test)] mod tests {
+    use super::*;
+    use roundtrip::{check_encode_round_trip, check_message_round_trip};
+
+    #[test]
+    fn test_corrupt_length() {
+        let mut bytes = vec![0u8, 53u8, 1u8, 1u8, 12u8, 1u8, b'a', 255u8];
+        corrupt(&mut bytes, 0, Corruption::Length(1, -1));
+        assert_eq!(bytes, vec![0u8, 53u8, 1u8, 1u8, 12u8, 0u8, b'a', 255u8]);
+    }
+
+    proptest! {
+        #[test]
+        fn options_round_trip(options in options(8)) {
+            prop_assert!(check_encode_round_trip(&options).is_ok());
+        }
+
+        #[test]
+        fn messages_round_trip(msg in message()) {
+            prop_assert!(check_message_round_trip(&msg).is_ok());
+        }
+
+        #[test]
+        fn near_valid_messages_parse_without_panicking(bytes in near_valid_message_bytes()) {
+            let _ = DhcpMessage::parse(&bytes);
+        }
+
+        #[test]
+        fn near_valid_options_parse_without_panicking(bytes in near_valid_option_bytes()) {
+            let _ = options::parse(&bytes);
+        }
+    }
+}
@@ -0,0 +1,557 @@
+//! Turn a captured message into a unit test for this crate
+//!
+//! `message_test` lays the bytes out field by field the way the tests in
+//! `lib.rs` do, and writes the current parse of them as the expected
+//! value. Check the expected value by hand before pasting the test in:
+//! it records what the parser does today, not what it should do.
+//! With the `pcap` feature, `capture_test` picks the message out of a
+//! capture file.
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Display};
+use serde::ser::{self, Serialize, Serializer};
+use options::raw::next_raw_option;
+use {DhcpMessage, Result, Error, OPTIONS_OFFSET};
+#[cfg(feature = "pcap")]
+use pcap;
+
+/// Where the types that can show up in a message live, for the `use`
+/// lines at the top of the test. `message_test` fails on a type missing
+/// from here rather than write a test that doesn't compile.
+const TYPE_PATHS: [(&str, &str); 33] = [
+    ("DhcpMessage", "DhcpMessage"),
+    ("Op", "op::Op"),
+    ("Htype", "htype::Htype"),
+    ("DhcpOption", "options::DhcpOption"),
+    ("DhcpMessageTypes", "options::DhcpMessageTypes"),
+    ("NodeType", "options::NodeType"),
+    ("OptionOverloadType", "options::OptionOverloadType"),
+    ("ClientId", "options::ClientId"),
+    ("SipServers", "options::SipServers"),
+    ("UserClasses", "options::UserClasses"),
+    ("AutoConfigure", "options::AutoConfigure"),
+    ("NameService", "options::NameService"),
+    ("LeasequeryStatus", "options::LeasequeryStatus"),
+    ("LeaseState", "options::LeaseState"),
+    ("NetwareIpSubOption", "options::NetwareIpSubOption"),
+    ("SubnetAllocationSubOption", "options::SubnetAllocationSubOption"),
+    ("SubnetPrefix", "options::SubnetPrefix"),
+    ("RelayAgentInformationSubOption", "options::RelayAgentInformationSubOption"),
+    ("VirtualSubnetSelection", "options::VirtualSubnetSelection"),
+    ("DeviceClass", "options::DeviceClass"),
+    ("AgentFlags", "options::AgentFlags"),
+    ("CivicLocation", "options::CivicLocation"),
+    ("CivicAddressElement", "options::CivicAddressElement"),
+    ("CivicWhat", "options::CivicWhat"),
+    ("CaType", "options::CaType"),
+    ("GeoLocation", "options::GeoLocation"),
+    ("AltitudeType", "options::AltitudeType"),
+    ("Datum", "options::Datum"),
+    ("MacAddress", "hwaddr::MacAddress"),
+    ("HardwareAddr", "hwaddr::HardwareAddr"),
+    ("IpAddr", "std::net::IpAddr"),
+    ("Ipv4Addr", "std::net::Ipv4Addr"),
+    ("Ipv6Addr", "std::net::Ipv6Addr"),
+];
+
+/// Column the field comments start at, as in the tests in `lib.rs`
+const COMMENT_COLUMN: usize = 52;
+const MAX_WIDTH: usize = 100;
+
+/// A `#[test]` function named `test_<name>` that parses `bytes` as a
+/// message and compares it against what it parses to now
+pub fn message_test(name: &str, bytes: &[u8]) -> Result<String> {
+    if bytes.len() < OPTIONS_OFFSET {
+        return Err(Error::ParseError("Message is shorter than the fixed header".to_owned()));
+    }
+    let message = DhcpMessage::parse(bytes)?;
+    let mut uses = BTreeSet::new();
+    let expected = message.serialize(Render { uses: &mut uses }).map_err(|e| Error::ParseError(e.0))?;
+    if let Some(ty) = uses.iter().find(|ty| !TYPE_PATHS.iter().any(|&(t, _)| t == **ty)) {
+        return Err(Error::ParseError(format!("No path for {} in TYPE_PATHS", ty)));
+    }
+
+    let mut out = String::new();
+    out.push_str("    #[test]\n");
+    out.push_str(&format!("    fn test_{}() {{\n", name));
+    for (ty, path) in TYPE_PATHS.iter() {
+        if uses.contains(ty) {
+            out.push_str(&format!("        use {};\n", path));
+        }
+    }
+    out.push_str("\n        let test_message: Vec<u8> = vec![\n");
+    write_bytes(&mut out, bytes);
+    out.push_str("        ];\n");
+    out.push_str("        assert_eq!(DhcpMessage::parse(&test_message).unwrap(), ");
+    expected.write(&mut out, 8);
+    out.push_str(");\n    }\n");
+    Ok(out)
+}
+
+/// `message_test` for the `index`th DHCP datagram in a pcap or pcapng
+/// capture, counting from 0
+#[cfg(feature = "pcap")]
+pub fn capture_test(name: &str, capture: &[u8], index: usize) -> Result<String> {
+    match pcap::datagrams(capture)?.nth(index) {
+        Some(datagram) => message_test(name, datagram?.payload),
+        None => Err(Error::ParseError(format!("The capture has no DHCP datagram {}", index))),
+    }
+}
+
+fn byte_line(out: &mut String, bytes: &[u8], comment: Option<&str>) {
+    let mut line = String::from("            ");
+    for b in bytes {
+        line.push_str(&format!("{}, ", b));
+    }
+    let mut line = line.trim_end().to_owned();
+    if let Some(comment) = comment {
+        while line.len() < COMMENT_COLUMN {
+            line.push(' ');
+        }
+        line.push_str("// ");
+        line.push_str(comment);
+    }
+    out.push_str(&line);
+    out.push('\n');
+}
+
+/// Lines of 8 with a gap every 32 bytes, commenting the last line
+fn byte_block(out: &mut String, bytes: &[u8], comment: &str) {
+    let lines: Vec<&[u8]> = bytes.chunks(8).collect();
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 && i % 4 == 0 {
+            out.push('\n');
+        }
+        byte_line(out, line, if i + 1 == lines.len() { Some(comment) } else { None });
+    }
+}
+
+fn write_bytes(out: &mut String, bytes: &[u8]) {
+    let fields: [(&str, usize, usize); 11] = [
+        ("op", 0, 1), ("htype", 1, 2), ("hlen", 2, 3), ("hops", 3, 4),
+        ("xid", 4, 8), ("secs", 8, 10), ("flags", 10, 12),
+        ("ciaddr", 12, 16), ("yiaddr", 16, 20), ("siaddr", 20, 24), ("giaddr", 24, 28),
+    ];
+    for &(name, start, end) in fields.iter() {
+        byte_line(out, &bytes[start..end], Some(name));
+    }
+    let chaddr: Vec<&[u8]> = bytes[28..44].chunks(4).collect();
+    for (i, line) in chaddr.iter().enumerate() {
+        byte_line(out, line, if i == 3 { Some("chaddr") } else { None });
+    }
+    byte_block(out, &bytes[44..108], "sname");
+    out.push('\n');
+    byte_block(out, &bytes[108..236], "file");
+    out.push('\n');
+    byte_line(out, &bytes[236..240], Some("magic cookie"));
+
+    let mut remaining = &bytes[OPTIONS_OFFSET..];
+    while let Some((opt, rest)) = next_raw_option(remaining) {
+        let comment = match opt.code {
+            0u8 => "pad".to_owned(),
+            255u8 => "end".to_owned(),
+            code => format!("option {}", code),
+        };
+        byte_block(out, &remaining[..remaining.len() - rest.len()], &comment);
+        remaining = rest;
+    }
+    if !remaining.is_empty() {
+        byte_block(out, remaining, "trailing bytes");
+    }
+}
+
+/// Rust source for a value, before it is laid out over lines
+enum Node {
+    Atom(String),
+    /// `head(items)`, `head[items]` or `vec![items]`
+    List(String, &'static str, Vec<Node>),
+    Struct(String, Vec<(&'static str, Node)>),
+}
+
+impl Node {
+    fn inline(&self) -> Option<String> {
+        match self {
+            Node::Atom(s) => Some(s.clone()),
+            Node::List(head, close, items) => {
+                let items: Option<Vec<String>> = items.iter().map(|i| i.inline()).collect();
+                Some(format!("{}{}{}", head, items?.join(", "), close))
+            },
+            Node::Struct(..) => None,
+        }
+    }
+
+    fn is_ip_addr(&self) -> bool {
+        matches!(self, Node::List(head, ..) if head.starts_with("IpAddr::"))
+    }
+
+    fn is_byte(&self) -> bool {
+        match self {
+            Node::Atom(s) => s.parse::<u8>().is_ok(),
+            _ => false,
+        }
+    }
+
+    fn write(&self, out: &mut String, indent: usize) {
+        let pad = " ".repeat(indent + 4);
+        match self {
+            Node::List(head, _, items) if head == "vec![" && items.len() > 1
+                && items.iter().all(|i| i.is_byte() && i.inline() == items[0].inline()) => {
+                out.push_str(&format!("vec![{}; {}]", items[0].inline().unwrap_or_default(), items.len()));
+            },
+            node => match node.inline() {
+                Some(ref s) if indent + s.len() < MAX_WIDTH => out.push_str(s),
+                _ => match node {
+                    // Keep a lone argument on the same line as the call
+                    Node::List(head, close, items) if items.len() == 1 && head.ends_with('(') => {
+                        out.push_str(head);
+                        items[0].write(out, indent);
+                        out.push_str(close);
+                    },
+                    Node::List(head, close, items) => {
+                        out.push_str(head);
+                        out.push('\n');
+                        if items.iter().all(|i| i.is_byte()) {
+                            for line in items.chunks(8) {
+                                let line: Vec<String> = line.iter().filter_map(|i| i.inline()).collect();
+                                out.push_str(&format!("{}{},\n", pad, line.join(", ")));
+                            }
+                        } else {
+                            for item in items {
+                                out.push_str(&pad);
+                                item.write(out, indent + 4);
+                                out.push_str(",\n");
+                            }
+                        }
+                        out.push_str(&" ".repeat(indent));
+                        out.push_str(close);
+                    },
+                    Node::Struct(name, fields) => {
+                        out.push_str(name);
+                        out.push_str(" {\n");
+                        for &(field, ref value) in fields {
+                            out.push_str(&format!("{}{}: ", pad, field));
+                            value.write(out, indent + 4);
+                            out.push_str(",\n");
+                        }
+                        out.push_str(&" ".repeat(indent));
+                        out.push('}');
+                    },
+                    Node::Atom(s) => out.push_str(s),
+                },
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct RenderError(String);
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl ::std::error::Error for RenderError {}
+
+impl ser::Error for RenderError {
+    fn custom<T: Display>(msg: T) -> RenderError {
+        RenderError(msg.to_string())
+    }
+}
+
+/// Serializes values to `Node`s, noting the types it names in `uses`
+struct Render<'r> {
+    uses: &'r mut BTreeSet<&'static str>,
+}
+
+impl<'r> Render<'r> {
+    fn name(&mut self, name: &'static str) -> &'static str {
+        self.uses.insert(name);
+        name
+    }
+
+    fn reborrow(&mut self) -> Render<'_> {
+        Render { uses: self.uses }
+    }
+
+    fn atom<T: Display>(value: T) -> ::std::result::Result<Node, RenderError> {
+        Ok(Node::Atom(value.to_string()))
+    }
+}
+
+/// Collects the items of a list, then finishes it off in `end`
+struct ListRender<'r> {
+    render: Render<'r>,
+    head: String,
+    close: &'static str,
+    items: Vec<Node>,
+}
+
+impl<'r> ListRender<'r> {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> ::std::result::Result<(), RenderError> {
+        let node = value.serialize(self.render.reborrow())?;
+        self.items.push(node);
+        Ok(())
+    }
+
+    fn finish(mut self) -> ::std::result::Result<Node, RenderError> {
+        // Arrays are serialized as tuples, and addresses as arrays of
+        // octets when the output isn't human readable
+        if self.head == "(" && self.items.iter().all(|i| i.is_byte()) {
+            return Ok(match self.items.len() {
+                4 => Node::List(format!("{}::new(", self.render.name("Ipv4Addr")), ")", self.items),
+                16 => Node::List(format!("{}::from([", self.render.name("Ipv6Addr")), "])", self.items),
+                _ => Node::List("[".to_owned(), "]", self.items),
+            });
+        }
+        // Address lists are `IpAddrList`s, which `vec!` doesn't build with
+        // the `smallvec` feature on
+        if self.head == "vec![" && !self.items.is_empty() && self.items.iter().all(Node::is_ip_addr) {
+            return Ok(Node::List("ip_list![".to_owned(), "]", self.items));
+        }
+        Ok(Node::List(self.head, self.close, self.items))
+    }
+}
+
+struct StructRender<'r> {
+    render: Render<'r>,
+    name: String,
+    fields: Vec<(&'static str, Node)>,
+}
+
+impl<'r> StructRender<'r> {
+    fn push<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> ::std::result::Result<(), RenderError> {
+        let node = value.serialize(self.render.reborrow())?;
+        self.fields.push((key, node));
+        Ok(())
+    }
+}
+
+macro_rules! list_impls(
+    ($($trait_:ident :: $method:ident),*) => ($(
+        impl<'r> ser::$trait_ for ListRender<'r> {
+            type Ok = Node;
+            type Error = RenderError;
+
+            fn $method<T: ?Sized + Serialize>(&mut self, value: &T) -> ::std::result::Result<(), RenderError> {
+                self.push(value)
+            }
+
+            fn end(self) -> ::std::result::Result<Node, RenderError> {
+                self.finish()
+            }
+        }
+    )*)
+);
+
+list_impls!(SerializeSeq::serialize_element, SerializeTuple::serialize_element,
+            SerializeTupleStruct::serialize_field, SerializeTupleVariant::serialize_field);
+
+macro_rules! struct_impls(
+    ($($trait_:ident),*) => ($(
+        impl<'r> ser::$trait_ for StructRender<'r> {
+            type Ok = Node;
+            type Error = RenderError;
+
+            fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T)
+                                                      -> ::std::result::Result<(), RenderError> {
+                self.push(key, value)
+            }
+
+            fn end(self) -> ::std::result::Result<Node, RenderError> {
+                Ok(Node::Struct(self.name, self.fields))
+            }
+        }
+    )*)
+);
+
+struct_impls!(SerializeStruct, SerializeStructVariant);
+
+impl<'r> Serializer for Render<'r> {
+    type Ok = Node;
+    type Error = RenderError;
+    type SerializeSeq = ListRender<'r>;
+    type SerializeTuple = ListRender<'r>;
+    type SerializeTupleStruct = ListRender<'r>;
+    type SerializeTupleVariant = ListRender<'r>;
+    type SerializeMap = ser::Impossible<Node, RenderError>;
+    type SerializeStruct = StructRender<'r>;
+    type SerializeStructVariant = StructRender<'r>;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> ::std::result::Result<Node, RenderError> { Render::atom(v) }
+    fn serialize_i8(self, v: i8) -> ::std::result::Result<Node, RenderError> { Render::atom(v) }
+    fn serialize_i16(self, v: i16) -> ::std::result::Result<Node, RenderError> { Render::atom(v) }
+    fn serialize_i32(self, v: i32) -> ::std::result::Result<Node, RenderError> { Render::atom(v) }
+    fn serialize_i64(self, v: i64) -> ::std::result::Result<Node, RenderError> { Render::atom(v) }
+    fn serialize_u8(self, v: u8) -> ::std::result::Result<Node, RenderError> { Render::atom(v) }
+    fn serialize_u16(self, v: u16) -> ::std::result::Result<Node, RenderError> { Render::atom(v) }
+    fn serialize_u32(self, v: u32) -> ::std::result::Result<Node, RenderError> { Render::atom(v) }
+    fn serialize_u64(self, v: u64) -> ::std::result::Result<Node, RenderError> { Render::atom(v) }
+    fn serialize_f32(self, v: f32) -> ::std::result::Result<Node, RenderError> { Render::atom(format!("{:?}f32", v)) }
+    fn serialize_f64(self, v: f64) -> ::std::result::Result<Node, RenderError> { Render::atom(format!("{:?}f64", v)) }
+    fn serialize_char(self, v: char) -> ::std::result::Result<Node, RenderError> { Render::atom(format!("{:?}", v)) }
+
+    fn serialize_str(self, v: &str) -> ::std::result::Result<Node, RenderError> {
+        Render::atom(format!("{:?}.to_string()", v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> ::std::result::Result<Node, RenderError> {
+        Ok(Node::List("vec![".to_owned(), "]", v.iter().map(|b| Node::Atom(b.to_string())).collect()))
+    }
+
+    fn serialize_none(self) -> ::std::result::Result<Node, RenderError> {
+        Render::atom("None")
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> ::std::result::Result<Node, RenderError> {
+        Ok(Node::List("Some(".to_owned(), ")", vec![value.serialize(self)?]))
+    }
+
+    fn serialize_unit(self) -> ::std::result::Result<Node, RenderError> {
+        Render::atom("()")
+    }
+
+    fn serialize_unit_struct(mut self, name: &'static str) -> ::std::result::Result<Node, RenderError> {
+        Render::atom(self.name(name))
+    }
+
+    fn serialize_unit_variant(mut self, name: &'static str, _index: u32, variant: &'static str)
+                              -> ::std::result::Result<Node, RenderError> {
+        Render::atom(format!("{}::{}", self.name(name), variant))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(mut self, name: &'static str, value: &T)
+                                                       -> ::std::result::Result<Node, RenderError> {
+        let head = format!("{}(", self.name(name));
+        Ok(Node::List(head, ")", vec![value.serialize(self)?]))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(mut self, name: &'static str, _index: u32,
+                                                        variant: &'static str, value: &T)
+                                                        -> ::std::result::Result<Node, RenderError> {
+        let head = format!("{}::{}(", self.name(name), variant);
+        Ok(Node::List(head, ")", vec![value.serialize(self)?]))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> ::std::result::Result<ListRender<'r>, RenderError> {
+        Ok(ListRender { render: self, head: "vec![".to_owned(), close: "]", items: Vec::new() })
+    }
+
+    fn serialize_tuple(self, _len: usize) -> ::std::result::Result<ListRender<'r>, RenderError> {
+        Ok(ListRender { render: self, head: "(".to_owned(), close: ")", items: Vec::new() })
+    }
+
+    fn serialize_tuple_struct(mut self, name: &'static str, _len: usize)
+                              -> ::std::result::Result<ListRender<'r>, RenderError> {
+        let head = format!("{}(", self.name(name));
+        Ok(ListRender { render: self, head, close: ")", items: Vec::new() })
+    }
+
+    fn serialize_tuple_variant(mut self, name: &'static str, _index: u32, variant: &'static str, _len: usize)
+                               -> ::std::result::Result<ListRender<'r>, RenderError> {
+        let head = format!("{}::{}(", self.name(name), variant);
+        Ok(ListRender { render: self, head, close: ")", items: Vec::new() })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> ::std::result::Result<Self::SerializeMap, RenderError> {
+        Err(RenderError("Maps can't be written as Rust source".to_owned()))
+    }
+
+    fn serialize_struct(mut self, name: &'static str, _len: usize)
+                        -> ::std::result::Result<StructRender<'r>, RenderError> {
+        let name = self.name(name).to_owned();
+        Ok(StructRender { render: self, name, fields: Vec::new() })
+    }
+
+    fn serialize_struct_variant(mut self, name: &'static str, _index: u32, variant: &'static str, _len: usize)
+                                -> ::std::result::Result<StructRender<'r>, RenderError> {
+        let name = format!("{}::{}", self.name(name), variant);
+        Ok(StructRender { render: self, name, fields: Vec::new() })
+    }
+}
+
+#[cfg(test)] mod tests {
+    use super::{message_test, TYPE_PATHS};
+    use DhcpMessage;
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, DhcpMessageTypes};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn test_message_test() {
+        let msg = DhcpMessage {
+            op: Op::BootReply,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(192, 168, 1, 100),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options: vec![
+                DhcpOption::MessageType(DhcpMessageTypes::Offer),
+                DhcpOption::Router(ip_list![IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))]),
+                DhcpOption::End,
+            ],
+        };
+        let test = message_test("offer", &msg.encode()).unwrap();
+        assert!(test.starts_with("    #[test]\n    fn test_offer() {\n"));
+        assert!(test.contains("        use options::DhcpOption;\n"));
+        assert!(test.contains("            192, 168, 1, 100,                       // yiaddr\n"));
+        assert!(test.contains("            53, 1, 2,                               // option 53\n"));
+        assert!(test.contains("            sname: vec![0; 64],\n"));
+        assert!(test.contains("            yiaddr: Ipv4Addr::new(192, 168, 1, 100),\n"));
+        assert!(test.contains("DhcpOption::Router(ip_list![IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))]),\n"));
+        assert!(test.ends_with("        });\n    }\n"));
+    }
+
+    /// Sources with the types a `DhcpMessage` serializes through
+    const SOURCES: [&str; 11] = [
+        include_str!("lib.rs"),
+        include_str!("op.rs"),
+        include_str!("htype.rs"),
+        include_str!("hwaddr.rs"),
+        include_str!("options/mod.rs"),
+        include_str!("options/option82.rs"),
+        include_str!("options/netware.rs"),
+        include_str!("options/subnet_alloc.rs"),
+        include_str!("options/civic.rs"),
+        include_str!("options/geoconf.rs"),
+        include_str!("options/code.rs"),
+    ];
+
+    /// Serializable types that never appear inside a message
+    const NOT_IN_MESSAGES: [&str; 1] = ["OptionCode"];
+
+    #[test]
+    fn test_type_paths_cover_serialized_types() {
+        for source in SOURCES.iter() {
+            let mut serialized = false;
+            for line in source.lines().map(str::trim) {
+                if line.starts_with("#[") {
+                    serialized |= line.contains("Serialize");
+                    continue;
+                }
+                let decl = line.strip_prefix("pub enum ").or_else(|| line.strip_prefix("pub struct "));
+                if let (true, Some(decl)) = (serialized, decl) {
+                    let name: String = decl.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+                    assert!(TYPE_PATHS.iter().any(|&(t, _)| t == name) || NOT_IN_MESSAGES.contains(&&name[..]),
+                            "{} is serializable but has no path in TYPE_PATHS", name);
+                }
+                serialized = false;
+            }
+        }
+    }
+}
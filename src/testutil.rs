@@ -0,0 +1,158 @@
+//! Loading captured messages from a directory of fixtures, for tests
+//!
+//! Files ending in `.hex` or `.txt` hold a hex dump: pairs of hex digits,
+//! with any whitespace, `:` or `-` between them and `#` starting a
+//! comment that runs to the end of the line. Any other file is read as
+//! the raw message bytes. Real captures can be dropped into a corpus
+//! directory and checked with
+//!
+//! ```no_run
+//! dhcp_parser::testutil::assert_corpus_round_trips("tests/corpus");
+//! ```
+
+// Failing the calling test is the point here.
+#![allow(clippy::panic)]
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use DhcpMessage;
+use roundtrip::check_message_round_trip;
+
+/// A fixture file and the message bytes in it
+#[derive(Debug, PartialEq, Clone)]
+pub struct Fixture {
+    pub path: PathBuf,
+    pub bytes: Vec<u8>,
+}
+
+fn invalid_data(path: &Path, msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), msg))
+}
+
+/// The bytes of a hex dump as described in the module docs
+pub fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    let mut digits = Vec::new();
+    for line in text.lines() {
+        let line = match line.find('#') {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+        for c in line.chars() {
+            match c.to_digit(16) {
+                Some(digit) => digits.push(digit as u8),
+                None if c.is_whitespace() || c == ':' || c == '-' => {},
+                None => return None,
+            }
+        }
+    }
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    Some(digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect())
+}
+
+/// Read one fixture, decoding it if it's a hex dump
+pub fn load_fixture<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+    let path = path.as_ref();
+    let is_hex = matches!(path.extension().and_then(|e| e.to_str()), Some("hex") | Some("txt"));
+    if !is_hex {
+        return fs::read(path);
+    }
+    let text = fs::read_to_string(path)?;
+    decode_hex(&text).ok_or_else(|| invalid_data(path, "not a hex dump"))
+}
+
+/// Every file directly in `dir`, ordered by name. Hidden files are left
+/// out, so a corpus can have a `.gitignore` or a README as `.README`.
+pub fn load_corpus<P: AsRef<Path>>(dir: P) -> io::Result<Vec<Fixture>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path.file_name().and_then(|n| n.to_str()).is_none_or(|n| n.starts_with('.'));
+        if path.is_file() && !hidden {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths.into_iter().map(|path| {
+        let bytes = load_fixture(&path)?;
+        Ok(Fixture { path, bytes })
+    }).collect()
+}
+
+/// Each fixture in `dir` must parse as a message that encodes and parses
+/// back to the same message. Panics listing every fixture that doesn't,
+/// or if `dir` can't be read or is empty.
+pub fn assert_corpus_round_trips<P: AsRef<Path>>(dir: P) {
+    let dir = dir.as_ref();
+    let corpus = match load_corpus(dir) {
+        Ok(corpus) => corpus,
+        Err(e) => panic!("Could not load corpus {}: {}", dir.display(), e),
+    };
+    assert!(!corpus.is_empty(), "Corpus {} is empty", dir.display());
+    let failures: Vec<String> = corpus.iter().filter_map(|fixture| {
+        DhcpMessage::parse(&fixture.bytes)
+            .and_then(|msg| check_message_round_trip(&msg))
+            .err()
+            .map(|e| format!("{}: {}", fixture.path.display(), e))
+    }).collect();
+    assert!(failures.is_empty(), "{} of {} fixtures failed:\n{}", failures.len(), corpus.len(), failures.join("\n"));
+}
+
+#[cfg(test)] mod tests {
+    use super::{decode_hex, load_corpus, assert_corpus_round_trips};
+    use std::env;
+    use std::fs;
+    use std::net::Ipv4Addr;
+    use DhcpMessage;
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, DhcpMessageTypes};
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("# op, htype\n01 01\n0a:0b-0C # more\n"), Some(vec![1u8, 1u8, 10u8, 11u8, 12u8]));
+        assert_eq!(decode_hex("012"), None);
+        assert_eq!(decode_hex("0x01"), None);
+    }
+
+    #[test]
+    fn test_corpus() {
+        let msg = DhcpMessage {
+            op: Op::BootRequest,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 0x3903f326,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(0, 0, 0, 0),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            sname: vec![0; 64],
+            file: vec![0; 128],
+            options: vec![DhcpOption::MessageType(DhcpMessageTypes::Discover), DhcpOption::End],
+        };
+        let bytes = msg.encode();
+        let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+        let dir = env::temp_dir().join(format!("dhcp_parser_corpus_{}", ::std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a_discover.bin"), &bytes).unwrap();
+        fs::write(dir.join("b_discover.hex"), format!("# a discover\n{}\n", hex.join(" "))).unwrap();
+        fs::write(dir.join(".gitignore"), "*.tmp\n").unwrap();
+
+        let corpus = load_corpus(&dir).unwrap();
+        assert_eq!(corpus.len(), 2);
+        assert!(corpus.iter().all(|fixture| fixture.bytes == bytes));
+        assert_corpus_round_trips(&dir);
+
+        fs::write(dir.join("c_truncated.bin"), &bytes[..100]).unwrap();
+        let result = ::std::panic::catch_unwind(|| assert_corpus_round_trips(&dir));
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_err());
+    }
+}
@@ -3,7 +3,7 @@ use nom::{IResult};
 use super::{Result, Error};
 
 pub fn take_rest(input: &[u8]) -> IResult<&[u8], &[u8]> {
-    IResult::Done(&input[input.len()..], input)
+    Ok((&[], input))
 }
 
 #[allow(dead_code)]
@@ -12,7 +12,7 @@ pub fn null_terminated_slice_to_string(bytes: &[u8]) -> Result<&str> {
         Some(p) => p,
         None => return Err(Error::ParseError("NO NULL TERMINATION FOUND".into())),
     };
-    match str::from_utf8(&bytes[0..pos]) {
+    match str::from_utf8(bytes.split_at(pos).0) {
         Ok(s) => Ok(s),
         Err(_) => Err(Error::ParseError("Could not get utf8 from bytes".into())),
     }
@@ -22,7 +22,6 @@ pub fn null_terminated_slice_to_string(bytes: &[u8]) -> Result<&str> {
 #[cfg(test)] mod tests {
 
 use super::{take_rest};
-use nom::{IResult};
 use std::str;
 
 #[test]
@@ -36,7 +35,7 @@ fn test_take_rest() {
         )
     );
 
-    assert_eq!(parts(b"abcd:thisistherestofthestring"), IResult::Done(&b""[..], ("abcd", "thisistherestofthestring")));
+    assert_eq!(parts(b"abcd:thisistherestofthestring"), Ok((&b""[..], ("abcd", "thisistherestofthestring"))));
 }
 
 
@@ -0,0 +1,294 @@
+//! RFC 2131 rules for what each message type carries
+//!
+//! `validate` checks a message against the option tables in RFC 2131
+//! (table 3 for server messages, table 5 for client messages) and the
+//! related header rules from section 4, e.g. that an OFFER offers an
+//! address for a non-zero lease. Only MUST and MUST NOT are checked.
+//!
+//! Servers echo option 61 per RFC 6842 and relays add option 82 to any
+//! message (RFC 3046), so neither is ever reported.
+
+use std::net::Ipv4Addr;
+use DhcpMessage;
+use op::Op;
+use options::{DhcpOption, DhcpMessageTypes};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Violation {
+    /// There is no option 53, so no other rule can be checked
+    MissingMessageType,
+    /// `op` is BOOTREPLY in a client message or BOOTREQUEST in a server one
+    WrongOp,
+    /// An option the message type requires
+    MissingOption(u8),
+    /// An option the message type doesn't allow
+    ForbiddenOption(u8),
+    /// Option 51 is zero in an OFFER or ACK
+    ZeroLeaseTime,
+    /// `yiaddr` is zero in an OFFER
+    MissingYiaddr,
+    /// `ciaddr` is zero where the client has to fill it in
+    MissingCiaddr,
+    /// `ciaddr` is set where it has to be zero
+    UnexpectedCiaddr,
+}
+
+struct Rules {
+    op: Op,
+    required: &'static [u8],
+    forbidden: &'static [u8],
+    /// For message types where "all others" are MUST NOT, every option
+    /// that may appear
+    allowed: Option<&'static [u8]>,
+}
+
+fn rules(message_type: DhcpMessageTypes) -> Option<Rules> {
+    use options::DhcpMessageTypes::*;
+    let rules = match message_type {
+        Discover => Rules { op: Op::BootRequest, required: &[53], forbidden: &[54], allowed: None },
+        Request => Rules { op: Op::BootRequest, required: &[53], forbidden: &[], allowed: None },
+        Decline => Rules {
+            op: Op::BootRequest,
+            required: &[50, 53, 54],
+            forbidden: &[],
+            allowed: Some(&[50, 52, 53, 54, 56, 61, 82]),
+        },
+        Release => Rules {
+            op: Op::BootRequest,
+            required: &[53, 54],
+            forbidden: &[],
+            allowed: Some(&[52, 53, 54, 56, 61, 82]),
+        },
+        Offer => Rules { op: Op::BootReply, required: &[51, 53, 54], forbidden: &[50, 55, 57], allowed: None },
+        Ack => Rules { op: Op::BootReply, required: &[53, 54], forbidden: &[50, 55, 57], allowed: None },
+        Nak => Rules {
+            op: Op::BootReply,
+            required: &[53, 54],
+            forbidden: &[],
+            allowed: Some(&[53, 54, 56, 60, 61, 82]),
+        },
+        Other(_) => return None,
+    };
+    Some(rules)
+}
+
+/// Check `message` against the rules for its message type. Returns
+/// every violation found, or an empty list for a conforming message or
+/// one whose message type RFC 2131 doesn't cover.
+pub fn validate(message: &DhcpMessage) -> Vec<Violation> {
+    let message_type = message.options.iter().filter_map(|o| match o {
+        &DhcpOption::MessageType(t) => Some(t),
+        _ => None,
+    }).next();
+    let message_type = match message_type {
+        Some(t) => t,
+        None => return vec![Violation::MissingMessageType],
+    };
+    let rules = match rules(message_type) {
+        Some(rules) => rules,
+        None => return Vec::new(),
+    };
+
+    let mut codes: Vec<u8> = Vec::new();
+    for code in message.options.iter().map(DhcpOption::code) {
+        if code != 0u8 && code != 255u8 && !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+    let has = |code: u8| codes.contains(&code);
+    let unspecified = Ipv4Addr::new(0, 0, 0, 0);
+
+    let mut violations = Vec::new();
+    if message.op != rules.op {
+        violations.push(Violation::WrongOp);
+    }
+    let mut required = rules.required.to_vec();
+    let mut forbidden = rules.forbidden.to_vec();
+    match message_type {
+        DhcpMessageTypes::Request => {
+            // SELECTING names the server and the offered address;
+            // INIT-REBOOT only the address; RENEWING and REBINDING
+            // neither, and identify the client by ciaddr instead
+            if has(54) {
+                required.push(50);
+            }
+            if has(54) || has(50) {
+                if message.ciaddr != unspecified {
+                    violations.push(Violation::UnexpectedCiaddr);
+                }
+            } else if message.ciaddr == unspecified {
+                violations.push(Violation::MissingCiaddr);
+            }
+        },
+        DhcpMessageTypes::Discover | DhcpMessageTypes::Decline if message.ciaddr != unspecified => {
+            violations.push(Violation::UnexpectedCiaddr);
+        },
+        DhcpMessageTypes::Release if message.ciaddr == unspecified => {
+            violations.push(Violation::MissingCiaddr);
+        },
+        DhcpMessageTypes::Offer if message.yiaddr == unspecified => {
+            violations.push(Violation::MissingYiaddr);
+        },
+        DhcpMessageTypes::Ack => {
+            // An ACK to an INFORM has no address, and so no lease
+            if message.yiaddr == unspecified {
+                forbidden.push(51);
+            } else {
+                required.push(51);
+            }
+        },
+        _ => {},
+    }
+
+    for &code in &required {
+        if !has(code) {
+            violations.push(Violation::MissingOption(code));
+        }
+    }
+    for &code in &codes {
+        let allowed = rules.allowed.is_none_or(|allowed| allowed.contains(&code));
+        if !allowed || forbidden.contains(&code) {
+            violations.push(Violation::ForbiddenOption(code));
+        }
+    }
+    let offers_lease = message_type == DhcpMessageTypes::Offer || message_type == DhcpMessageTypes::Ack;
+    if offers_lease && message.options.contains(&DhcpOption::IpAddressLeaseTime(0)) {
+        violations.push(Violation::ZeroLeaseTime);
+    }
+    violations
+}
+
+#[cfg(test)] mod tests {
+    use super::{validate, Violation};
+    use DhcpMessage;
+    use op::Op;
+    use htype::Htype;
+    use options::{DhcpOption, DhcpMessageTypes};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn message(op: Op, options: Vec<DhcpOption>) -> DhcpMessage {
+        DhcpMessage {
+            op,
+            htype: Htype::Ethernet_10mb,
+            hlen: 6,
+            hops: 0,
+            xid: 1,
+            secs: 0,
+            flags: 0,
+            ciaddr: Ipv4Addr::new(0, 0, 0, 0),
+            yiaddr: Ipv4Addr::new(0, 0, 0, 0),
+            siaddr: Ipv4Addr::new(0, 0, 0, 0),
+            giaddr: Ipv4Addr::new(0, 0, 0, 0),
+            chaddr: vec![0, 11, 130, 1, 252, 66],
+            sname: vec![],
+            file: vec![],
+            options,
+        }
+    }
+
+    fn server() -> DhcpOption {
+        DhcpOption::ServerIdentifier(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)))
+    }
+
+    fn requested() -> DhcpOption {
+        DhcpOption::RequestedIpAddress(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)))
+    }
+
+    #[test]
+    fn test_valid_exchange() {
+        let discover = message(Op::BootRequest, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Discover),
+            DhcpOption::ParamRequestList(vec![1, 3, 6]),
+            DhcpOption::End,
+        ]);
+        assert_eq!(validate(&discover), vec![]);
+
+        let mut offer = message(Op::BootReply, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Offer),
+            server(),
+            DhcpOption::IpAddressLeaseTime(3600),
+            DhcpOption::End,
+        ]);
+        offer.yiaddr = Ipv4Addr::new(10, 0, 0, 5);
+        assert_eq!(validate(&offer), vec![]);
+
+        let request = message(Op::BootRequest, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Request),
+            server(),
+            requested(),
+            DhcpOption::End,
+        ]);
+        assert_eq!(validate(&request), vec![]);
+    }
+
+    #[test]
+    fn test_server_message_violations() {
+        let offer = message(Op::BootRequest, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Offer),
+            DhcpOption::IpAddressLeaseTime(0),
+            DhcpOption::MaxMessageSize(1500),
+        ]);
+        assert_eq!(validate(&offer), vec![
+            Violation::WrongOp,
+            Violation::MissingYiaddr,
+            Violation::MissingOption(54),
+            Violation::ForbiddenOption(57),
+            Violation::ZeroLeaseTime,
+        ]);
+
+        let nak = message(Op::BootReply, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Nak),
+            server(),
+            DhcpOption::Message("wrong network".to_string()),
+            DhcpOption::DomainName("example.com".to_string()),
+        ]);
+        assert_eq!(validate(&nak), vec![Violation::ForbiddenOption(15)]);
+
+        let inform_ack = message(Op::BootReply, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Ack),
+            server(),
+            DhcpOption::IpAddressLeaseTime(3600),
+        ]);
+        assert_eq!(validate(&inform_ack), vec![Violation::ForbiddenOption(51)]);
+    }
+
+    #[test]
+    fn test_request_states() {
+        let selecting = message(Op::BootRequest, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Request),
+            server(),
+        ]);
+        assert_eq!(validate(&selecting), vec![Violation::MissingOption(50)]);
+
+        let mut renewing = message(Op::BootRequest, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Request),
+        ]);
+        assert_eq!(validate(&renewing), vec![Violation::MissingCiaddr]);
+        renewing.ciaddr = Ipv4Addr::new(10, 0, 0, 5);
+        assert_eq!(validate(&renewing), vec![]);
+
+        let mut init_reboot = message(Op::BootRequest, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Request),
+            requested(),
+        ]);
+        init_reboot.ciaddr = Ipv4Addr::new(10, 0, 0, 5);
+        assert_eq!(validate(&init_reboot), vec![Violation::UnexpectedCiaddr]);
+    }
+
+    #[test]
+    fn test_client_message_violations() {
+        let release = message(Op::BootRequest, vec![
+            DhcpOption::MessageType(DhcpMessageTypes::Release),
+            DhcpOption::ParamRequestList(vec![1]),
+            DhcpOption::RelayAgentInformation(vec![]),
+        ]);
+        assert_eq!(validate(&release), vec![
+            Violation::MissingCiaddr,
+            Violation::MissingOption(54),
+            Violation::ForbiddenOption(55),
+        ]);
+
+        assert_eq!(validate(&message(Op::BootRequest, vec![DhcpOption::End])),
+                   vec![Violation::MissingMessageType]);
+    }
+}
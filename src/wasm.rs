@@ -0,0 +1,25 @@
+//! `wasm-bindgen` bindings, for decoding in the browser
+//!
+//! Messages and options are handed to JavaScript as JSON, as laid out by
+//! `json::to_json_pretty`. Errors are thrown as strings.
+
+use wasm_bindgen::prelude::*;
+use {DhcpMessage, Error};
+use json::to_json_pretty;
+use options;
+
+fn js_error(e: Error) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+/// Decode a whole message, as sent over UDP
+#[wasm_bindgen(js_name = decodeMessage)]
+pub fn decode_message(bytes: &[u8]) -> ::std::result::Result<String, JsValue> {
+    DhcpMessage::parse(bytes).and_then(|msg| to_json_pretty(&msg)).map_err(js_error)
+}
+
+/// Decode an options area, starting after the magic cookie
+#[wasm_bindgen(js_name = decodeOptions)]
+pub fn decode_options(bytes: &[u8]) -> ::std::result::Result<String, JsValue> {
+    options::parse(bytes).and_then(|opts| to_json_pretty(&opts)).map_err(js_error)
+}